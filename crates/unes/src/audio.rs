@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+// cpu (and apu) clock of the ntsc console
+pub const NTSC_CLOCK: f64 = 1_789_773.0;
+pub const SAMPLE_RATE: u32 = 48_000;
+
+// taps of the band-limited step, the kernel is kept for 32 fractional positions
+const WIDTH: usize = 32;
+const PHASES: usize = 32;
+// a bit below nyquist so the transition band ends before it
+const CUTOFF: f64 = 0.9;
+
+// first order filter, the console has two high-passes and a low-pass between the dacs and the jack
+#[derive(Clone, Copy)]
+struct Filter {
+    high_pass: bool,
+    alpha: f64,
+    input: f64,
+    output: f64
+}
+impl Filter {
+    fn new(high_pass: bool, frequency: f64, sample_rate: f64) -> Filter {
+        let rc = 1.0 / (2.0 * PI * frequency);
+        let dt = 1.0 / sample_rate;
+        let alpha = if high_pass { rc / (rc + dt) } else { dt / (rc + dt) };
+        Filter { high_pass, alpha, input: 0.0, output: 0.0 }
+    }
+    fn process(&mut self, input: f64) -> f64 {
+        self.output = if self.high_pass {
+            self.alpha * (self.output + input - self.input)
+        } else {
+            self.output + self.alpha * (input - self.output)
+        };
+        self.input = input;
+        self.output
+    }
+}
+
+// Turns the amplitude the apu produces every cpu cycle into samples at the host rate.
+// Every change of the amplitude adds a band-limited step (a windowed sinc impulse,
+// integrated when the sample goes out) so nothing above nyquist aliases back. Samples
+// are delayed by half the kernel width.
+pub struct AudioOutput {
+    pub clock_rate: f64,
+    pub sample_rate: u32,
+    // 90Hz and 440Hz high-pass and 14kHz low-pass
+    pub filtering: bool,
+    kernel: Vec<[f64; WIDTH]>,
+    filters: [Filter; 3],
+    // output samples per clock
+    step: f64,
+    // position of the current clock between the first two pending samples
+    time: f64,
+    amplitude: f32,
+    pending: VecDeque<f64>,
+    level: f64,
+    samples: Vec<f32>
+}
+impl Default for AudioOutput {
+    fn default() -> Self {
+        AudioOutput::new(NTSC_CLOCK, SAMPLE_RATE)
+    }
+}
+impl AudioOutput {
+    pub fn new(clock_rate: f64, sample_rate: u32) -> AudioOutput {
+        let mut output = AudioOutput {
+            clock_rate,
+            sample_rate,
+            filtering: true,
+            kernel: kernel(),
+            filters: [Filter::new(true, 0.0, 1.0); 3],
+            step: 0.0,
+            time: 0.0,
+            amplitude: 0.0,
+            pending: VecDeque::from(vec![0.0; WIDTH + 1]),
+            level: 0.0,
+            samples: Vec::new()
+        };
+        output.set_rates(clock_rate, sample_rate);
+        output
+    }
+    // resets the filters, the samples already produced are kept
+    pub fn set_rates(&mut self, clock_rate: f64, sample_rate: u32) {
+        let rate = sample_rate as f64;
+        self.clock_rate = clock_rate;
+        self.sample_rate = sample_rate;
+        self.step = rate / clock_rate;
+        self.filters = [Filter::new(true, 90.0, rate), Filter::new(true, 440.0, rate), Filter::new(false, 14_000.0, rate)];
+    }
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.set_rates(self.clock_rate, sample_rate);
+    }
+    // one clock with the amplitude the apu outputs during it
+    pub fn clock(&mut self, amplitude: f32) {
+        if amplitude != self.amplitude {
+            self.add_step(amplitude - self.amplitude);
+            self.amplitude = amplitude;
+        }
+        self.time += self.step;
+        if self.time >= 1.0 {
+            self.time -= 1.0;
+            self.push_sample();
+        }
+    }
+    fn add_step(&mut self, delta: f32) {
+        let phase = ((self.time * PHASES as f64) as usize).min(PHASES - 1);
+        for (pending, tap) in self.pending.iter_mut().zip(&self.kernel[phase]) {
+            *pending += delta as f64 * tap;
+        }
+    }
+    fn push_sample(&mut self) {
+        self.level += self.pending.pop_front().unwrap_or(0.0);
+        self.pending.push_back(0.0);
+        let mut sample = self.level;
+        if self.filtering {
+            for filter in &mut self.filters { sample = filter.process(sample) }
+        }
+        self.samples.push(sample as f32);
+    }
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+    // the samples produced since the last take
+    pub fn take_f32(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.samples)
+    }
+    pub fn take_i16(&mut self) -> Vec<i16> {
+        self.take_f32().into_iter().map(to_i16).collect()
+    }
+}
+
+pub fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+// windowed sinc impulses, each phase shifted by a fraction of a sample and summing to 1
+fn kernel() -> Vec<[f64; WIDTH]> {
+    (0..PHASES).map(|phase| {
+        let mut taps = [0.0; WIDTH];
+        for (i, tap) in taps.iter_mut().enumerate() {
+            let x = i as f64 - (WIDTH / 2) as f64 + 1.0 - phase as f64 / PHASES as f64;
+            let sinc = if x == 0.0 { 1.0 } else { (PI * CUTOFF * x).sin() / (PI * CUTOFF * x) };
+            // blackman window over the width of the kernel
+            let w = (x + (WIDTH / 2) as f64) / WIDTH as f64;
+            let window = 0.42 - 0.5 * (2.0 * PI * w).cos() + 0.08 * (4.0 * PI * w).cos();
+            *tap = sinc * window;
+        }
+        let sum: f64 = taps.iter().sum();
+        taps.map(|tap| tap / sum)
+    }).collect()
+}
\ No newline at end of file
@@ -0,0 +1,197 @@
+use unes_apu::APU;
+use unes_cartridge::Cartridge;
+use unes_cpu::Bus;
+use unes_ppu::PPU;
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::AudioOutput;
+use crate::input::Input;
+use crate::mixer::Mixer;
+use crate::region::Region;
+
+const OAM_DATA: u16 = 0x2004;
+
+// the console as seen from the cpu
+// $0000-$1fff 2KB of ram, mirrored
+// $2000-$3fff ppu registers, mirrored every 8 bytes
+// $4000-$401f apu and io
+// $4020-$ffff cartridge
+pub struct NesBus {
+    pub ram: [u8; 0x800],
+    pub ppu: PPU,
+    pub apu: APU,
+    pub mixer: Mixer,
+    pub audio: AudioOutput,
+    pub input: Input,
+    pub cartridge: Cartridge,
+    // last value on the data bus, unmapped reads return it
+    pub open_bus: u8,
+    // page written to $4014, copied once the cpu reads next
+    pub dma_page: Option<u8>,
+    // set by `Console::set_region`, the clock dividers depend on it
+    pub(crate) region: Region,
+    // master clock ticks since power on, and how far the ppu got
+    pub master_clock: u64,
+    pub ppu_clock: u64,
+    // nmi output of the ppu and the edge latched from it
+    pub nmi_line: bool,
+    pub nmi_edge: bool,
+    // what the cpu polls, sampled at the start of the last cycle
+    pub polled_nmi: bool,
+    pub polled_irq: bool
+}
+impl NesBus {
+    pub fn new(cartridge: Cartridge) -> NesBus {
+        NesBus {
+            ram: [0; 0x800],
+            ppu: PPU::new(),
+            apu: APU::new(),
+            mixer: Mixer::new(cartridge.mapper.audio()),
+            audio: AudioOutput::default(),
+            input: Input::new(),
+            cartridge,
+            open_bus: 0,
+            dma_page: None,
+            region: Region::Ntsc,
+            master_clock: 0,
+            ppu_clock: 0,
+            nmi_line: false,
+            nmi_edge: false,
+            polled_nmi: false,
+            polled_irq: false
+        }
+    }
+    // one cpu cycle worth of apu and cartridge
+    pub fn clock(&mut self) {
+        self.apu.clock();
+        let sample = self.mixer.mix(&self.apu, self.cartridge.mapper.audio());
+        self.audio.clock(sample);
+        self.cartridge.mapper.clock();
+    }
+    pub fn irq(&self) -> bool {
+        self.apu.irq() || self.cartridge.mapper.irq()
+    }
+    // the ppu runs 3 (or 3.2 on pal) dots a cpu cycle, so the access that
+    // follows sees the state it would on the console
+    fn cycle(&mut self) {
+        self.polled_nmi = self.nmi_edge;
+        self.polled_irq = self.irq();
+        self.master_clock += self.region.cpu_divider();
+        let ppu_divider = self.region.ppu_divider();
+        while self.ppu_clock + ppu_divider <= self.master_clock {
+            self.ppu_clock += ppu_divider;
+            self.ppu.tick(self.cartridge.mapper.as_mut());
+        }
+        let nmi = self.ppu.nmi_line();
+        if nmi && !self.nmi_line { self.nmi_edge = true }
+        self.nmi_line = nmi;
+        self.clock();
+    }
+    // The cpu is halted on its read of `addr`, then reads and writes alternate
+    // between get and put cycles (gets on odd cycles), a dma reads on a get.
+    // The oam dma takes 256 gets and puts after maybe aligning: 513 or 514
+    // cycles. The dmc dma takes a dummy cycle after the halt, maybe aligns
+    // and reads: 3 or 4 cycles. Once the oam dma runs it only waits for two
+    // cycles and steals a get, which costs the oam dma 2 more.
+    fn dma(&mut self, addr: u16, odd_cycle: bool) -> u16 {
+        let page = self.dma_page.take();
+        let (mut offset, mut latch) = (0, None);
+        let (mut cycles, mut dmc_wait) = (0, 0);
+        loop {
+            let dmc = self.apu.dmc.pending_read();
+            let oam = page.filter(|_| offset <= 0xff);
+            if dmc.is_none() && oam.is_none() && latch.is_none() { break }
+            let get = (cycles + odd_cycle as u16) & 1 == 1;
+            self.cycle();
+            match (dmc, oam, latch) {
+                (Some(dmc), ..) if get && dmc_wait >= 2 => {
+                    let value = self.read(dmc);
+                    self.apu.dmc.fill(value);
+                },
+                (_, Some(page), None) if get && cycles > 0 => {
+                    latch = Some(self.read((page as u16) << 8 | offset));
+                    offset += 1;
+                },
+                (.., Some(value)) if !get => {
+                    self.ppu.write_register(OAM_DATA, value, self.cartridge.mapper.as_mut());
+                    latch = None;
+                },
+                // the halted read, registers with side effects ($2007, the
+                // controllers) see it twice, the repeats after it are ignored
+                _ => if cycles == 0 && (0x2000..=0x401f).contains(&addr) { self.read(addr); }
+            }
+            if dmc.is_some() { dmc_wait += 1 }
+            cycles += 1;
+        }
+        cycles
+    }
+}
+// ram and bus latches, the ppu, apu, cartridge and input have chunks of their own
+impl Snapshot for NesBus {
+    fn save(&self, w: &mut StateWriter) {
+        self.ram.save(w);
+        self.open_bus.save(w);
+        self.dma_page.save(w);
+        self.nmi_line.save(w);
+        self.nmi_edge.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ram.load(r)?;
+        self.open_bus.load(r)?;
+        self.dma_page.load(r)?;
+        // the address the dma used to repeat, it halts on the read now
+        if r.version < 3 { r.read_u16()?; }
+        if r.version < 2 {
+            // an edge already latched went into the cpu chunk
+            self.nmi_line = true;
+            self.nmi_edge = false;
+            return Ok(())
+        }
+        self.nmi_line.load(r)?;
+        self.nmi_edge.load(r)
+    }
+}
+impl Bus for NesBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        let value = match addr {
+            0x0000..=0x1fff => Some(self.ram[addr as usize & 0x7ff]),
+            0x2000..=0x3fff => Some(self.ppu.read_register(addr, self.cartridge.mapper.as_mut())),
+            // bit 5 is not driven
+            0x4015 => Some(self.apu.read_status() | self.open_bus & 0x20),
+            // controllers drive d0-d4
+            0x4016 | 0x4017 => Some(self.input.read(addr, &self.ppu) | self.open_bus & 0xe0),
+            0x4000..=0x401f => None,
+            // the bits the board leaves floating keep the open bus value
+            _ => self.cartridge.mapper.cpu_read(addr)
+                .map(|value| value | self.open_bus & !self.cartridge.mapper.driven_bits(addr))
+        };
+        self.open_bus = value.unwrap_or(self.open_bus);
+        self.open_bus
+    }
+    fn write(&mut self, addr: u16, value: u8) {
+        self.open_bus = value;
+        match addr {
+            0x0000..=0x1fff => self.ram[addr as usize & 0x7ff] = value,
+            0x2000..=0x3fff => self.ppu.write_register(addr, value, self.cartridge.mapper.as_mut()),
+            0x4014 => self.dma_page = Some(value),
+            0x4016 => self.input.write(value),
+            0x4000..=0x4013 | 0x4015 | 0x4017 => self.apu.write_register(addr, value),
+            _ => ()
+        }
+        // mappers may watch any address, not only the cartridge space
+        self.cartridge.mapper.cpu_write(addr, value);
+    }
+    fn tick(&mut self, read: Option<u16>, odd_cycle: bool) -> u16 {
+        let stall = match read {
+            Some(addr) if self.dma_page.is_some() || self.apu.dmc.pending_read().is_some() => self.dma(addr, odd_cycle),
+            _ => 0
+        };
+        self.cycle();
+        stall
+    }
+    fn poll_interrupts(&mut self) -> Option<(bool, bool)> {
+        // an edge seen by the last cycle waits for the next instruction
+        if self.polled_nmi { self.nmi_edge = false }
+        Some((self.polled_nmi, self.polled_irq))
+    }
+}
\ No newline at end of file
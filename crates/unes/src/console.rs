@@ -0,0 +1,124 @@
+use unes_cartridge::{Cartridge, CartridgeError};
+use unes_cpu::CPU;
+use unes_state::{Chunk, Snapshot, State, StateError, StateWriter};
+
+use crate::bus::NesBus;
+use crate::palette::Palette;
+use crate::region::Region;
+
+// The whole console. The cpu runs an instruction at a time, the bus clocks
+// the ppu, apu and cartridge on each of its cycles (see `NesBus::tick`).
+pub struct Console {
+    pub cpu: CPU<NesBus>,
+    pub palette: Palette,
+    // identifies the game in save states
+    rom_hash: u64
+}
+impl Console {
+    pub fn from_rom(bytes: &[u8]) -> Result<Console, CartridgeError> {
+        let cartridge = Cartridge::from_bytes(bytes)?;
+        let mut console = Console {
+            cpu: CPU::with_bus(NesBus::new(cartridge)),
+            palette: Palette::default(),
+            rom_hash: fnv1a(bytes)
+        };
+        console.set_region(console.cpu.memory.cartridge.header.timing.into());
+        console.cpu.reset();
+        Ok(console)
+    }
+    pub fn region(&self) -> Region {
+        self.cpu.memory.region
+    }
+    // picked from the header by `from_rom`, switching later keeps the state
+    // but the ppu picks up the new frame layout from where it is
+    pub fn set_region(&mut self, region: Region) {
+        let bus = &mut self.cpu.memory;
+        bus.region = region;
+        bus.ppu.layout = region.layout();
+        bus.apu.set_pal(region == Region::Pal);
+        let sample_rate = bus.audio.sample_rate;
+        bus.audio.set_rates(region.cpu_clock(), sample_rate);
+        bus.mixer.set_rates(&bus.audio);
+    }
+    // the reset button, silences the apu and restarts the cpu
+    pub fn reset(&mut self) {
+        self.cpu.memory.apu.write_register(0x4015, 0);
+        self.cpu.reset();
+    }
+    // one instruction (or interrupt), returns the cpu cycles it took
+    pub fn step(&mut self) -> u16 {
+        self.cpu.step()
+    }
+    // runs until the ppu starts the next frame
+    pub fn run_frame(&mut self) {
+        let frame = self.cpu.memory.ppu.frame;
+        while self.cpu.memory.ppu.frame == frame { self.step(); }
+    }
+    // ppu output, palette index | emphasis << 6 (see `Palette`)
+    pub fn framebuffer(&self) -> &[u16] {
+        &self.cpu.memory.ppu.framebuffer
+    }
+    pub fn framebuffer_rgb(&self) -> Vec<u8> {
+        self.palette.to_rgb(self.framebuffer())
+    }
+    // the samples produced since the last call
+    pub fn audio_samples(&mut self) -> Vec<f32> {
+        self.cpu.memory.audio.take_f32()
+    }
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        let bus = &mut self.cpu.memory;
+        bus.audio.set_sample_rate(sample_rate);
+        bus.mixer.set_rates(&bus.audio);
+    }
+    // buttons (see `input::joypad`) of player 1 to 4 (0-3)
+    pub fn set_input(&mut self, player: usize, buttons: u8) {
+        self.cpu.memory.input.set_buttons(player, buttons);
+    }
+    // the whole machine, in the format described in `unes_state`
+    pub fn save_state(&self) -> Vec<u8> {
+        let bus = &self.cpu.memory;
+        let mut w = StateWriter::new();
+        w.chunk(Chunk::CONSOLE, |w| {
+            self.rom_hash.save(w);
+            bus.region.save(w);
+            bus.master_clock.save(w);
+            bus.ppu_clock.save(w);
+        });
+        w.chunk(Chunk::CPU, |w| self.cpu.save(w));
+        w.chunk(Chunk::BUS, |w| bus.save(w));
+        w.chunk(Chunk::PPU, |w| bus.ppu.save(w));
+        w.chunk(Chunk::APU, |w| bus.apu.save(w));
+        w.chunk(Chunk::CARTRIDGE, |w| bus.cartridge.mapper.save(w));
+        w.chunk(Chunk::INPUT, |w| bus.input.save(w));
+        w.finish()
+    }
+    // nothing changes when the state is refused
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let state = State::parse(data)?;
+        let backup = self.save_state();
+        let result = self.load_chunks(&state);
+        if result.is_err() { self.load_state(&backup)? }
+        result
+    }
+    fn load_chunks(&mut self, state: &State) -> Result<(), StateError> {
+        let mut r = state.chunk(Chunk::CONSOLE)?;
+        if r.read_u64()? != self.rom_hash { return Err(StateError::CartridgeMismatch) }
+        let mut region = self.region();
+        region.load(&mut r)?;
+        if region != self.region() { self.set_region(region) }
+        self.cpu.memory.master_clock.load(&mut r)?;
+        self.cpu.memory.ppu_clock.load(&mut r)?;
+        Snapshot::load(&mut self.cpu, &mut state.chunk(Chunk::CPU)?)?;
+        let bus = &mut self.cpu.memory;
+        bus.load(&mut state.chunk(Chunk::BUS)?)?;
+        bus.ppu.load(&mut state.chunk(Chunk::PPU)?)?;
+        bus.apu.load(&mut state.chunk(Chunk::APU)?)?;
+        bus.cartridge.mapper.load(&mut state.chunk(Chunk::CARTRIDGE)?)?;
+        bus.input.load(&mut state.chunk(Chunk::INPUT)?)
+    }
+}
+
+// 64 bit FNV-1a
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3))
+}
\ No newline at end of file
@@ -0,0 +1,57 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// Four player adapters, both send 24 bits per port: two controllers and a signature.
+// The Four Score plugs into the NES ports and shifts players 1 and 3 out of $4016 d0,
+// 2 and 4 out of $4017 d0. The Hori adapter goes into the Famicom expansion port,
+// players 1 and 2 stay on the built-in controllers and 3 and 4 come out of d1
+// followed by 8 zeros. Hori signatures are the Four Score ones swapped.
+#[derive(Clone, Copy, Default)]
+pub struct FourScore {
+    pub hori: bool,
+    pub buttons: [u8; 4],
+    strobe: bool,
+    shift: [u32; 2]
+}
+impl FourScore {
+    pub fn new() -> FourScore {
+        FourScore::default()
+    }
+    pub fn hori() -> FourScore {
+        FourScore { hori: true, ..FourScore::default() }
+    }
+    fn reload(&mut self) {
+        let [p1, p2, p3, p4] = self.buttons.map(|b| b as u32);
+        // sent lsb first: %00010000 and %00100000 in read order
+        self.shift = if self.hori {
+            [p3 | 0x04 << 16, p4 | 0x08 << 16]
+        } else {
+            [p1 | p3 << 8 | 0x08 << 16, p2 | p4 << 8 | 0x04 << 16]
+        };
+    }
+    pub fn write(&mut self, value: u8) {
+        if self.strobe || value & 1 != 0 { self.reload() }
+        self.strobe = value & 1 != 0;
+    }
+    // the data line of `port` (0 for $4016, 1 for $4017), 1s after the 24 bits
+    pub fn read(&mut self, port: usize) -> u8 {
+        if self.strobe { self.reload() }
+        let bit = (self.shift[port] & 1) as u8;
+        if !self.strobe { self.shift[port] = self.shift[port] >> 1 | 1 << 23 }
+        if self.hori { bit << 1 } else { bit }
+    }
+}
+
+impl Snapshot for FourScore {
+    fn save(&self, w: &mut StateWriter) {
+        self.hori.save(w);
+        self.buttons.save(w);
+        self.strobe.save(w);
+        self.shift.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.hori.load(r)?;
+        self.buttons.load(r)?;
+        self.strobe.load(r)?;
+        self.shift.load(r)
+    }
+}
\ No newline at end of file
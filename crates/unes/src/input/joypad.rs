@@ -0,0 +1,49 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// buttons in the order the shift register sends them
+pub const BUTTON_A: u8 = 0b0000_0001;
+pub const BUTTON_B: u8 = 0b0000_0010;
+pub const BUTTON_SELECT: u8 = 0b0000_0100;
+pub const BUTTON_START: u8 = 0b0000_1000;
+pub const BUTTON_UP: u8 = 0b0001_0000;
+pub const BUTTON_DOWN: u8 = 0b0010_0000;
+pub const BUTTON_LEFT: u8 = 0b0100_0000;
+pub const BUTTON_RIGHT: u8 = 0b1000_0000;
+
+// standard controller, a 4021 shift register loaded while the strobe is high
+#[derive(Clone, Copy, Default)]
+pub struct Joypad {
+    pub buttons: u8,
+    strobe: bool,
+    shift: u8
+}
+impl Joypad {
+    pub fn new() -> Joypad {
+        Joypad::default()
+    }
+    // the buttons are latched until the strobe goes low
+    pub fn write(&mut self, value: u8) {
+        if self.strobe || value & 1 != 0 { self.shift = self.buttons }
+        self.strobe = value & 1 != 0;
+    }
+    // serial data, 1s once all 8 buttons are out
+    pub fn read(&mut self) -> u8 {
+        if self.strobe { return self.buttons & 1 }
+        let bit = self.shift & 1;
+        self.shift = self.shift >> 1 | 0x80;
+        bit
+    }
+}
+
+impl Snapshot for Joypad {
+    fn save(&self, w: &mut StateWriter) {
+        self.buttons.save(w);
+        self.strobe.save(w);
+        self.shift.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.buttons.load(r)?;
+        self.strobe.load(r)?;
+        self.shift.load(r)
+    }
+}
\ No newline at end of file
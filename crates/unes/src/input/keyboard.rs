@@ -0,0 +1,52 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+pub const ROWS: usize = 9;
+
+// Family BASIC keyboard on the expansion port, a matrix of 9 rows of two 4 key
+// columns. $4016 bit 2 enables it, bit 0 goes back to the first row and bit 1
+// selects the column, the row advances when it goes from 1 to 0. The selected
+// keys are read inverted on $4017 d1-d4.
+#[derive(Clone, Copy, Default)]
+pub struct Keyboard {
+    // column 0 in the low nibble, column 1 in the high one, set bits are pressed
+    pub keys: [u8; ROWS],
+    enabled: bool,
+    row: usize,
+    column: usize
+}
+impl Keyboard {
+    pub fn new() -> Keyboard {
+        Keyboard::default()
+    }
+    pub fn write(&mut self, value: u8) {
+        let column = (value >> 1 & 1) as usize;
+        self.enabled = value & 4 != 0;
+        if value & 1 != 0 {
+            self.row = 0;
+        } else if self.column == 1 && column == 0 {
+            // the 10th row reads as nothing pressed
+            self.row = (self.row + 1).min(ROWS);
+        }
+        self.column = column;
+    }
+    pub fn read(&self, port: usize) -> u8 {
+        if !self.enabled || port == 0 { return 0 }
+        let keys = self.keys.get(self.row).map_or(0, |row| row >> (self.column * 4) & 0xf);
+        (!keys & 0xf) << 1
+    }
+}
+
+impl Snapshot for Keyboard {
+    fn save(&self, w: &mut StateWriter) {
+        self.keys.save(w);
+        self.enabled.save(w);
+        self.row.save(w);
+        self.column.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.keys.load(r)?;
+        self.enabled.load(r)?;
+        self.row.load(r)?;
+        self.column.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,163 @@
+use unes_ppu::PPU;
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+pub mod four_score;
+pub mod joypad;
+pub mod keyboard;
+pub mod power_pad;
+pub mod vaus;
+pub mod zapper;
+
+pub use four_score::FourScore;
+pub use joypad::Joypad;
+pub use keyboard::Keyboard;
+pub use power_pad::PowerPad;
+pub use vaus::Vaus;
+pub use zapper::Zapper;
+
+// what is plugged into a port or the expansion port
+#[derive(Clone, Copy, Default)]
+pub enum Device {
+    #[default]
+    None,
+    Joypad(Joypad),
+    FourScore(FourScore),
+    // NES zappers go into port 2, Famicom ones into the expansion port
+    Zapper(Zapper),
+    // the Famicom paddle, the Family Trainer and the keyboard go into the expansion port
+    Vaus(Vaus),
+    PowerPad(PowerPad),
+    Keyboard(Keyboard)
+}
+impl Device {
+    // $4016 writes, every device sees the strobe
+    fn write(&mut self, value: u8) {
+        match self {
+            Device::Joypad(joypad) => joypad.write(value),
+            Device::FourScore(adapter) => adapter.write(value),
+            Device::Vaus(paddle) => paddle.write(value),
+            Device::PowerPad(mat) => mat.write(value),
+            Device::Keyboard(keyboard) => keyboard.write(value),
+            Device::None | Device::Zapper(_) => ()
+        }
+    }
+    // bits d0-d4 the device drives when port 0 ($4016) or 1 ($4017) is read
+    fn read(&mut self, port: usize, ppu: &PPU) -> u8 {
+        match self {
+            Device::None => 0,
+            Device::Joypad(joypad) => joypad.read(),
+            Device::FourScore(adapter) => adapter.read(port),
+            // the Famicom one is only wired to $4017
+            Device::Zapper(zapper) if port == 1 => zapper.read(ppu),
+            Device::Zapper(_) => 0,
+            Device::Vaus(paddle) => paddle.read(port),
+            Device::PowerPad(mat) => mat.read(port),
+            Device::Keyboard(keyboard) => keyboard.read(port)
+        }
+    }
+}
+// which device it is, then its state
+impl Snapshot for Device {
+    fn save(&self, w: &mut StateWriter) {
+        match self {
+            Device::None => w.write_u8(0),
+            Device::Joypad(joypad) => {
+                w.write_u8(1);
+                joypad.save(w);
+            },
+            Device::FourScore(adapter) => {
+                w.write_u8(2);
+                adapter.save(w);
+            },
+            Device::Zapper(zapper) => {
+                w.write_u8(3);
+                zapper.save(w);
+            },
+            Device::Vaus(paddle) => {
+                w.write_u8(4);
+                paddle.save(w);
+            },
+            Device::PowerPad(mat) => {
+                w.write_u8(5);
+                mat.save(w);
+            },
+            Device::Keyboard(keyboard) => {
+                w.write_u8(6);
+                keyboard.save(w);
+            }
+        }
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        let mut device = match r.read_u8()? {
+            0 => Device::None,
+            1 => Device::Joypad(Joypad::new()),
+            2 => Device::FourScore(FourScore::new()),
+            3 => Device::Zapper(Zapper::new()),
+            4 => Device::Vaus(Vaus::default()),
+            5 => Device::PowerPad(PowerPad::default()),
+            6 => Device::Keyboard(Keyboard::new()),
+            _ => return Err(StateError::InvalidData)
+        };
+        match &mut device {
+            Device::None => (),
+            Device::Joypad(joypad) => joypad.load(r)?,
+            Device::FourScore(adapter) => adapter.load(r)?,
+            Device::Zapper(zapper) => zapper.load(r)?,
+            Device::Vaus(paddle) => paddle.load(r)?,
+            Device::PowerPad(mat) => mat.load(r)?,
+            Device::Keyboard(keyboard) => keyboard.load(r)?
+        }
+        *self = device;
+        Ok(())
+    }
+}
+
+// The two controller ports and the Famicom expansion port. A port device
+// only answers reads of its own register, an expansion device both. The
+// Four Score takes both ports, it goes into the expansion slot with the
+// ports left empty.
+#[derive(Clone, Copy)]
+pub struct Input {
+    pub ports: [Device; 2],
+    pub expansion: Device
+}
+impl Default for Input {
+    fn default() -> Self {
+        Input { ports: [Device::Joypad(Joypad::new()); 2], expansion: Device::None }
+    }
+}
+impl Input {
+    pub fn new() -> Input {
+        Input::default()
+    }
+    pub fn write(&mut self, value: u8) {
+        self.ports.iter_mut().for_each(|port| port.write(value));
+        self.expansion.write(value);
+    }
+    // $4016 or $4017, the upper bits are left to open bus
+    pub fn read(&mut self, addr: u16, ppu: &PPU) -> u8 {
+        let port = addr as usize & 1;
+        (self.ports[port].read(port, ppu) | self.expansion.read(port, ppu)) & 0x1f
+    }
+    // buttons of player 1 to 4 (0-3), wherever their controller is
+    pub fn set_buttons(&mut self, player: usize, buttons: u8) {
+        match &mut self.expansion {
+            Device::FourScore(adapter) if !adapter.hori || player >= 2 => {
+                adapter.buttons[player] = buttons;
+                return
+            },
+            _ => ()
+        }
+        if let Some(Device::Joypad(joypad)) = self.ports.get_mut(player) { joypad.buttons = buttons }
+    }
+}
+impl Snapshot for Input {
+    fn save(&self, w: &mut StateWriter) {
+        self.ports.save(w);
+        self.expansion.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ports.load(r)?;
+        self.expansion.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,67 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// order the NES Power Pad sends its buttons (numbered as on side B) on d3 and d4
+const D3_ORDER: [usize; 8] = [2, 1, 5, 9, 6, 10, 11, 7];
+const D4_ORDER: [usize; 4] = [4, 3, 12, 8];
+
+// 12 button mat. The NES Power Pad latches every button with the strobe and shifts
+// them out of two registers (1s once empty). The Famicom Family Trainer instead
+// scans rows: a low bit 2, 1 or 0 in $4016 selects buttons 1-4, 5-8 or 9-12, read
+// back inverted from $4017 d1-d4.
+#[derive(Clone, Copy, Default)]
+pub struct PowerPad {
+    pub family_trainer: bool,
+    // bit n - 1 for button n
+    pub buttons: u16,
+    strobe: bool,
+    shift: [u8; 2],
+    rows: u8
+}
+impl PowerPad {
+    pub fn nes() -> PowerPad {
+        PowerPad::default()
+    }
+    pub fn family_trainer() -> PowerPad {
+        PowerPad { family_trainer: true, ..PowerPad::default() }
+    }
+    fn pressed(&self, button: usize) -> u8 {
+        (self.buttons >> (button - 1) & 1) as u8
+    }
+    pub fn write(&mut self, value: u8) {
+        self.rows = value & 7;
+        if self.strobe || value & 1 != 0 {
+            let d3 = D3_ORDER.iter().enumerate().fold(0, |bits, (i, b)| bits | self.pressed(*b) << i);
+            let d4 = D4_ORDER.iter().enumerate().fold(0xf0, |bits, (i, b)| bits | self.pressed(*b) << i);
+            self.shift = [d3, d4];
+        }
+        self.strobe = value & 1 != 0;
+    }
+    pub fn read(&mut self, port: usize) -> u8 {
+        if self.family_trainer {
+            if port == 0 { return 0 }
+            let pressed = (0..3).filter(|row| self.rows & 4 >> row == 0)
+                .fold(0, |bits, row| bits | (self.buttons >> (row * 4) & 0xf) as u8);
+            return (!pressed & 0xf) << 1
+        }
+        let [d3, d4] = self.shift.map(|bits| bits & 1);
+        if !self.strobe { self.shift = self.shift.map(|bits| bits >> 1 | 0x80) }
+        d4 << 4 | d3 << 3
+    }
+}
+
+impl Snapshot for PowerPad {
+    fn save(&self, w: &mut StateWriter) {
+        self.family_trainer.save(w);
+        self.buttons.save(w);
+        self.strobe.save(w);
+        self.shift.save(w);
+        self.rows.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.family_trainer.load(r)?;
+        self.buttons.load(r)?;
+        self.strobe.load(r)?;
+        self.shift.load(r)?;
+        self.rows.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// Arkanoid paddle, a potentiometer converted to 8 bits and latched by the strobe,
+// then sent inverted, msb first. The NES one sends it on d4 with the button on d3,
+// the Famicom one (expansion port) on $4017 d1 with the button on $4016 d1.
+#[derive(Clone, Copy, Default)]
+pub struct Vaus {
+    pub famicom: bool,
+    // the knob, around 98 (left) to 242 (right) on real paddles
+    pub position: u8,
+    pub button: bool,
+    strobe: bool,
+    shift: u8
+}
+impl Vaus {
+    pub fn nes() -> Vaus {
+        Vaus::default()
+    }
+    pub fn famicom() -> Vaus {
+        Vaus { famicom: true, ..Vaus::default() }
+    }
+    pub fn write(&mut self, value: u8) {
+        if self.strobe || value & 1 != 0 { self.shift = !self.position }
+        self.strobe = value & 1 != 0;
+    }
+    fn shift_out(&mut self) -> u8 {
+        let bit = self.shift >> 7;
+        if !self.strobe { self.shift <<= 1 }
+        bit
+    }
+    pub fn read(&mut self, port: usize) -> u8 {
+        match (self.famicom, port) {
+            (false, _) => self.shift_out() << 4 | (self.button as u8) << 3,
+            (true, 0) => (self.button as u8) << 1,
+            _ => self.shift_out() << 1
+        }
+    }
+}
+
+impl Snapshot for Vaus {
+    fn save(&self, w: &mut StateWriter) {
+        self.famicom.save(w);
+        self.position.save(w);
+        self.button.save(w);
+        self.strobe.save(w);
+        self.shift.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.famicom.load(r)?;
+        self.position.load(r)?;
+        self.button.load(r)?;
+        self.strobe.load(r)?;
+        self.shift.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+use unes_ppu::{PPU, WIDTH, HEIGHT};
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// pixels around the aim point the sensor sees
+const RADIUS: i32 = 2;
+// the photodiode keeps reacting for a while after the beam went past
+const LIGHT_SCANLINES: u16 = 20;
+
+// Light gun, d3 is low while the sensor sees light, d4 high while the trigger is held.
+// The sensor looks at what the ppu has drawn around the aim point during the last
+// few scanlines of the current frame, so what it sees depends on when it is read.
+#[derive(Clone, Copy, Default)]
+pub struct Zapper {
+    // pixel the gun points at, None when off screen
+    pub aim: Option<(u16, u16)>,
+    pub trigger: bool
+}
+impl Zapper {
+    pub fn new() -> Zapper {
+        Zapper::default()
+    }
+    pub fn read(&self, ppu: &PPU) -> u8 {
+        (!self.light(ppu) as u8) << 3 | (self.trigger as u8) << 4
+    }
+    pub fn light(&self, ppu: &PPU) -> bool {
+        let Some((aim_x, aim_y)) = self.aim else { return false };
+        (-RADIUS..=RADIUS).any(|dy| (-RADIUS..=RADIUS).any(|dx| {
+            let (x, y) = (aim_x as i32 + dx, aim_y as i32 + dy);
+            if x < 0 || y < 0 || x >= WIDTH as i32 || y >= HEIGHT as i32 { return false }
+            let (x, y) = (x as u16, y as u16);
+            // output on dot x + 1 of scanline y
+            let drawn = y < ppu.scanline || y == ppu.scanline && x < ppu.dot.saturating_sub(1);
+            drawn && ppu.scanline - y < LIGHT_SCANLINES && bright(ppu.framebuffer[y as usize * WIDTH + x as usize])
+        }))
+    }
+}
+
+// the two brightest rows of the palette, minus the greys and blacks of columns $d-$f
+fn bright(pixel: u16) -> bool {
+    let color = pixel & 0x3f;
+    color & 0x30 >= 0x20 && color & 0x0f < 0x0d
+}
+
+impl Snapshot for Zapper {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_bool(self.aim.is_some());
+        let (x, y) = self.aim.unwrap_or_default();
+        w.write_u16(x);
+        w.write_u16(y);
+        self.trigger.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        let on_screen = r.read_bool()?;
+        let aim = (r.read_u16()?, r.read_u16()?);
+        self.aim = on_screen.then_some(aim);
+        self.trigger.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+pub mod audio;
+mod bus;
+mod console;
+pub mod input;
+pub mod mixer;
+pub mod palette;
+mod region;
+mod rewind;
+mod tests;
+
+pub use audio::AudioOutput;
+pub use bus::NesBus;
+pub use console::Console;
+pub use input::Input;
+pub use mixer::Mixer;
+pub use palette::Palette;
+pub use region::Region;
+pub use rewind::Rewind;
+pub use unes_state::StateError;
\ No newline at end of file
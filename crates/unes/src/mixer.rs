@@ -0,0 +1,81 @@
+use unes_apu::APU;
+use unes_cartridge::audio::ExpansionAudio;
+
+use crate::audio::AudioOutput;
+
+pub const APU_CHANNELS: [&str; 5] = ["pulse 1", "pulse 2", "triangle", "noise", "dmc"];
+
+// Mixes the 2A03 channels through lookup tables of its two non-linear dacs, the
+// expansion chip goes on top linearly. The output of a dac is split between its
+// channels by their share of the dac input, this is what gains apply to (and
+// what goes to the stems) so the default gains give back the plain curves.
+pub struct Mixer {
+    pulse_table: [f32; 31],
+    tnd_table: [f32; 203],
+    // apu channels first, then the expansion ones
+    pub names: Vec<&'static str>,
+    pub gains: Vec<f32>,
+    pub muted: Vec<bool>,
+    // contribution of each channel to the last mix
+    pub levels: Vec<f32>,
+    stems: Vec<AudioOutput>
+}
+impl Mixer {
+    pub fn new(expansion: Option<&dyn ExpansionAudio>) -> Mixer {
+        let mut names = APU_CHANNELS.to_vec();
+        names.extend_from_slice(expansion.map_or(&[], |chip| chip.channels()));
+        let count = names.len();
+        Mixer {
+            pulse_table: std::array::from_fn(|n| if n == 0 { 0.0 } else { 95.52 / (8128.0 / n as f32 + 100.0) }),
+            tnd_table: std::array::from_fn(|n| if n == 0 { 0.0 } else { 163.67 / (24329.0 / n as f32 + 100.0) }),
+            names,
+            gains: vec![1.0; count],
+            muted: vec![false; count],
+            levels: vec![0.0; count],
+            stems: Vec::new()
+        }
+    }
+    fn gain(&self, channel: usize) -> f32 {
+        if self.muted[channel] { 0.0 } else { self.gains[channel] }
+    }
+    pub fn mix(&mut self, apu: &APU, expansion: Option<&dyn ExpansionAudio>) -> f32 {
+        self.mix_levels(apu.channel_outputs(), |c| expansion.map_or(0.0, |chip| chip.channel_output(c)))
+    }
+    // the dac inputs of the apu channels (see `APU::channel_outputs`) and the expansion channel outputs
+    pub fn mix_outputs(&mut self, outputs: [u8; 5], expansion: &[f32]) -> f32 {
+        self.mix_levels(outputs, |c| expansion.get(c).copied().unwrap_or(0.0))
+    }
+    fn mix_levels(&mut self, outputs: [u8; 5], expansion: impl Fn(usize) -> f32) -> f32 {
+        let [pulse1, pulse2, triangle, noise, dmc] = outputs.map(|o| o as usize);
+        // the triangle, noise and dmc dac weighs its inputs 3:2:1
+        let inputs = [pulse1, pulse2, 3 * triangle, 2 * noise, dmc];
+        let pulse = pulse1 + pulse2;
+        let tnd = inputs[2] + inputs[3] + inputs[4];
+        for (channel, input) in inputs.into_iter().enumerate() {
+            let (table, total) = if channel < 2 { (&self.pulse_table[..], pulse) } else { (&self.tnd_table[..], tnd) };
+            self.levels[channel] = if input == 0 { 0.0 } else { table[total] * input as f32 / total as f32 * self.gain(channel) };
+        }
+        for channel in APU_CHANNELS.len()..self.levels.len() {
+            self.levels[channel] = expansion(channel - APU_CHANNELS.len()) * self.gain(channel);
+        }
+        for (stem, level) in self.stems.iter_mut().zip(&self.levels) {
+            stem.clock(*level);
+        }
+        self.levels.iter().sum()
+    }
+    // resamples every channel on its own, at the rates of `output`
+    pub fn enable_stems(&mut self, output: &AudioOutput) {
+        self.stems = self.names.iter().map(|_| AudioOutput::new(output.clock_rate, output.sample_rate)).collect();
+    }
+    // follows a change of the rates of `output`
+    pub fn set_rates(&mut self, output: &AudioOutput) {
+        self.stems.iter_mut().for_each(|stem| stem.set_rates(output.clock_rate, output.sample_rate));
+    }
+    pub fn disable_stems(&mut self) {
+        self.stems.clear();
+    }
+    // one buffer per channel (empty without stems), the samples produced since the last take
+    pub fn take_stems(&mut self) -> Vec<Vec<f32>> {
+        self.stems.iter_mut().map(|stem| stem.take_f32()).collect()
+    }
+}
\ No newline at end of file
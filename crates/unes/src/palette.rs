@@ -0,0 +1,129 @@
+use std::f32::consts::PI;
+use std::fmt;
+
+pub const COLORS: usize = 64;
+// every color for each of the 8 emphasis combinations, the layout of 512 entry .pal files
+pub const ENTRIES: usize = COLORS * 8;
+
+// composite levels of the 2C02 for the 4 luma rows, in volts
+const LOW: [f32; 4] = [0.350, 0.518, 0.962, 1.550];
+const HIGH: [f32; 4] = [1.094, 1.506, 1.962, 1.962];
+const BLACK: f32 = 0.518;
+const WHITE: f32 = 1.962;
+// emphasis darkens the signal during a third of the color cycle
+const ATTENUATION: f32 = 0.746;
+// phase of the color burst, what the decoder takes as its reference
+const BURST_PHASE: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteError {
+    InvalidSize(usize)
+}
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidSize(size) => write!(f, "a palette has 64 or 512 colors, got {} bytes", size)
+        }
+    }
+}
+
+// knobs of the ntsc decoder, the defaults give the plain signal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NtscParams {
+    // degrees
+    pub hue: f32,
+    pub saturation: f32,
+    pub contrast: f32,
+    pub brightness: f32,
+    pub gamma: f32
+}
+impl Default for NtscParams {
+    fn default() -> Self {
+        NtscParams { hue: 0.0, saturation: 1.0, contrast: 1.0, brightness: 0.0, gamma: 1.0 }
+    }
+}
+
+// maps the ppu output (color index | emphasis << 6) to rgb
+pub struct Palette {
+    pub colors: Vec<[u8; 3]>
+}
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::ntsc(&NtscParams::default())
+    }
+}
+impl Palette {
+    pub fn ntsc(params: &NtscParams) -> Palette {
+        let colors = (0..ENTRIES).map(|entry| ntsc_color(entry & 0x3f, entry >> 6, params)).collect();
+        Palette { colors }
+    }
+    // 64 entry files get their emphasized colors computed
+    pub fn from_pal(bytes: &[u8]) -> Result<Palette, PaletteError> {
+        let entries = match bytes.len() {
+            len if len == COLORS * 3 || len == ENTRIES * 3 => len / 3,
+            len => return Err(PaletteError::InvalidSize(len))
+        };
+        let colors = (0..ENTRIES).map(|entry| {
+            let rgb = &bytes[(entry % entries) * 3..][..3];
+            let rgb = [rgb[0], rgb[1], rgb[2]];
+            if entries == ENTRIES { rgb } else { emphasize(rgb, entry & 0x3f, entry >> 6) }
+        }).collect();
+        Ok(Palette { colors })
+    }
+    pub fn rgb(&self, entry: u16) -> [u8; 3] {
+        self.colors[entry as usize % ENTRIES]
+    }
+    // a whole framebuffer as packed 24 bit rgb
+    pub fn to_rgb(&self, framebuffer: &[u16]) -> Vec<u8> {
+        framebuffer.iter().flat_map(|&entry| self.rgb(entry)).collect()
+    }
+}
+
+// the emphasis bits are red, green and blue, each darkening the other two
+fn emphasize(rgb: [u8; 3], color: usize, emphasis: usize) -> [u8; 3] {
+    // the blacks in the last columns are not affected
+    if color & 0x0e == 0x0e { return rgb }
+    let mut out = rgb;
+    for (channel, value) in out.iter_mut().enumerate() {
+        let others = (emphasis & !(1 << channel)).count_ones();
+        *value = (*value as f32 * ATTENUATION.powi(others as i32)).round() as u8;
+    }
+    out
+}
+
+// decodes the square wave the ppu generates for a color
+// The chroma is one of 12 phases of the color subcarrier, the luma the low
+// and high levels of the wave. Columns $0 and $d are plain grey levels,
+// $e and $f black.
+fn ntsc_color(color: usize, emphasis: usize, params: &NtscParams) -> [u8; 3] {
+    let hue = color & 0x0f;
+    let luma = if hue > 13 { 1 } else { color >> 4 };
+    let low = if hue == 0 { HIGH[luma] } else { LOW[luma] };
+    let high = if hue > 12 { LOW[luma] } else { HIGH[luma] };
+
+    let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+    for phase in 0..12 {
+        let in_phase = |hue: usize| (hue + phase) % 12 < 6;
+        let mut signal = if in_phase(hue) { high } else { low };
+        let emphasized = (emphasis & 1 != 0 && in_phase(0))
+            || (emphasis & 2 != 0 && in_phase(4))
+            || (emphasis & 4 != 0 && in_phase(8));
+        // the blacks in the last columns are not affected
+        if emphasized && hue < 0x0e { signal *= ATTENUATION }
+        let level = (signal - BLACK) / (WHITE - BLACK) / 12.0;
+        let angle = PI * (phase as f32 + BURST_PHASE) / 6.0 + params.hue.to_radians();
+        y += level;
+        i += level * angle.cos();
+        q += level * angle.sin();
+    }
+    y = y * params.contrast + params.brightness;
+    i *= params.saturation;
+    q *= params.saturation;
+
+    let rgb = [
+        y + 0.946_882 * i + 0.623_557 * q,
+        y - 0.274_788 * i - 0.635_691 * q,
+        y - 1.108_545 * i + 1.709_007 * q
+    ];
+    rgb.map(|value| (value.clamp(0.0, 1.0).powf(params.gamma) * 255.0).round() as u8)
+}
\ No newline at end of file
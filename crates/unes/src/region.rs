@@ -0,0 +1,78 @@
+use unes_cartridge::Timing;
+use unes_ppu::Layout;
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// The console variant: the master clock, how it is divided for the cpu and the
+// ppu, and the frame the ppu draws. Dendy (the famiclone) has the pal clock and
+// frame but keeps 3 dots per cpu cycle and the ntsc apu.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+    Dendy
+}
+impl From<Timing> for Region {
+    // multi-region games get the ntsc console
+    fn from(timing: Timing) -> Region {
+        match timing {
+            Timing::Pal => Region::Pal,
+            Timing::Dendy => Region::Dendy,
+            Timing::Ntsc | Timing::MultiRegion => Region::Ntsc
+        }
+    }
+}
+impl Region {
+    // Hz
+    pub fn master_clock(self) -> u64 {
+        match self {
+            Region::Ntsc => 21_477_272,
+            Region::Pal | Region::Dendy => 26_601_712
+        }
+    }
+    pub fn cpu_divider(self) -> u64 {
+        match self {
+            Region::Ntsc => 12,
+            Region::Pal => 16,
+            Region::Dendy => 15
+        }
+    }
+    pub fn ppu_divider(self) -> u64 {
+        match self {
+            Region::Ntsc => 4,
+            Region::Pal | Region::Dendy => 5
+        }
+    }
+    pub fn cpu_clock(self) -> f64 {
+        self.master_clock() as f64 / self.cpu_divider() as f64
+    }
+    pub fn layout(self) -> Layout {
+        match self {
+            Region::Ntsc => Layout::NTSC,
+            Region::Pal => Layout::PAL,
+            Region::Dendy => Layout::DENDY
+        }
+    }
+    // frames per second
+    pub fn frame_rate(self) -> f64 {
+        let layout = self.layout();
+        let frame = self.ppu_divider() * 341 * layout.scanlines as u64;
+        // the short odd frames
+        let frame = if layout.odd_frame_skip { frame as f64 - self.ppu_divider() as f64 / 2.0 } else { frame as f64 };
+        self.master_clock() as f64 / frame
+    }
+}
+impl Snapshot for Region {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u8(*self as u8);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = match r.read_u8()? {
+            0 => Region::Ntsc,
+            1 => Region::Pal,
+            2 => Region::Dendy,
+            _ => return Err(StateError::InvalidData)
+        };
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::mem::size_of;
+
+use unes_state::StateError;
+
+use crate::console::Console;
+use crate::input::Input;
+
+// a run of changed bytes ends after this many unchanged ones
+const MIN_SKIP: usize = 4;
+
+// a save state taken at the start of `frame`
+struct Entry {
+    frame: u64,
+    // the newest state is kept whole, the others as a delta against the next one
+    data: Vec<u8>
+}
+
+// Runs the console a frame at a time, keeping a save state every `interval`
+// frames and the input of every frame since the oldest one. Going back restores
+// the nearest state before the target and replays the frames from there. The
+// oldest states are dropped once they take more than `capacity` bytes.
+pub struct Rewind {
+    pub interval: u64,
+    pub capacity: usize,
+    entries: VecDeque<Entry>,
+    // input of each frame from the oldest entry on
+    inputs: VecDeque<Input>,
+    frame: u64,
+    size: usize
+}
+impl Rewind {
+    pub fn new(interval: u64, capacity: usize) -> Rewind {
+        Rewind {
+            interval: interval.max(1),
+            capacity,
+            entries: VecDeque::new(),
+            inputs: VecDeque::new(),
+            frame: 0,
+            size: 0
+        }
+    }
+    // frames run since the start or the last clear
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+    // how far back `rewind` can go
+    pub fn available(&self) -> u64 {
+        self.entries.front().map_or(0, |entry| self.frame - entry.frame)
+    }
+    // bytes taken by the states and inputs
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.inputs.clear();
+        self.frame = 0;
+        self.size = 0;
+    }
+    // runs a frame with the input the console has now
+    pub fn run_frame(&mut self, console: &mut Console) {
+        if self.frame.is_multiple_of(self.interval) && self.entries.back().is_none_or(|entry| entry.frame != self.frame) {
+            self.push(console.save_state());
+        }
+        self.inputs.push_back(console.cpu.memory.input);
+        self.size += size_of::<Input>();
+        console.run_frame();
+        self.frame += 1;
+        self.trim();
+    }
+    pub fn step_back(&mut self, console: &mut Console) -> Result<bool, StateError> {
+        Ok(self.rewind(console, 1)? == 1)
+    }
+    // goes back up to `frames` frames, returns how many it went back
+    // The audio of the replayed frames is dropped.
+    pub fn rewind(&mut self, console: &mut Console, frames: u64) -> Result<u64, StateError> {
+        let frames = frames.min(self.available());
+        if frames == 0 { return Ok(0) }
+        let target = self.frame - frames;
+        // undo the deltas down to the newest state at or before the target
+        let mut state = self.entries.back().unwrap().data.clone();
+        while self.entries.back().unwrap().frame > target {
+            let entry = self.entries.pop_back().unwrap();
+            self.size -= entry.data.len();
+            let older = self.entries.back_mut().unwrap();
+            let delta = std::mem::take(&mut older.data);
+            state = apply(&state, &delta);
+            self.size = self.size - delta.len() + state.len();
+            older.data = state.clone();
+        }
+        let start = self.entries.back().unwrap().frame;
+        let oldest = self.entries.front().unwrap().frame;
+        self.size -= (self.inputs.len() - (target - oldest) as usize) * size_of::<Input>();
+        self.inputs.truncate((target - oldest) as usize);
+        console.load_state(&state)?;
+        for frame in start..target {
+            console.cpu.memory.input = self.inputs[(frame - oldest) as usize];
+            console.run_frame();
+        }
+        console.cpu.memory.audio.clear();
+        console.cpu.memory.mixer.take_stems();
+        self.frame = target;
+        Ok(frames)
+    }
+    fn push(&mut self, state: Vec<u8>) {
+        if let Some(newest) = self.entries.back_mut() {
+            let delta = delta(&state, &newest.data);
+            self.size -= newest.data.len();
+            self.size += delta.len();
+            newest.data = delta;
+        }
+        self.size += state.len();
+        self.entries.push_back(Entry { frame: self.frame, data: state });
+    }
+    // drops the oldest states (and their inputs) while over capacity, keeping one
+    fn trim(&mut self) {
+        while self.size > self.capacity && self.entries.len() > 1 {
+            let entry = self.entries.pop_front().unwrap();
+            let frames = (self.entries[0].frame - entry.frame) as usize;
+            self.inputs.drain(..frames);
+            self.size -= entry.data.len() + frames * size_of::<Input>();
+        }
+    }
+}
+
+// `target` as runs of bytes to keep from `base` and bytes to replace:
+// the target length, then pairs of skip and literal counts, the literals after their count
+fn delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_count(&mut out, target.len());
+    let same = |i: usize| base.get(i) == Some(&target[i]);
+    let mut i = 0;
+    while i < target.len() {
+        let start = i;
+        while i < target.len() && same(i) { i += 1 }
+        write_count(&mut out, i - start);
+        let start = i;
+        while i < target.len() && !(i + MIN_SKIP <= target.len() && (i..i + MIN_SKIP).all(same)) { i += 1 }
+        write_count(&mut out, i - start);
+        out.extend_from_slice(&target[start..i]);
+    }
+    out
+}
+fn apply(base: &[u8], delta: &[u8]) -> Vec<u8> {
+    let mut position = 0;
+    let len = read_count(delta, &mut position);
+    let mut out = base.to_vec();
+    out.resize(len, 0);
+    let mut i = 0;
+    while i < len {
+        i += read_count(delta, &mut position);
+        let literal = read_count(delta, &mut position);
+        out[i..i + literal].copy_from_slice(&delta[position..position + literal]);
+        position += literal;
+        i += literal;
+    }
+    out
+}
+// leb128
+fn write_count(out: &mut Vec<u8>, mut count: usize) {
+    while count >= 0x80 {
+        out.push(count as u8 | 0x80);
+        count >>= 7;
+    }
+    out.push(count as u8);
+}
+fn read_count(data: &[u8], position: &mut usize) -> usize {
+    let mut count = 0;
+    let mut shift = 0;
+    loop {
+        let byte = data[*position];
+        *position += 1;
+        count |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 { return count }
+        shift += 7;
+    }
+}
\ No newline at end of file
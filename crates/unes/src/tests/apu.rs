@@ -0,0 +1,17 @@
+#[cfg(test)]
+mod tests {
+    use unes_cpu::Bus;
+
+    use crate::tests::cpu;
+
+    #[test]
+    fn test_apu_registers() {
+        let mut cpu = cpu(&[]);
+        cpu.memory.write(0x4015, 0x01);
+        cpu.memory.write(0x4003, 0x08);
+        assert!(cpu.memory.apu.pulse1.length.counter == 254);
+        // bit 5 of the status is open bus
+        cpu.memory.write(0x4000, 0x20);
+        assert!(cpu.memory.read(0x4015) == 0x21);
+    }
+}
\ No newline at end of file
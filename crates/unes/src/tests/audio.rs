@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use crate::audio::{AudioOutput, NTSC_CLOCK, to_i16};
+
+    // rms of the samples around their mean
+    fn ac_rms(samples: &[f32]) -> f32 {
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        (samples.iter().map(|s| (s - mean) * (s - mean)).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_sample_rate() {
+        for rate in [44_100, 48_000] {
+            let mut audio = AudioOutput::new(NTSC_CLOCK, rate);
+            for _ in 0..NTSC_CLOCK as u32 { audio.clock(0.0) }
+            assert!(audio.len().abs_diff(rate as usize) <= 1);
+        }
+    }
+    #[test]
+    fn test_step() {
+        let mut audio = AudioOutput::default();
+        audio.filtering = false;
+        for _ in 0..10_000 { audio.clock(0.5) }
+        let samples = audio.take_f32();
+        // the step is delayed by half the kernel, then settles without drift
+        assert!(samples[0].abs() < 0.01);
+        assert!(samples[100..].iter().all(|s| (s - 0.5).abs() < 1e-4));
+        assert!(audio.is_empty());
+    }
+    #[test]
+    fn test_high_pass() {
+        let mut audio = AudioOutput::default();
+        for _ in 0..NTSC_CLOCK as u32 / 10 { audio.clock(0.5) }
+        let samples = audio.samples();
+        // the dc offset is removed
+        assert!(samples.iter().any(|s| *s > 0.3));
+        assert!(samples[samples.len() - 1].abs() < 0.01);
+    }
+    #[test]
+    fn test_no_aliasing() {
+        // a square at 1789773 / 64 = 28kHz is above nyquist at 44.1kHz
+        let mut audio = AudioOutput::new(NTSC_CLOCK, 44_100);
+        audio.filtering = false;
+        for cycle in 0..NTSC_CLOCK as u32 / 10 {
+            audio.clock(if cycle & 32 != 0 { 0.5 } else { 0.0 });
+        }
+        assert!(ac_rms(&audio.samples()[100..]) < 0.01);
+        // an audible one at 1789773 / 4096 = 437Hz goes through
+        let mut audio = AudioOutput::new(NTSC_CLOCK, 44_100);
+        audio.filtering = false;
+        for cycle in 0..NTSC_CLOCK as u32 / 10 {
+            audio.clock(if cycle & 2048 != 0 { 0.5 } else { 0.0 });
+        }
+        assert!(ac_rms(&audio.samples()[100..]) > 0.2);
+    }
+    #[test]
+    fn test_i16() {
+        let mut audio = AudioOutput::default();
+        audio.filtering = false;
+        for _ in 0..2_000 { audio.clock(1.5) }
+        let samples = audio.take_i16();
+        assert!(samples[samples.len() - 1] == i16::MAX);
+        assert!(to_i16(-0.5) == -16383 && to_i16(0.0) == 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use unes_cartridge::CartridgeError;
+
+    use crate::Console;
+    use crate::input::joypad::*;
+    use crate::tests::rom;
+
+    // jmp to itself
+    const LOOP: [u8; 3] = [0x4c, 0x00, 0x80];
+
+    #[test]
+    fn test_from_rom() {
+        assert!(Console::from_rom(&[0; 16]).err() == Some(CartridgeError::InvalidHeader));
+        let console = Console::from_rom(&rom(&LOOP, &[], &[])).unwrap();
+        // started from the reset vector
+        assert!(console.cpu.pc == 0x8000 && console.cpu.sp == 0xfc);
+    }
+    #[test]
+    fn test_run_frame() {
+        let mut console = Console::from_rom(&rom(&LOOP, &[], &[])).unwrap();
+        console.run_frame();
+        assert!(console.cpu.memory.ppu.frame == 1);
+        let cycles = console.cpu.cycles;
+        console.run_frame();
+        // 341 * 262 dots, 3 per cpu cycle
+        assert!((console.cpu.cycles - cycles).abs_diff(29781) <= 3);
+        // the reset sequence is not clocked
+        assert!(console.cpu.memory.master_clock == (console.cpu.cycles - 7) * 12);
+    }
+    #[test]
+    fn test_vblank_read() {
+        // lda $2002, lda $2002
+        let code = [0xad, 0x02, 0x20, 0xad, 0x02, 0x20];
+        // bit 7 of both reads, the first one on the 4th cycle lands on `dot`
+        // of the vblank scanline: 12 dots after the step starts
+        let reads = |dot: u16| {
+            let mut console = Console::from_rom(&rom(&code, &[], &[])).unwrap();
+            let ppu = &mut console.cpu.memory.ppu;
+            (ppu.scanline, ppu.dot) = (240, 341 + dot - 12);
+            console.step();
+            let first = console.cpu.reg_a & 0x80;
+            console.step();
+            (first, console.cpu.reg_a & 0x80)
+        };
+        assert!(reads(0) == (0, 0x80));
+        // right when the flag would be set, it is not and it stays clear
+        assert!(reads(1) == (0, 0));
+        assert!(reads(2) == (0x80, 0));
+    }
+    #[test]
+    fn test_nmi() {
+        // lda #$80, sta $2000, loop; the handler does inc $00, rti
+        let code = [0xa9, 0x80, 0x8d, 0x00, 0x20, 0x4c, 0x05, 0x80];
+        let mut console = Console::from_rom(&rom(&code, &[0xe6, 0x00, 0x40], &[])).unwrap();
+        for _ in 0..3 { console.run_frame() }
+        assert!(console.cpu.memory.ram[0] == 3);
+    }
+    #[test]
+    fn test_irq() {
+        // lda #$00, sta $4017 (4-step mode with the frame irq), cli, loop
+        // the handler does inc $00, lda $4015 (acknowledges), rti
+        let code = [0xa9, 0x00, 0x8d, 0x17, 0x40, 0x58, 0x4c, 0x06, 0x80];
+        let mut console = Console::from_rom(&rom(&code, &[], &[0xe6, 0x00, 0xad, 0x15, 0x40, 0x40])).unwrap();
+        // the frame counter runs at 60Hz too
+        for _ in 0..4 { console.run_frame() }
+        assert!((3..=4).contains(&console.cpu.memory.ram[0]));
+    }
+    #[test]
+    fn test_input() {
+        // strobe, then lda $4016 / ror a / rol $00 eight times
+        let mut code = vec![0xa9, 0x01, 0x8d, 0x16, 0x40, 0xa9, 0x00, 0x8d, 0x16, 0x40];
+        for _ in 0..8 { code.extend([0xad, 0x16, 0x40, 0x6a, 0x26, 0x00]) }
+        code.extend([0x4c, code.len() as u8, 0x80]);
+        let mut console = Console::from_rom(&rom(&code, &[], &[])).unwrap();
+        console.set_input(0, BUTTON_A | BUTTON_UP);
+        console.run_frame();
+        // first button in the high bit
+        assert!(console.cpu.memory.ram[0] == 0b1000_1000);
+    }
+    #[test]
+    fn test_framebuffer_and_audio() {
+        // backdrop color $21, background on
+        let code = [
+            0xa9, 0x3f, 0x8d, 0x06, 0x20, 0xa9, 0x00, 0x8d, 0x06, 0x20,
+            0xa9, 0x21, 0x8d, 0x07, 0x20, 0xa9, 0x08, 0x8d, 0x01, 0x20,
+            0x4c, 0x14, 0x80
+        ];
+        let mut console = Console::from_rom(&rom(&code, &[], &[])).unwrap();
+        console.run_frame();
+        console.run_frame();
+        assert!(console.framebuffer().iter().all(|pixel| *pixel == 0x21));
+        let rgb = console.framebuffer_rgb();
+        assert!(rgb.len() == 256 * 240 * 3 && rgb[..3] == console.palette.rgb(0x21));
+        // about 800 samples a frame at 48kHz
+        let samples = console.audio_samples();
+        assert!((1590..=1610).contains(&samples.len()));
+        assert!(console.audio_samples().is_empty());
+    }
+}
\ No newline at end of file
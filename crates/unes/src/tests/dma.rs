@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    use unes_cartridge::Cartridge;
+    use unes_cpu::{Bus, CPU};
+
+    use crate::NesBus;
+    use crate::input::joypad::*;
+    use crate::tests::{cpu, rom};
+
+    // the dmc plays the last bit of a byte with one more to fetch, it asks
+    // for it on the cpu cycle after the next `timer`
+    fn dmc_request(cpu: &mut CPU<NesBus>, timer: u16) {
+        cpu.memory.write(0x4015, 0x10);
+        let dmc = &mut cpu.memory.apu.dmc;
+        (dmc.buffer, dmc.bits_remaining, dmc.timer) = (Some(0), 1, timer);
+    }
+
+    #[test]
+    fn test_ram_mirroring() {
+        let mut cpu = cpu(&[]);
+        cpu.memory.write(0x0801, 0x42);
+        assert!(cpu.memory.read(0x1801) == 0x42 && cpu.memory.ram[1] == 0x42);
+        // prg rom is mirrored on NROM-128
+        assert!(cpu.memory.read(0xc000) == cpu.memory.read(0x8000));
+    }
+    #[test]
+    fn test_ppu_registers() {
+        let mut cpu = cpu(&[]);
+        cpu.memory.write(0x3ff8, 0x80);
+        assert!(cpu.memory.ppu.ctrl == 0x80);
+        cpu.memory.write(0x2003, 0x10);
+        cpu.memory.write(0x2004, 0x55);
+        assert!(cpu.memory.ppu.oam[0x10] == 0x55);
+    }
+    #[test]
+    fn test_open_bus() {
+        let mut cpu = cpu(&[0x12]);
+        cpu.memory.read(0x8000);
+        assert!(cpu.memory.read(0x4018) == 0x12 && cpu.memory.read(0x5000) == 0x12);
+    }
+    #[test]
+    fn test_partly_driven_open_bus() {
+        // Bandai with an eeprom (mapper 159), only bit 4 of $6000 is driven
+        let mut bytes = rom(&[], &[], &[]);
+        (bytes[6], bytes[7]) = (0xf0, 0x90);
+        let mut bus = NesBus::new(Cartridge::from_bytes(&bytes).unwrap());
+        bus.write(0x0000, 0xef);
+        assert!(bus.read(0x6000) & 0xef == 0xef);
+        bus.write(0x0000, 0x00);
+        assert!(bus.read(0x6000) & 0xef == 0);
+    }
+    #[test]
+    fn test_oam_dma() {
+        // lda #2, sta $4014, nop
+        let mut cpu = cpu(&[0xa9, 0x02, 0x8d, 0x14, 0x40, 0xea]);
+        for i in 0..256 { cpu.memory.ram[0x200 + i] = i as u8 }
+        cpu.memory.ppu.oam_addr = 4;
+        cpu.step();
+        assert!(cpu.step() == 4);
+        // halted on the opcode fetch
+        assert!(cpu.step() == 2 + 513);
+        assert!(cpu.cycles == 8 + 513);
+        // the copy starts at oam_addr and wraps around
+        assert!(cpu.memory.ppu.oam[4] == 0 && cpu.memory.ppu.oam[3] == 0xff);
+        assert!(cpu.memory.ppu.oam_addr == 4);
+    }
+    #[test]
+    fn test_oam_dma_odd_cycle() {
+        // lda $00, sta $4014, nop
+        let mut cpu = cpu(&[0xa5, 0x00, 0x8d, 0x14, 0x40, 0xea]);
+        cpu.step();
+        cpu.step();
+        assert!(cpu.step() == 2 + 514);
+        assert!(cpu.cycles == 9 + 514);
+        assert!(cpu.memory.dma_page.is_none());
+    }
+    #[test]
+    fn test_dmc_dma() {
+        // lda #$10, sta $4015 starts a one byte sample at $c000, nop
+        let mut cpu = cpu(&[0xa9, 0x10, 0x8d, 0x15, 0x40, 0xea]);
+        cpu.step();
+        cpu.step();
+        assert!(cpu.step() == 2 + 4);
+        assert!(cpu.cycles == 8 + 4);
+        // the rom mirrors the code at $c000
+        assert!(cpu.memory.apu.dmc.buffer == Some(0xa9) && cpu.memory.apu.dmc.bytes_remaining == 0);
+        // one cycle less when the halt lands on an odd cycle
+        cpu.memory.apu.dmc.buffer = None;
+        cpu.memory.apu.dmc.bytes_remaining = 1;
+        assert!(cpu.memory.tick(Some(0x8000), true) == 3);
+        // writes are not halted
+        cpu.memory.apu.dmc.buffer = None;
+        cpu.memory.apu.dmc.bytes_remaining = 1;
+        assert!(cpu.memory.tick(None, true) == 0);
+    }
+    #[test]
+    fn test_dmc_during_oam_dma() {
+        // lda #2, sta $4014, nop
+        let mut cpu = cpu(&[0xa9, 0x02, 0x8d, 0x14, 0x40, 0xea]);
+        dmc_request(&mut cpu, 100);
+        cpu.step();
+        cpu.step();
+        assert!(cpu.step() == 2 + 513 + 2);
+        assert!(cpu.memory.apu.dmc.buffer.is_some() && cpu.memory.apu.dmc.bytes_remaining == 0);
+    }
+    #[test]
+    fn test_dmc_double_read() {
+        // lda $2007, halted on the read: the address is incremented twice
+        let mut cpu = cpu(&[0xad, 0x07, 0x20]);
+        dmc_request(&mut cpu, 2);
+        cpu.step();
+        assert!(cpu.memory.ppu.v == 2);
+    }
+    #[test]
+    fn test_dmc_controller_read() {
+        // bit 0 of lda $4016 with A held, and the cycles it took
+        let read = |timer| {
+            let mut cpu = cpu(&[0xad, 0x16, 0x40]);
+            cpu.memory.input.set_buttons(0, BUTTON_A);
+            cpu.memory.write(0x4016, 1);
+            cpu.memory.write(0x4016, 0);
+            dmc_request(&mut cpu, timer);
+            let cycles = cpu.step();
+            assert!(cpu.memory.apu.dmc.buffer.is_some());
+            (cpu.reg_a & 1, cycles)
+        };
+        // halted on the operand fetch
+        assert!(read(0) == (1, 4 + 3));
+        // halted on the read of $4016, the repeated read shifts A out
+        assert!(read(2) == (0, 4 + 3));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod tests {
+    use unes_cpu::{Bus, CPU};
+    use unes_ppu::{PPU, WIDTH};
+
+    use crate::NesBus;
+    use crate::input::{Device, FourScore, Keyboard, PowerPad, Vaus, Zapper};
+    use crate::input::joypad::*;
+    use crate::tests::cpu;
+
+    #[test]
+    fn test_joypad() {
+        let mut cpu = cpu(&[]);
+        cpu.memory.input.set_buttons(0, BUTTON_A | BUTTON_START | BUTTON_RIGHT);
+        cpu.memory.write(0x4016, 1);
+        cpu.memory.write(0x4016, 0);
+        let bits: Vec<u8> = (0..8).map(|_| cpu.memory.read(0x4016) & 1).collect();
+        assert!(bits == [1, 0, 0, 1, 0, 0, 0, 1]);
+        // official controllers return 1s after the 8 buttons
+        assert!(cpu.memory.read(0x4016) & 1 == 1);
+        // the other port was not shifted
+        assert!(cpu.memory.read(0x4017) & 1 == 0);
+    }
+    #[test]
+    fn test_strobe_held() {
+        let mut cpu = cpu(&[]);
+        cpu.memory.input.set_buttons(1, BUTTON_A);
+        cpu.memory.write(0x4016, 1);
+        for _ in 0..10 { assert!(cpu.memory.read(0x4017) & 1 == 1) }
+        // the register follows the buttons while the strobe is high
+        cpu.memory.input.set_buttons(1, BUTTON_B);
+        assert!(cpu.memory.read(0x4017) & 1 == 0);
+        cpu.memory.write(0x4016, 0);
+        assert!(cpu.memory.read(0x4017) & 1 == 0 && cpu.memory.read(0x4017) & 1 == 1);
+    }
+    #[test]
+    fn test_open_bus() {
+        // lda $4016 leaves $40 (the high byte of the operand) on the bus
+        let mut cpu = cpu(&[0xad, 0x16, 0x40]);
+        cpu.memory.input.set_buttons(0, BUTTON_A);
+        cpu.memory.write(0x4016, 1);
+        cpu.step();
+        assert!(cpu.reg_a == 0x41);
+    }
+    #[test]
+    fn test_four_score() {
+        let mut cpu = cpu(&[]);
+        cpu.memory.input.ports = [Device::None; 2];
+        cpu.memory.input.expansion = Device::FourScore(FourScore::new());
+        for (player, buttons) in [0x01, 0x02, 0x80, 0x40].into_iter().enumerate() {
+            cpu.memory.input.set_buttons(player, buttons);
+        }
+        cpu.memory.write(0x4016, 1);
+        cpu.memory.write(0x4016, 0);
+        let mut streams = [0u32; 2];
+        for bit in 0..24 {
+            for (port, stream) in streams.iter_mut().enumerate() {
+                *stream |= ((cpu.memory.read(0x4016 + port as u16) & 1) as u32) << bit;
+            }
+        }
+        // players 1 and 3, then the signature
+        assert!(streams[0] == 0x01 | 0x80 << 8 | 0x08 << 16);
+        assert!(streams[1] == 0x02 | 0x40 << 8 | 0x04 << 16);
+        assert!(cpu.memory.read(0x4016) & 1 == 1);
+    }
+    #[test]
+    fn test_hori() {
+        let mut cpu = cpu(&[]);
+        cpu.memory.input.expansion = Device::FourScore(FourScore::hori());
+        for player in 0..4 { cpu.memory.input.set_buttons(player, 1 << player) }
+        cpu.memory.write(0x4016, 1);
+        cpu.memory.write(0x4016, 0);
+        let mut streams = [0u32; 2];
+        for bit in 0..24 {
+            for (port, stream) in streams.iter_mut().enumerate() {
+                let value = cpu.memory.read(0x4016 + port as u16);
+                // the built-in controllers stay on d0
+                if bit < 8 { assert!(value & 1 == (1 << port >> bit & 1) as u8) }
+                *stream |= ((value >> 1 & 1) as u32) << bit;
+            }
+        }
+        assert!(streams[0] == 0x04 | 0x04 << 16);
+        assert!(streams[1] == 0x08 | 0x08 << 16);
+    }
+    #[test]
+    fn test_zapper() {
+        let mut cpu = cpu(&[]);
+        cpu.memory.input.ports[1] = Device::Zapper(Zapper { aim: Some((100, 50)), trigger: false });
+        // a white pixel near the aim, drawn 5 scanlines ago
+        cpu.memory.ppu.framebuffer[51 * WIDTH + 101] = 0x30;
+        cpu.memory.ppu.scanline = 56;
+        assert!(cpu.memory.read(0x4017) & 0x18 == 0x00);
+        // not drawn yet in this frame
+        cpu.memory.ppu.scanline = 51;
+        cpu.memory.ppu.dot = 100;
+        assert!(cpu.memory.read(0x4017) & 0x18 == 0x08);
+        cpu.memory.ppu.dot = 103;
+        assert!(cpu.memory.read(0x4017) & 0x18 == 0x00);
+        // the sensor stopped reacting
+        cpu.memory.ppu.scanline = 80;
+        assert!(cpu.memory.read(0x4017) & 0x18 == 0x08);
+        // the trigger, $4016 is not wired
+        cpu.memory.input.ports[1] = Device::Zapper(Zapper { aim: None, trigger: true });
+        assert!(cpu.memory.read(0x4017) & 0x18 == 0x18);
+    }
+    #[test]
+    fn test_zapper_brightness() {
+        let mut ppu = PPU::new();
+        let zapper = Zapper { aim: Some((10, 10)), trigger: false };
+        ppu.scanline = 12;
+        for (color, light) in [(0x20, true), (0x3c, true), (0x16, false), (0x2d, false), (0x0f, false)] {
+            ppu.framebuffer[10 * WIDTH + 10] = color;
+            assert!(zapper.light(&ppu) == light);
+        }
+        // greyscale and emphasis bits are ignored
+        ppu.framebuffer[10 * WIDTH + 10] = 0x1c0 | 0x21;
+        assert!(zapper.light(&ppu));
+        // too far from the aim
+        ppu.framebuffer[10 * WIDTH + 10] = 0;
+        ppu.framebuffer[10 * WIDTH + 13] = 0x30;
+        assert!(!zapper.light(&ppu));
+    }
+    // the bits of `mask` for 8 reads of `addr`, first read in bit 0
+    fn serial(cpu: &mut CPU<NesBus>, addr: u16, mask: u8) -> u8 {
+        (0..8).fold(0, |bits, i| bits | ((cpu.memory.read(addr) & mask != 0) as u8) << i)
+    }
+
+    #[test]
+    fn test_vaus() {
+        let mut cpu = cpu(&[]);
+        let mut paddle = Vaus::nes();
+        paddle.position = 0x9c;
+        paddle.button = true;
+        cpu.memory.input.ports[1] = Device::Vaus(paddle);
+        cpu.memory.write(0x4016, 1);
+        cpu.memory.write(0x4016, 0);
+        assert!(cpu.memory.read(0x4017) & 0x08 == 0x08);
+        // inverted, msb first: !$9c = %01100011
+        assert!(serial(&mut cpu, 0x4017, 0x10) == 0b1100_0110 >> 1);
+        paddle.famicom = true;
+        cpu.memory.input.ports[1] = Device::None;
+        cpu.memory.input.expansion = Device::Vaus(paddle);
+        cpu.memory.write(0x4016, 1);
+        cpu.memory.write(0x4016, 0);
+        assert!(cpu.memory.read(0x4016) & 0x02 == 0x02);
+        assert!(serial(&mut cpu, 0x4017, 0x02) == 0b1100_0110);
+    }
+    #[test]
+    fn test_power_pad() {
+        let mut cpu = cpu(&[]);
+        // buttons 1, 9 and 12
+        let mut mat = PowerPad::nes();
+        mat.buttons = 0b1001_0000_0001;
+        cpu.memory.input.ports[1] = Device::PowerPad(mat);
+        cpu.memory.write(0x4016, 1);
+        cpu.memory.write(0x4016, 0);
+        let mut bits = [0u8; 2];
+        for i in 0..8 {
+            let value = cpu.memory.read(0x4017);
+            bits[0] |= (value >> 3 & 1) << i;
+            bits[1] |= (value >> 4 & 1) << i;
+        }
+        // d3 sends 2, 1, 5, 9..., d4 sends 4, 3, 12, 8 then 1s
+        assert!(bits == [0b0000_1010, 0b1111_0100]);
+    }
+    #[test]
+    fn test_family_trainer() {
+        let mut cpu = cpu(&[]);
+        // buttons 2 and 7
+        let mut mat = PowerPad::family_trainer();
+        mat.buttons = 0b0000_0100_0010;
+        cpu.memory.input.expansion = Device::PowerPad(mat);
+        cpu.memory.write(0x4016, 0b011);
+        assert!(cpu.memory.read(0x4017) & 0x1e == 0b11010);
+        cpu.memory.write(0x4016, 0b101);
+        assert!(cpu.memory.read(0x4017) & 0x1e == 0b10110);
+        cpu.memory.write(0x4016, 0b110);
+        assert!(cpu.memory.read(0x4017) & 0x1e == 0x1e);
+    }
+    #[test]
+    fn test_keyboard() {
+        let mut cpu = cpu(&[]);
+        let mut keyboard = Keyboard::new();
+        keyboard.keys[0] = 0x01;
+        keyboard.keys[1] = 0x80;
+        cpu.memory.input.expansion = Device::Keyboard(keyboard);
+        // disabled
+        assert!(cpu.memory.read(0x4017) & 0x1e == 0);
+        cpu.memory.write(0x4016, 0b101);
+        cpu.memory.write(0x4016, 0b100);
+        assert!(cpu.memory.read(0x4017) & 0x1e == 0x1c);
+        cpu.memory.write(0x4016, 0b110);
+        assert!(cpu.memory.read(0x4017) & 0x1e == 0x1e);
+        // the next row, second column
+        cpu.memory.write(0x4016, 0b100);
+        cpu.memory.write(0x4016, 0b110);
+        assert!(cpu.memory.read(0x4017) & 0x1e == 0x0e);
+        // past the last row
+        for _ in 0..9 {
+            cpu.memory.write(0x4016, 0b100);
+            cpu.memory.write(0x4016, 0b110);
+        }
+        assert!(cpu.memory.read(0x4017) & 0x1e == 0x1e);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use unes_apu::APU;
+    use unes_cartridge::audio::ExpansionAudio;
+
+    use crate::{Console, Mixer, Region};
+    use crate::audio::AudioOutput;
+    use crate::tests::{cpu, rom};
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_dac_curves() {
+        let mut mixer = Mixer::new(None);
+        for (outputs, expected) in [
+            ([0, 0, 0, 0, 0], 0.0),
+            ([15, 0, 0, 0, 0], 95.52 / (8128.0 / 15.0 + 100.0)),
+            ([15, 15, 0, 0, 0], 95.52 / (8128.0 / 30.0 + 100.0)),
+            ([0, 0, 15, 15, 127], 163.67 / (24329.0 / (45.0 + 30.0 + 127.0) + 100.0))
+        ] {
+            assert!(approx(mixer.mix_outputs(outputs, &[]), expected));
+        }
+        // two pulses are quieter than twice one
+        let one = mixer.mix_outputs([8, 0, 0, 0, 0], &[]);
+        assert!(mixer.mix_outputs([8, 8, 0, 0, 0], &[]) < 2.0 * one);
+    }
+    #[test]
+    fn test_gain_and_mute() {
+        let mut mixer = Mixer::new(None);
+        let full = mixer.mix_outputs([10, 10, 0, 0, 0], &[]);
+        mixer.gains[0] = 0.5;
+        assert!(approx(mixer.mix_outputs([10, 10, 0, 0, 0], &[]), full * 0.75));
+        // muting keeps the gain
+        mixer.muted[0] = true;
+        assert!(approx(mixer.mix_outputs([10, 10, 0, 0, 0], &[]), full * 0.5));
+        mixer.muted[0] = false;
+        assert!(mixer.gains[0] == 0.5);
+        // the levels split the dac output between the channels
+        let mixed = mixer.mix_outputs([0, 0, 4, 4, 4], &[]);
+        assert!(approx(mixer.levels[2], mixed * 12.0 / 24.0) && approx(mixer.levels[4], mixed * 4.0 / 24.0));
+    }
+    struct Chip;
+    impl ExpansionAudio for Chip {
+        fn channels(&self) -> &'static [&'static str] {
+            &["pulse 3", "saw"]
+        }
+        fn channel_output(&self, _channel: usize) -> f32 {
+            0.1
+        }
+    }
+
+    #[test]
+    fn test_expansion_channels() {
+        let mut mixer = Mixer::new(Some(&Chip));
+        assert!(mixer.names[5..] == ["pulse 3", "saw"]);
+        mixer.muted[5] = true;
+        mixer.gains[6] = 2.0;
+        mixer.mix(&APU::new(), Some(&Chip));
+        assert!(mixer.levels[5] == 0.0 && approx(mixer.levels[6], 0.2));
+        // mixed linearly on top of the apu
+        assert!(approx(mixer.mix_outputs([0; 5], &[0.1, 0.3]), 0.6));
+    }
+    #[test]
+    fn test_stems() {
+        let mut audio = AudioOutput::default();
+        let mut mixer = Mixer::new(None);
+        assert!(mixer.take_stems().is_empty());
+        mixer.enable_stems(&audio);
+        for cycle in 0..20_000u32 {
+            let outputs = [(cycle >> 6 & 15) as u8, 7, (cycle >> 4 & 15) as u8, 3, (cycle >> 8 & 127) as u8];
+            audio.clock(mixer.mix_outputs(outputs, &[]));
+        }
+        let stems = mixer.take_stems();
+        let mixed = audio.take_f32();
+        assert!(stems.len() == 5 && stems[0].len() == mixed.len());
+        for (i, sample) in mixed.iter().enumerate() {
+            assert!((stems.iter().map(|stem| stem[i]).sum::<f32>() - sample).abs() < 1e-4);
+        }
+        mixer.disable_stems();
+        assert!(mixer.take_stems().is_empty());
+    }
+    #[test]
+    fn test_stems_follow_rates() {
+        let mut console = Console::from_rom(&rom(&[], &[], &[])).unwrap();
+        let bus = &mut console.cpu.memory;
+        bus.mixer.enable_stems(&bus.audio);
+        console.set_region(Region::Pal);
+        console.set_sample_rate(44_100);
+        console.run_frame();
+        let stems = console.cpu.memory.mixer.take_stems();
+        assert!(stems[0].len() == console.audio_samples().len());
+    }
+    #[test]
+    fn test_bus_mixer() {
+        let mut cpu = cpu(&[]);
+        // nrom has no expansion audio
+        assert!(cpu.memory.mixer.names == ["pulse 1", "pulse 2", "triangle", "noise", "dmc"]);
+        for _ in 0..1_000 { cpu.memory.clock() }
+        assert!(cpu.memory.audio.len() == 26);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,41 @@
+mod apu;
+mod audio;
+mod console;
+mod dma;
+mod input;
+mod mixer;
+mod palette;
+mod region;
+mod rewind;
+mod state;
+
+#[cfg(test)]
+use unes_cartridge::Cartridge;
+#[cfg(test)]
+use unes_cpu::CPU;
+#[cfg(test)]
+use crate::NesBus;
+
+// NROM with 16KB of prg rom holding `code` at $8000 (the reset vector),
+// `nmi` at $9000 and `irq` at $a000
+#[cfg(test)]
+pub fn rom(code: &[u8], nmi: &[u8], irq: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![b'N', b'E', b'S', 0x1a, 1, 1, 0, 0];
+    bytes.extend_from_slice(&[0; 8]);
+    let mut prg = vec![0; 0x4000];
+    prg[..code.len()].copy_from_slice(code);
+    prg[0x1000..0x1000 + nmi.len()].copy_from_slice(nmi);
+    prg[0x2000..0x2000 + irq.len()].copy_from_slice(irq);
+    prg[0x3ffa..].copy_from_slice(&[0x00, 0x90, 0x00, 0x80, 0x00, 0xa0]);
+    bytes.extend(prg);
+    bytes.extend_from_slice(&[0; 0x2000]);
+    bytes
+}
+
+// the cpu of a console running `code`, starting at $8000
+#[cfg(test)]
+pub fn cpu(code: &[u8]) -> CPU<NesBus> {
+    let mut cpu = CPU::with_bus(NesBus::new(Cartridge::from_bytes(&rom(code, &[], &[])).unwrap()));
+    cpu.pc = 0x8000;
+    cpu
+}
\ No newline at end of file
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use crate::Palette;
+    use crate::palette::{NtscParams, PaletteError};
+
+    fn brightest(rgb: [u8; 3]) -> usize {
+        (0..3).max_by_key(|&c| rgb[c]).unwrap()
+    }
+
+    #[test]
+    fn test_ntsc_greys() {
+        let palette = Palette::default();
+        for row in 0..4 {
+            for column in [0x0, 0xd, 0xe, 0xf] {
+                let [r, g, b] = palette.rgb(row << 4 | column);
+                assert!(r == g && g == b);
+            }
+        }
+        assert!(palette.rgb(0x0f) == [0, 0, 0] && palette.rgb(0x30) == [255, 255, 255]);
+        assert!(palette.rgb(0x00)[0] < palette.rgb(0x10)[0]);
+    }
+    #[test]
+    fn test_ntsc_hues() {
+        let palette = Palette::default();
+        assert!(brightest(palette.rgb(0x16)) == 0);
+        assert!(brightest(palette.rgb(0x1a)) == 1);
+        assert!(brightest(palette.rgb(0x12)) == 2);
+        let greys = Palette::ntsc(&NtscParams { saturation: 0.0, ..Default::default() });
+        let [r, g, b] = greys.rgb(0x16);
+        assert!(r == g && g == b);
+    }
+    #[test]
+    fn test_ntsc_emphasis() {
+        let palette = Palette::default();
+        // red emphasis tints white
+        let [r, g, b] = palette.rgb(0x40 | 0x30);
+        assert!(r > g && r > b && r < 255);
+        // all three darken it
+        let [r, g, b] = palette.rgb(0x1c0 | 0x30);
+        assert!(r == g && g == b && r < 200);
+        // but not the blacks
+        assert!(palette.rgb(0x1c0 | 0x0f) == [0, 0, 0]);
+        assert!(palette.rgb(0x100 | 0x1e) == [0, 0, 0]);
+    }
+    #[test]
+    fn test_pal_64() {
+        let bytes: Vec<u8> = (0..64 * 3).map(|i| (i % 200) as u8 + 50).collect();
+        let palette = Palette::from_pal(&bytes).unwrap();
+        assert!(palette.rgb(0x05) == [65, 66, 67]);
+        // green emphasis
+        assert!(palette.rgb(0x85) == [48, 66, 50]);
+        assert!(palette.rgb(0x8e) == palette.rgb(0x0e));
+    }
+    #[test]
+    fn test_pal_512() {
+        let bytes: Vec<u8> = (0..512 * 3).map(|i| (i / 3 / 2) as u8).collect();
+        let palette = Palette::from_pal(&bytes).unwrap();
+        assert!(palette.rgb(0x1ff) == [255, 255, 255] && palette.rgb(0x85) == [66, 66, 66]);
+        assert!(Palette::from_pal(&[0; 100]).err() == Some(PaletteError::InvalidSize(100)));
+    }
+    #[test]
+    fn test_to_rgb() {
+        let palette = Palette::default();
+        let rgb = palette.to_rgb(&[0x0f, 0x30]);
+        assert!(rgb == [0, 0, 0, 255, 255, 255]);
+    }
+}
\ No newline at end of file
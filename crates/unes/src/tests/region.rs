@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use unes_cartridge::Timing;
+
+    use crate::{Console, Region};
+    use crate::tests::rom;
+
+    const LOOP: [u8; 3] = [0x4c, 0x00, 0x80];
+
+    // the cpu cycles of a frame, after a first one to line up
+    fn frame_cycles(console: &mut Console) -> u64 {
+        console.run_frame();
+        let cycles = console.cpu.cycles;
+        console.run_frame();
+        console.cpu.cycles - cycles
+    }
+
+    #[test]
+    fn test_from_timing() {
+        assert!(Region::from(Timing::Pal) == Region::Pal);
+        assert!(Region::from(Timing::MultiRegion) == Region::Ntsc);
+        // nes 2.0 header with the dendy timing
+        let mut bytes = rom(&LOOP, &[], &[]);
+        bytes[7] = 0x08;
+        bytes[12] = 3;
+        let console = Console::from_rom(&bytes).unwrap();
+        assert!(console.region() == Region::Dendy);
+        assert!(console.cpu.memory.ppu.layout.vblank_scanline == 291);
+    }
+    #[test]
+    fn test_pal() {
+        let mut console = Console::from_rom(&rom(&LOOP, &[], &[])).unwrap();
+        console.set_region(Region::Pal);
+        // 341 * 312 dots, 3.2 per cpu cycle
+        assert!(frame_cycles(&mut console).abs_diff(33248) <= 3);
+        assert!(console.cpu.memory.audio.clock_rate == 26_601_712.0 / 16.0);
+        assert!(console.cpu.memory.apu.frame_counter.pal);
+    }
+    #[test]
+    fn test_dendy() {
+        let mut console = Console::from_rom(&rom(&LOOP, &[], &[])).unwrap();
+        console.set_region(Region::Dendy);
+        // same frame as pal at 3 dots per cycle, ntsc apu
+        assert!(frame_cycles(&mut console).abs_diff(35464) <= 3);
+        assert!(!console.cpu.memory.apu.frame_counter.pal);
+        assert!((Region::Dendy.frame_rate() - 50.0).abs() < 0.01);
+        assert!((Region::Ntsc.frame_rate() - 60.0988).abs() < 0.001);
+    }
+}
\ No newline at end of file
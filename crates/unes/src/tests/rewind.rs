@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Console, Rewind};
+    use crate::input::joypad::BUTTON_A;
+    use crate::tests::rom;
+
+    // every nmi adds the A button of the first joypad to $00
+    fn console() -> Console {
+        let code = [0xa9, 0x80, 0x8d, 0x00, 0x20, 0x4c, 0x05, 0x80];
+        let nmi = [
+            0xa9, 0x01, 0x8d, 0x16, 0x40, 0xa9, 0x00, 0x8d, 0x16, 0x40,
+            0xad, 0x16, 0x40, 0x29, 0x01, 0x18, 0x65, 0x00, 0x85, 0x00, 0x40
+        ];
+        Console::from_rom(&rom(&code, &nmi, &[])).unwrap()
+    }
+
+    #[test]
+    fn test_step_back() {
+        let mut console = console();
+        let mut rewind = Rewind::new(4, usize::MAX);
+        // cycles and $00 at the start of each frame
+        let mut history = vec![(console.cpu.cycles, 0)];
+        for frame in 0..10 {
+            console.set_input(0, if frame % 3 == 0 { BUTTON_A } else { 0 });
+            rewind.run_frame(&mut console);
+            history.push((console.cpu.cycles, console.cpu.memory.ram[0]));
+        }
+        assert!(history[10].1 == 4 && rewind.available() == 10);
+        // the frames are replayed with the input they had
+        for frame in (0..10).rev() {
+            assert!(rewind.step_back(&mut console) == Ok(true));
+            assert!(rewind.frame() == frame && (console.cpu.cycles, console.cpu.memory.ram[0]) == history[frame as usize]);
+        }
+        assert!(rewind.step_back(&mut console) == Ok(false));
+    }
+    #[test]
+    fn test_run_after_rewind() {
+        let mut console = console();
+        let mut rewind = Rewind::new(4, usize::MAX);
+        for _ in 0..9 { rewind.run_frame(&mut console) }
+        assert!(rewind.rewind(&mut console, 3) == Ok(3));
+        // a new branch from frame 6, pressing A this time
+        console.set_input(0, BUTTON_A);
+        for _ in 0..3 { rewind.run_frame(&mut console) }
+        assert!(console.cpu.memory.ram[0] == 3);
+        assert!(rewind.rewind(&mut console, 2) == Ok(2));
+        assert!(console.cpu.memory.ram[0] == 1 && rewind.frame() == 7);
+    }
+    #[test]
+    fn test_capacity() {
+        let mut console = console();
+        let mut rewind = Rewind::new(2, 16 * 1024);
+        for _ in 0..100 { rewind.run_frame(&mut console) }
+        // older states are dropped, newer ones only cost their delta
+        assert!(rewind.size() <= 16 * 1024);
+        let available = rewind.available();
+        assert!(available > 10 && available < 100);
+        assert!(rewind.rewind(&mut console, 1000) == Ok(available));
+        assert!(rewind.frame() == 100 - available);
+    }
+}
\ No newline at end of file
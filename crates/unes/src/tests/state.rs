@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use unes_state::{Chunk, Snapshot, State, StateWriter};
+
+    use crate::{Console, Region, StateError};
+    use crate::tests::rom;
+
+    // nmi and rendering on, every apu channel enabled, then inx in a loop
+    // the nmi handler does inc $00, rti
+    const CODE: [u8; 19] = [
+        0xa9, 0x80, 0x8d, 0x00, 0x20, 0xa9, 0x1e, 0x8d, 0x01, 0x20,
+        0xa9, 0x0f, 0x8d, 0x15, 0x40, 0xe8, 0x4c, 0x0f, 0x80
+    ];
+
+    fn console() -> Console {
+        Console::from_rom(&rom(&CODE, &[0xe6, 0x00, 0x40], &[])).unwrap()
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut console = console();
+        for _ in 0..2 { console.run_frame() }
+        let state = console.save_state();
+        let cycles = console.cpu.cycles;
+        for _ in 0..3 { console.run_frame() }
+        let ram = console.cpu.memory.ram;
+        let framebuffer = console.framebuffer().to_vec();
+        let (x, after) = (console.cpu.reg_x, console.cpu.cycles);
+        console.load_state(&state).unwrap();
+        assert!(console.cpu.cycles == cycles && console.cpu.memory.ram[0] == 2);
+        // and runs the same frames again
+        for _ in 0..3 { console.run_frame() }
+        assert!(console.cpu.memory.ram == ram && console.framebuffer() == framebuffer);
+        assert!(console.cpu.reg_x == x && console.cpu.cycles == after);
+    }
+    #[test]
+    fn test_other_game() {
+        let mut console = console();
+        console.run_frame();
+        let other = Console::from_rom(&rom(&CODE, &[], &[])).unwrap();
+        let cycles = console.cpu.cycles;
+        assert!(console.load_state(&other.save_state()) == Err(StateError::CartridgeMismatch));
+        assert!(console.cpu.cycles == cycles);
+    }
+    #[test]
+    fn test_region() {
+        let (mut pal, mut fresh) = (console(), console());
+        pal.set_region(Region::Pal);
+        pal.run_frame();
+        fresh.load_state(&pal.save_state()).unwrap();
+        assert!(fresh.region() == Region::Pal && fresh.cpu.memory.ppu.layout.scanlines == 312);
+        assert!(fresh.cpu.memory.audio.clock_rate == Region::Pal.cpu_clock());
+    }
+    #[test]
+    fn test_corrupted() {
+        let mut console = console();
+        console.run_frame();
+        let mut state = console.save_state();
+        let cycles = console.cpu.cycles;
+        // the expansion port device, last in the state, of an unknown kind
+        let len = state.len();
+        state[len - 1] = 9;
+        assert!(console.load_state(&state) == Err(StateError::InvalidData));
+        assert!(console.load_state(&state[..len - 1]) == Err(StateError::Truncated));
+        assert!(console.cpu.cycles == cycles);
+    }
+    #[test]
+    fn test_bus_version_2() {
+        // ram, open bus, dma page, the address of the last read, nmi line and edge
+        let mut w = StateWriter::new();
+        w.chunk(Chunk { tag: *b"BUS ", version: 2 }, |w| {
+            [0x55u8; 0x800].save(w);
+            0x12u8.save(w);
+            Some(0x02u8).save(w);
+            0x2007u16.save(w);
+            false.save(w);
+            true.save(w);
+        });
+        let data = w.finish();
+        let mut console = console();
+        let bus = &mut console.cpu.memory;
+        bus.load(&mut State::parse(&data).unwrap().chunk(Chunk::BUS).unwrap()).unwrap();
+        assert!(bus.ram == [0x55; 0x800] && bus.open_bus == 0x12 && bus.dma_page == Some(2));
+        assert!(!bus.nmi_line && bus.nmi_edge);
+    }
+}
\ No newline at end of file
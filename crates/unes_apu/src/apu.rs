@@ -0,0 +1,127 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::dmc::Dmc;
+use crate::frame_counter::FrameCounter;
+use crate::noise::Noise;
+use crate::pulse::Pulse;
+use crate::triangle::Triangle;
+
+// registers $4000-$4013, $4015 and $4017, clocked once per cpu cycle
+pub struct APU {
+    pub pulse1: Pulse,
+    pub pulse2: Pulse,
+    pub triangle: Triangle,
+    pub noise: Noise,
+    pub dmc: Dmc,
+    pub frame_counter: FrameCounter,
+    pub cycle: u64
+}
+impl Default for APU {
+    fn default() -> Self {
+        APU {
+            pulse1: Pulse::new(true),
+            pulse2: Pulse::new(false),
+            triangle: Triangle::default(),
+            noise: Noise::default(),
+            dmc: Dmc::default(),
+            frame_counter: FrameCounter::default(),
+            cycle: 0
+        }
+    }
+}
+impl APU {
+    pub fn new() -> APU {
+        APU::default()
+    }
+    // pal timings of the frame counter, noise and dmc, the periods already
+    // loaded are kept until the next register write
+    pub fn set_pal(&mut self, pal: bool) {
+        self.frame_counter.pal = pal;
+        self.noise.pal = pal;
+        self.dmc.pal = pal;
+    }
+    // $4015: length counters, dmc bytes left and both irqs, reading acknowledges the frame irq
+    // Bit 5 is open bus, left clear.
+    pub fn read_status(&mut self) -> u8 {
+        let value = self.pulse1.length.active() as u8
+            | (self.pulse2.length.active() as u8) << 1
+            | (self.triangle.length.active() as u8) << 2
+            | (self.noise.length.active() as u8) << 3
+            | ((self.dmc.bytes_remaining > 0) as u8) << 4
+            | (self.frame_counter.irq as u8) << 6
+            | (self.dmc.irq as u8) << 7;
+        self.frame_counter.irq = false;
+        value
+    }
+    pub fn write_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x4000..=0x4003 => self.pulse1.write(addr, value),
+            0x4004..=0x4007 => self.pulse2.write(addr, value),
+            0x4008..=0x400b => self.triangle.write(addr, value),
+            0x400c..=0x400f => self.noise.write(addr, value),
+            0x4010..=0x4013 => self.dmc.write(addr, value),
+            0x4015 => {
+                self.pulse1.length.set_enabled(value & 0x01 != 0);
+                self.pulse2.length.set_enabled(value & 0x02 != 0);
+                self.triangle.length.set_enabled(value & 0x04 != 0);
+                self.noise.length.set_enabled(value & 0x08 != 0);
+                self.dmc.set_enabled(value & 0x10 != 0);
+            },
+            0x4017 => self.frame_counter.write(value, self.cycle & 1 == 1),
+            _ => ()
+        }
+    }
+    pub fn clock(&mut self) {
+        self.triangle.clock_timer();
+        self.noise.clock_timer();
+        self.dmc.clock_timer();
+        if self.cycle & 1 == 1 {
+            self.pulse1.clock_timer();
+            self.pulse2.clock_timer();
+        }
+        let frame = self.frame_counter.clock();
+        if frame.quarter {
+            self.pulse1.envelope.clock();
+            self.pulse2.envelope.clock();
+            self.noise.envelope.clock();
+            self.triangle.clock_linear();
+        }
+        if frame.half {
+            self.pulse1.length.clock();
+            self.pulse2.length.clock();
+            self.triangle.length.clock();
+            self.noise.length.clock();
+            self.pulse1.clock_sweep();
+            self.pulse2.clock_sweep();
+        }
+        self.cycle += 1;
+    }
+    pub fn irq(&self) -> bool {
+        self.frame_counter.irq || self.dmc.irq
+    }
+    // current dac inputs: pulse 1, pulse 2, triangle, noise (0-15) and dmc (0-127)
+    pub fn channel_outputs(&self) -> [u8; 5] {
+        [self.pulse1.output(), self.pulse2.output(), self.triangle.output(), self.noise.output(), self.dmc.output()]
+    }
+}
+// the pal flags are not saved, they follow the region
+impl Snapshot for APU {
+    fn save(&self, w: &mut StateWriter) {
+        self.pulse1.save(w);
+        self.pulse2.save(w);
+        self.triangle.save(w);
+        self.noise.save(w);
+        self.dmc.save(w);
+        self.frame_counter.save(w);
+        self.cycle.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.pulse1.load(r)?;
+        self.pulse2.load(r)?;
+        self.triangle.load(r)?;
+        self.noise.load(r)?;
+        self.dmc.load(r)?;
+        self.frame_counter.load(r)?;
+        self.cycle.load(r)
+    }
+}
\ No newline at end of file
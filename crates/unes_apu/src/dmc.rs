@@ -0,0 +1,158 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// timer periods in cpu cycles (NTSC)
+pub const RATES: [u16; 16] = [428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54];
+pub const PAL_RATES: [u16; 16] = [398, 354, 316, 298, 276, 236, 210, 198, 176, 148, 132, 118, 98, 78, 66, 50];
+
+// delta modulation channel, $4010-$4013
+// The output unit moves a 7 bit level up or down by 2 for every bit of the
+// sample bytes. Those come from cpu memory through the reader: whenever the
+// one byte buffer is empty `pending_read` has an address and the bus answers
+// with `fill`.
+pub struct Dmc {
+    pub pal: bool,
+    pub irq_enabled: bool,
+    pub looping: bool,
+    pub rate: u16,
+    pub timer: u16,
+    pub level: u8,
+    pub sample_address: u16,
+    pub sample_length: u16,
+    pub current_address: u16,
+    pub bytes_remaining: u16,
+    pub buffer: Option<u8>,
+    pub shift: u8,
+    pub bits_remaining: u8,
+    pub silence: bool,
+    pub irq: bool
+}
+impl Default for Dmc {
+    fn default() -> Self {
+        Dmc {
+            pal: false,
+            irq_enabled: false,
+            looping: false,
+            rate: RATES[0],
+            timer: 0,
+            level: 0,
+            sample_address: 0xc000,
+            sample_length: 1,
+            current_address: 0xc000,
+            bytes_remaining: 0,
+            buffer: None,
+            shift: 0,
+            bits_remaining: 0,
+            silence: true,
+            irq: false
+        }
+    }
+}
+impl Dmc {
+    pub fn write(&mut self, reg: u16, value: u8) {
+        match reg & 3 {
+            0 => {
+                self.irq_enabled = value & 0x80 != 0;
+                if !self.irq_enabled { self.irq = false }
+                self.looping = value & 0x40 != 0;
+                self.rate = if self.pal { PAL_RATES } else { RATES }[value as usize & 0x0f];
+            },
+            1 => self.level = value & 0x7f,
+            2 => self.sample_address = 0xc000 | (value as u16) << 6,
+            _ => self.sample_length = (value as u16) << 4 | 1
+        }
+    }
+    // $4015 bit 4, also acknowledges the irq
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.irq = false;
+        if !enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart();
+        }
+    }
+    fn restart(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+    pub fn pending_read(&self) -> Option<u16> {
+        if self.buffer.is_none() && self.bytes_remaining > 0 { Some(self.current_address) } else { None }
+    }
+    pub fn fill(&mut self, value: u8) {
+        self.buffer = Some(value);
+        // the address wraps to $8000
+        self.current_address = self.current_address.checked_add(1).unwrap_or(0x8000);
+        self.bytes_remaining -= 1;
+        if self.bytes_remaining == 0 {
+            if self.looping {
+                self.restart();
+            } else if self.irq_enabled {
+                self.irq = true;
+            }
+        }
+    }
+    // clocked every cpu cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return
+        }
+        self.timer = self.rate - 1;
+        if !self.silence {
+            if self.shift & 1 != 0 {
+                if self.level <= 125 { self.level += 2 }
+            } else if self.level >= 2 {
+                self.level -= 2;
+            }
+        }
+        self.shift >>= 1;
+        self.bits_remaining = self.bits_remaining.saturating_sub(1);
+        if self.bits_remaining == 0 {
+            // a new output cycle takes the buffered byte, or stays silent without one
+            self.bits_remaining = 8;
+            match self.buffer.take() {
+                Some(byte) => {
+                    self.shift = byte;
+                    self.silence = false;
+                },
+                None => self.silence = true
+            }
+        }
+    }
+    pub fn output(&self) -> u8 {
+        self.level
+    }
+}
+impl Snapshot for Dmc {
+    fn save(&self, w: &mut StateWriter) {
+        self.irq_enabled.save(w);
+        self.looping.save(w);
+        self.rate.save(w);
+        self.timer.save(w);
+        self.level.save(w);
+        self.sample_address.save(w);
+        self.sample_length.save(w);
+        self.current_address.save(w);
+        self.bytes_remaining.save(w);
+        self.buffer.save(w);
+        self.shift.save(w);
+        self.bits_remaining.save(w);
+        self.silence.save(w);
+        self.irq.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.irq_enabled.load(r)?;
+        self.looping.load(r)?;
+        self.rate.load(r)?;
+        self.timer.load(r)?;
+        self.level.load(r)?;
+        self.sample_address.load(r)?;
+        self.sample_length.load(r)?;
+        self.current_address.load(r)?;
+        self.bytes_remaining.load(r)?;
+        self.buffer.load(r)?;
+        self.shift.load(r)?;
+        self.bits_remaining.load(r)?;
+        self.silence.load(r)?;
+        self.irq.load(r)
+    }
+}
\ No newline at end of file
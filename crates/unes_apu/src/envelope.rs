@@ -0,0 +1,59 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// volume of the pulse and noise channels: a constant level or
+// a sawtooth decaying from 15, clocked by the quarter frames
+#[derive(Default)]
+pub struct Envelope {
+    pub start: bool,
+    pub looping: bool,
+    pub constant: bool,
+    // constant volume or the divider period
+    pub volume: u8,
+    divider: u8,
+    decay: u8
+}
+impl Envelope {
+    // the low 6 bits of $4000/$4004/$400c
+    pub fn write(&mut self, value: u8) {
+        self.looping = value & 0x20 != 0;
+        self.constant = value & 0x10 != 0;
+        self.volume = value & 0x0f;
+    }
+    pub fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.volume;
+        } else if self.divider == 0 {
+            self.divider = self.volume;
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if self.looping {
+                self.decay = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+    pub fn output(&self) -> u8 {
+        if self.constant { self.volume } else { self.decay }
+    }
+}
+impl Snapshot for Envelope {
+    fn save(&self, w: &mut StateWriter) {
+        self.start.save(w);
+        self.looping.save(w);
+        self.constant.save(w);
+        self.volume.save(w);
+        self.divider.save(w);
+        self.decay.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.start.load(r)?;
+        self.looping.load(r)?;
+        self.constant.load(r)?;
+        self.volume.load(r)?;
+        self.divider.load(r)?;
+        self.decay.load(r)
+    }
+}
\ No newline at end of file
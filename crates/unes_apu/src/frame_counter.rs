@@ -0,0 +1,90 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// what a frame counter step clocks
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct FrameClock {
+    // envelopes and the triangle's linear counter
+    pub quarter: bool,
+    // length counters and sweeps
+    pub half: bool
+}
+const QUARTER: FrameClock = FrameClock { quarter: true, half: false };
+const HALF: FrameClock = FrameClock { quarter: true, half: true };
+
+// cpu cycle of each step, the 4 step sequence ends one cycle after the fourth
+const NTSC_STEPS: [u16; 5] = [7457, 14913, 22371, 29828, 37281];
+const PAL_STEPS: [u16; 5] = [8313, 16627, 24939, 33252, 41565];
+
+// $4017
+// The 4 step sequence raises the irq over its last three cycles unless inhibited.
+#[derive(Default)]
+pub struct FrameCounter {
+    pub pal: bool,
+    pub five_step: bool,
+    pub irq_inhibit: bool,
+    pub irq: bool,
+    pub cycle: u16,
+    // a write resets the sequence 3 or 4 cycles later
+    pub reset_delay: u8
+}
+impl FrameCounter {
+    pub fn write(&mut self, value: u8, odd_cycle: bool) {
+        self.five_step = value & 0x80 != 0;
+        self.irq_inhibit = value & 0x40 != 0;
+        if self.irq_inhibit { self.irq = false }
+        self.reset_delay = if odd_cycle { 4 } else { 3 };
+    }
+    pub fn clock(&mut self) -> FrameClock {
+        if self.reset_delay > 0 {
+            self.reset_delay -= 1;
+            if self.reset_delay == 0 {
+                self.cycle = 0;
+                // the 5 step mode clocks everything right away
+                if self.five_step { return HALF }
+            }
+        }
+        self.cycle += 1;
+        let steps = if self.pal { PAL_STEPS } else { NTSC_STEPS };
+        match (self.cycle, self.five_step) {
+            (c, _) if c == steps[0] || c == steps[2] => QUARTER,
+            (c, five_step) if c == steps[1] || five_step && c == steps[4] => HALF,
+            (c, false) if c == steps[3] => {
+                self.raise_irq();
+                FrameClock::default()
+            },
+            (c, false) if c == steps[3] + 1 => {
+                self.raise_irq();
+                HALF
+            },
+            (c, false) if c == steps[3] + 2 => {
+                self.raise_irq();
+                self.cycle = 0;
+                FrameClock::default()
+            },
+            (c, true) if c == steps[4] + 1 => {
+                self.cycle = 0;
+                FrameClock::default()
+            },
+            _ => FrameClock::default()
+        }
+    }
+    fn raise_irq(&mut self) {
+        if !self.irq_inhibit { self.irq = true }
+    }
+}
+impl Snapshot for FrameCounter {
+    fn save(&self, w: &mut StateWriter) {
+        self.five_step.save(w);
+        self.irq_inhibit.save(w);
+        self.irq.save(w);
+        self.cycle.save(w);
+        self.reset_delay.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.five_step.load(r)?;
+        self.irq_inhibit.load(r)?;
+        self.irq.load(r)?;
+        self.cycle.load(r)?;
+        self.reset_delay.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,42 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+const LENGTHS: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30
+];
+
+// silences a channel once it runs out, clocked by the half frames
+#[derive(Default)]
+pub struct LengthCounter {
+    pub counter: u8,
+    // $4015, a disabled channel cannot be loaded
+    pub enabled: bool,
+    pub halted: bool
+}
+impl LengthCounter {
+    pub fn load(&mut self, index: u8) {
+        if self.enabled { self.counter = LENGTHS[index as usize & 0x1f] }
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled { self.counter = 0 }
+    }
+    pub fn clock(&mut self) {
+        if !self.halted && self.counter > 0 { self.counter -= 1 }
+    }
+    pub fn active(&self) -> bool {
+        self.counter > 0
+    }
+}
+impl Snapshot for LengthCounter {
+    fn save(&self, w: &mut StateWriter) {
+        self.counter.save(w);
+        self.enabled.save(w);
+        self.halted.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.counter.load(r)?;
+        self.enabled.load(r)?;
+        self.halted.load(r)
+    }
+}
\ No newline at end of file
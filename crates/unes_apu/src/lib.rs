@@ -0,0 +1,12 @@
+#![no_std]
+mod apu;
+pub mod dmc;
+pub mod envelope;
+pub mod frame_counter;
+pub mod length;
+pub mod noise;
+pub mod pulse;
+mod tests;
+pub mod triangle;
+
+pub use apu::APU;
\ No newline at end of file
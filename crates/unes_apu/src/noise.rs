@@ -0,0 +1,85 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::envelope::Envelope;
+use crate::length::LengthCounter;
+
+// timer periods in cpu cycles (NTSC)
+pub const PERIODS: [u16; 16] = [4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068];
+pub const PAL_PERIODS: [u16; 16] = [4, 8, 14, 30, 60, 88, 118, 148, 188, 236, 354, 472, 708, 944, 1890, 3778];
+
+// $400c-$400f
+// A 15 bit lfsr, the feedback taps bit 1 or, in short mode, bit 6.
+pub struct Noise {
+    pub pal: bool,
+    pub shift: u16,
+    pub short_mode: bool,
+    pub timer_period: u16,
+    pub timer: u16,
+    pub envelope: Envelope,
+    pub length: LengthCounter
+}
+impl Default for Noise {
+    fn default() -> Self {
+        Noise {
+            pal: false,
+            shift: 1,
+            short_mode: false,
+            timer_period: PERIODS[0],
+            timer: 0,
+            envelope: Envelope::default(),
+            length: LengthCounter::default()
+        }
+    }
+}
+impl Noise {
+    pub fn write(&mut self, reg: u16, value: u8) {
+        match reg & 3 {
+            0 => {
+                self.length.halted = value & 0x20 != 0;
+                self.envelope.write(value);
+            },
+            1 => (),
+            2 => {
+                self.short_mode = value & 0x80 != 0;
+                self.timer_period = if self.pal { PAL_PERIODS } else { PERIODS }[value as usize & 0x0f];
+            },
+            _ => {
+                self.length.load(value >> 3);
+                self.envelope.start = true;
+            }
+        }
+    }
+    // clocked every cpu cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return
+        }
+        self.timer = self.timer_period - 1;
+        let tap = if self.short_mode { 6 } else { 1 };
+        let feedback = (self.shift ^ (self.shift >> tap)) & 1;
+        self.shift = self.shift >> 1 | feedback << 14;
+    }
+    pub fn output(&self) -> u8 {
+        if self.shift & 1 != 0 || !self.length.active() { return 0 }
+        self.envelope.output()
+    }
+}
+impl Snapshot for Noise {
+    fn save(&self, w: &mut StateWriter) {
+        self.shift.save(w);
+        self.short_mode.save(w);
+        self.timer_period.save(w);
+        self.timer.save(w);
+        self.envelope.save(w);
+        self.length.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.shift.load(r)?;
+        self.short_mode.load(r)?;
+        self.timer_period.load(r)?;
+        self.timer.load(r)?;
+        self.envelope.load(r)?;
+        Snapshot::load(&mut self.length, r)
+    }
+}
\ No newline at end of file
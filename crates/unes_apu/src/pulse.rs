@@ -0,0 +1,128 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::envelope::Envelope;
+use crate::length::LengthCounter;
+
+const DUTY: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 1, 0, 0, 0],
+    [1, 0, 0, 1, 1, 1, 1, 1]
+];
+
+// square wave channel, $4000-$4003 and $4004-$4007
+#[derive(Default)]
+pub struct Pulse {
+    // the first channel negates its sweep with ones' complement,
+    // so it goes one lower than the second
+    pub ones_complement: bool,
+    pub duty: u8,
+    pub step: u8,
+    pub timer_period: u16,
+    pub timer: u16,
+    pub envelope: Envelope,
+    pub length: LengthCounter,
+    pub sweep_enabled: bool,
+    pub sweep_period: u8,
+    pub sweep_negate: bool,
+    pub sweep_shift: u8,
+    pub sweep_divider: u8,
+    pub sweep_reload: bool
+}
+impl Pulse {
+    pub fn new(ones_complement: bool) -> Pulse {
+        Pulse { ones_complement, ..Default::default() }
+    }
+    pub fn write(&mut self, reg: u16, value: u8) {
+        match reg & 3 {
+            0 => {
+                self.duty = value >> 6;
+                self.length.halted = value & 0x20 != 0;
+                self.envelope.write(value);
+            },
+            1 => {
+                self.sweep_enabled = value & 0x80 != 0;
+                self.sweep_period = (value >> 4) & 7;
+                self.sweep_negate = value & 0x08 != 0;
+                self.sweep_shift = value & 7;
+                self.sweep_reload = true;
+            },
+            2 => self.timer_period = self.timer_period & 0x700 | value as u16,
+            _ => {
+                self.timer_period = self.timer_period & 0xff | ((value & 7) as u16) << 8;
+                self.length.load(value >> 3);
+                self.step = 0;
+                self.envelope.start = true;
+            }
+        }
+    }
+    // clocked every other cpu cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            self.step = (self.step + 1) & 7;
+        } else {
+            self.timer -= 1;
+        }
+    }
+    fn target_period(&self) -> u16 {
+        let change = self.timer_period >> self.sweep_shift;
+        if !self.sweep_negate {
+            self.timer_period + change
+        } else if self.ones_complement {
+            self.timer_period.saturating_sub(change + 1)
+        } else {
+            self.timer_period.saturating_sub(change)
+        }
+    }
+    // the sweep mutes the channel even when it is disabled
+    fn muted(&self) -> bool {
+        self.timer_period < 8 || self.target_period() > 0x7ff
+    }
+    // half frame
+    pub fn clock_sweep(&mut self) {
+        if self.sweep_divider == 0 && self.sweep_enabled && self.sweep_shift > 0 && !self.muted() {
+            self.timer_period = self.target_period();
+        }
+        if self.sweep_divider == 0 || self.sweep_reload {
+            self.sweep_divider = self.sweep_period;
+            self.sweep_reload = false;
+        } else {
+            self.sweep_divider -= 1;
+        }
+    }
+    pub fn output(&self) -> u8 {
+        if !self.length.active() || self.muted() || DUTY[self.duty as usize][self.step as usize] == 0 { return 0 }
+        self.envelope.output()
+    }
+}
+impl Snapshot for Pulse {
+    fn save(&self, w: &mut StateWriter) {
+        self.duty.save(w);
+        self.step.save(w);
+        self.timer_period.save(w);
+        self.timer.save(w);
+        self.envelope.save(w);
+        self.length.save(w);
+        self.sweep_enabled.save(w);
+        self.sweep_period.save(w);
+        self.sweep_negate.save(w);
+        self.sweep_shift.save(w);
+        self.sweep_divider.save(w);
+        self.sweep_reload.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.duty.load(r)?;
+        self.step.load(r)?;
+        self.timer_period.load(r)?;
+        self.timer.load(r)?;
+        self.envelope.load(r)?;
+        Snapshot::load(&mut self.length, r)?;
+        self.sweep_enabled.load(r)?;
+        self.sweep_period.load(r)?;
+        self.sweep_negate.load(r)?;
+        self.sweep_shift.load(r)?;
+        self.sweep_divider.load(r)?;
+        self.sweep_reload.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use crate::APU;
+    use crate::envelope::Envelope;
+    use crate::pulse::Pulse;
+
+    #[test]
+    fn test_length_counter() {
+        let mut apu = APU::new();
+        // disabled channels do not load
+        apu.write_register(0x4003, 0x08);
+        assert!(apu.read_status() & 1 == 0);
+        apu.write_register(0x4015, 0x0f);
+        // index 1 is 254
+        apu.write_register(0x4003, 0x08);
+        assert!(apu.pulse1.length.counter == 254 && apu.read_status() & 1 == 1);
+        apu.pulse1.length.clock();
+        assert!(apu.pulse1.length.counter == 253);
+        apu.write_register(0x4015, 0x0e);
+        assert!(apu.pulse1.length.counter == 0);
+    }
+    #[test]
+    fn test_length_halt() {
+        let mut apu = APU::new();
+        apu.write_register(0x4015, 0x04);
+        apu.write_register(0x4008, 0x80);
+        apu.write_register(0x400b, 0x00);
+        apu.triangle.length.clock();
+        assert!(apu.triangle.length.counter == 10);
+    }
+    #[test]
+    fn test_envelope() {
+        let mut envelope = Envelope::default();
+        // period 1: the level drops every second clock
+        envelope.write(0x01);
+        envelope.start = true;
+        envelope.clock();
+        assert!(envelope.output() == 15);
+        envelope.clock();
+        envelope.clock();
+        assert!(envelope.output() == 14);
+        for _ in 0..28 { envelope.clock() }
+        assert!(envelope.output() == 0);
+        // looping
+        envelope.write(0x21);
+        envelope.clock();
+        envelope.clock();
+        assert!(envelope.output() == 15);
+        envelope.write(0x17);
+        assert!(envelope.output() == 7);
+    }
+    #[test]
+    fn test_pulse_duty() {
+        let mut pulse = Pulse::new(false);
+        pulse.length.enabled = true;
+        // 25% duty, constant volume 9, period 8
+        pulse.write(0, 0x59);
+        pulse.write(2, 8);
+        pulse.write(3, 0x08);
+        let mut wave = [0; 16];
+        for sample in wave.iter_mut() {
+            *sample = pulse.output();
+            for _ in 0..9 { pulse.clock_timer() }
+        }
+        assert!(wave[..8] == [0, 9, 9, 0, 0, 0, 0, 0] && wave[8..] == wave[..8]);
+    }
+    #[test]
+    fn test_sweep() {
+        let mut pulse1 = Pulse::new(true);
+        let mut pulse2 = Pulse::new(false);
+        for pulse in [&mut pulse1, &mut pulse2] {
+            pulse.write(2, 0x00);
+            pulse.write(3, 0x01);
+            // enabled, period 0, negate, shift 1
+            pulse.write(1, 0x89);
+            pulse.clock_sweep();
+        }
+        // ones' complement on the first channel
+        assert!(pulse1.timer_period == 0x7f && pulse2.timer_period == 0x80);
+        // a target above $7ff mutes even with the sweep disabled
+        pulse2.length.enabled = true;
+        pulse2.write(0, 0xdf);
+        pulse2.write(1, 0x01);
+        pulse2.write(2, 0xff);
+        pulse2.write(3, 0x0d);
+        pulse2.step = 3;
+        assert!(pulse2.output() == 0);
+        pulse2.write(1, 0x02);
+        assert!(pulse2.output() == 15);
+    }
+    #[test]
+    fn test_triangle() {
+        let mut apu = APU::new();
+        apu.write_register(0x4015, 0x04);
+        apu.write_register(0x4008, 0x04);
+        apu.write_register(0x400a, 0x01);
+        apu.write_register(0x400b, 0x08);
+        // no steps before the linear counter is loaded
+        for _ in 0..4 { apu.triangle.clock_timer() }
+        assert!(apu.triangle.output() == 15);
+        apu.triangle.clock_linear();
+        assert!(apu.triangle.linear_counter == 4);
+        for _ in 0..32 { apu.triangle.clock_timer() }
+        assert!(apu.triangle.step == 16 && apu.triangle.output() == 0);
+        // control clear, the reload flag goes away and the counter runs out
+        for _ in 0..4 { apu.triangle.clock_linear() }
+        let step = apu.triangle.step;
+        for _ in 0..10 { apu.triangle.clock_timer() }
+        assert!(apu.triangle.step == step);
+    }
+    #[test]
+    fn test_noise() {
+        let mut apu = APU::new();
+        apu.write_register(0x4015, 0x08);
+        apu.write_register(0x400c, 0x1f);
+        apu.write_register(0x400e, 0x00);
+        apu.write_register(0x400f, 0x08);
+        // the long sequence repeats after 32767 steps
+        let start = apu.noise.shift;
+        let mut steps = 0;
+        loop {
+            for _ in 0..4 { apu.noise.clock_timer() }
+            steps += 1;
+            if apu.noise.shift == start { break }
+        }
+        assert!(steps == 32767);
+        // the short one after 93 (or 31)
+        apu.write_register(0x400e, 0x80);
+        let start = apu.noise.shift;
+        let mut steps = 0;
+        loop {
+            for _ in 0..4 { apu.noise.clock_timer() }
+            steps += 1;
+            if apu.noise.shift == start || steps > 100 { break }
+        }
+        assert!(steps == 93 || steps == 31);
+    }
+}
\ No newline at end of file
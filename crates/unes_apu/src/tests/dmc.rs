@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use crate::APU;
+
+    #[test]
+    fn test_sample_reader() {
+        let mut apu = APU::new();
+        // $c040, 17 bytes
+        apu.write_register(0x4012, 0x01);
+        apu.write_register(0x4013, 0x01);
+        assert!(apu.dmc.pending_read().is_none());
+        apu.write_register(0x4015, 0x10);
+        assert!(apu.dmc.pending_read() == Some(0xc040) && apu.read_status() & 0x10 != 0);
+        apu.dmc.fill(0xff);
+        // nothing until the buffer is emptied
+        assert!(apu.dmc.pending_read().is_none() && apu.dmc.bytes_remaining == 16);
+        apu.dmc.clock_timer();
+        assert!(apu.dmc.pending_read() == Some(0xc041) && apu.dmc.shift == 0xff);
+    }
+    #[test]
+    fn test_output_level() {
+        let mut apu = APU::new();
+        // fastest rate
+        apu.write_register(0x4010, 0x0f);
+        apu.write_register(0x4011, 0x40);
+        apu.write_register(0x4015, 0x10);
+        apu.dmc.fill(0b0000_0111);
+        apu.dmc.clock_timer();
+        for _ in 0..8 * 54 { apu.dmc.clock_timer() }
+        // three up, five down
+        assert!(apu.dmc.output() == 0x40 + 6 - 10);
+        // silent without a byte
+        for _ in 0..8 * 54 { apu.dmc.clock_timer() }
+        assert!(apu.dmc.output() == 0x40 - 4);
+    }
+    #[test]
+    fn test_irq_and_loop() {
+        let mut apu = APU::new();
+        apu.write_register(0x4010, 0x80);
+        apu.write_register(0x4013, 0x00);
+        apu.write_register(0x4015, 0x10);
+        apu.dmc.fill(0);
+        assert!(apu.irq() && apu.read_status() & 0x80 != 0);
+        // writing $4015 acknowledges it
+        apu.write_register(0x4015, 0x00);
+        assert!(!apu.irq());
+        apu.write_register(0x4010, 0xc0);
+        apu.write_register(0x4015, 0x10);
+        apu.dmc.fill(0);
+        assert!(!apu.irq() && apu.dmc.bytes_remaining == 1 && apu.dmc.current_address == 0xc000);
+    }
+    #[test]
+    fn test_address_wrap() {
+        let mut apu = APU::new();
+        apu.write_register(0x4012, 0xff);
+        apu.write_register(0x4013, 0xff);
+        apu.write_register(0x4015, 0x10);
+        for _ in 0..0x40 {
+            apu.dmc.fill(0);
+            apu.dmc.buffer = None;
+        }
+        assert!(apu.dmc.pending_read() == Some(0x8000));
+    }
+}
\ No newline at end of file
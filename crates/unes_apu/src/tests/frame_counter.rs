@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use crate::APU;
+
+    #[test]
+    fn test_four_step_irq() {
+        let mut apu = APU::new();
+        for _ in 0..29827 { apu.clock() }
+        assert!(!apu.irq());
+        apu.clock();
+        assert!(apu.irq());
+        // reading the status acknowledges it, but it is raised again on the next two cycles
+        assert!(apu.read_status() & 0x40 != 0);
+        apu.clock();
+        assert!(apu.read_status() & 0x40 != 0);
+        apu.clock();
+        assert!(apu.read_status() & 0x40 != 0 && apu.read_status() & 0x40 == 0);
+    }
+    #[test]
+    fn test_irq_inhibit() {
+        let mut apu = APU::new();
+        apu.write_register(0x4017, 0x40);
+        for _ in 0..40000 { apu.clock() }
+        assert!(!apu.irq());
+        // five step mode never raises it
+        apu.write_register(0x4017, 0x80);
+        for _ in 0..40000 { apu.clock() }
+        assert!(!apu.irq());
+    }
+    #[test]
+    fn test_length_clocks() {
+        let mut apu = APU::new();
+        apu.write_register(0x4015, 0x01);
+        apu.write_register(0x4003, 0x38);
+        // two half frames per 4 step sequence
+        for _ in 0..29830 { apu.clock() }
+        assert!(apu.pulse1.length.counter == 4);
+        // writing the 5 step mode clocks one right away (after the reset delay)
+        apu.write_register(0x4017, 0x80);
+        for _ in 0..4 { apu.clock() }
+        assert!(apu.pulse1.length.counter == 3);
+        for _ in 0..37282 { apu.clock() }
+        assert!(apu.pulse1.length.counter == 1);
+    }
+    #[test]
+    fn test_write_delay() {
+        let mut apu = APU::new();
+        // the sequence restarts on the third cycle after a write on an even one
+        apu.write_register(0x4017, 0x00);
+        for _ in 0..2 { apu.clock() }
+        assert!(apu.frame_counter.cycle == 2);
+        apu.clock();
+        assert!(apu.frame_counter.cycle == 1);
+        // and on the fourth after an odd one
+        for _ in 0..2 { apu.clock() }
+        apu.write_register(0x4017, 0x00);
+        for _ in 0..3 { apu.clock() }
+        assert!(apu.frame_counter.cycle == 6);
+        apu.clock();
+        assert!(apu.frame_counter.cycle == 1);
+    }
+    #[test]
+    fn test_pal_steps() {
+        let mut apu = APU::new();
+        apu.set_pal(true);
+        for _ in 0..33251 { apu.clock() }
+        assert!(!apu.irq());
+        apu.clock();
+        assert!(apu.irq());
+        // noise and dmc periods come from the pal tables
+        apu.write_register(0x400e, 0x02);
+        apu.write_register(0x4010, 0x0f);
+        assert!(apu.noise.timer_period == 14 && apu.dmc.rate == 50);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,3 @@
+mod channels;
+mod dmc;
+mod frame_counter;
\ No newline at end of file
@@ -0,0 +1,83 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::length::LengthCounter;
+
+const SEQUENCE: [u8; 32] = [
+    15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15
+];
+
+// $4008-$400b
+#[derive(Default)]
+pub struct Triangle {
+    pub timer_period: u16,
+    pub timer: u16,
+    pub step: u8,
+    pub length: LengthCounter,
+    // also halts the length counter
+    pub control: bool,
+    pub linear_counter: u8,
+    pub linear_period: u8,
+    pub linear_reload: bool
+}
+impl Triangle {
+    pub fn write(&mut self, reg: u16, value: u8) {
+        match reg & 3 {
+            0 => {
+                self.control = value & 0x80 != 0;
+                self.length.halted = self.control;
+                self.linear_period = value & 0x7f;
+            },
+            1 => (),
+            2 => self.timer_period = self.timer_period & 0x700 | value as u16,
+            _ => {
+                self.timer_period = self.timer_period & 0xff | ((value & 7) as u16) << 8;
+                self.length.load(value >> 3);
+                self.linear_reload = true;
+            }
+        }
+    }
+    // clocked every cpu cycle, the sequencer stops (holding its level) when either counter is out
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            if self.length.active() && self.linear_counter > 0 { self.step = (self.step + 1) & 31 }
+        } else {
+            self.timer -= 1;
+        }
+    }
+    // quarter frame
+    pub fn clock_linear(&mut self) {
+        if self.linear_reload {
+            self.linear_counter = self.linear_period;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+        if !self.control { self.linear_reload = false }
+    }
+    pub fn output(&self) -> u8 {
+        SEQUENCE[self.step as usize]
+    }
+}
+impl Snapshot for Triangle {
+    fn save(&self, w: &mut StateWriter) {
+        self.timer_period.save(w);
+        self.timer.save(w);
+        self.step.save(w);
+        self.length.save(w);
+        self.control.save(w);
+        self.linear_counter.save(w);
+        self.linear_period.save(w);
+        self.linear_reload.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.timer_period.load(r)?;
+        self.timer.load(r)?;
+        self.step.load(r)?;
+        Snapshot::load(&mut self.length, r)?;
+        self.control.load(r)?;
+        self.linear_counter.load(r)?;
+        self.linear_period.load(r)?;
+        self.linear_reload.load(r)
+    }
+}
\ No newline at end of file
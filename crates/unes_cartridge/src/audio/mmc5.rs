@@ -0,0 +1,225 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::{ExpansionAudio, MIX_UNIT};
+
+const CHANNELS: [&str; 3] = ["Pulse 1", "Pulse 2", "PCM"];
+const GAIN: f32 = 43.0 * MIX_UNIT;
+
+// envelopes and length counters run off a fixed 240Hz timer,
+// independent of the apu frame counter
+const FRAME_PERIOD: u16 = 7457;
+
+const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30
+];
+const DUTY_TABLE: [u8; 4] = [
+    0b0100_0000,
+    0b0110_0000,
+    0b0111_1000,
+    0b1001_1111
+];
+
+// same as the 2A03 pulse, minus the sweep unit
+#[derive(Default)]
+struct Pulse {
+    enabled: bool,
+    duty: u8,
+    duty_step: u8,
+    halt: bool,
+    constant_volume: bool,
+    volume: u8,
+    period: u16,
+    timer: u16,
+    length: u8,
+    envelope_start: bool,
+    envelope_divider: u8,
+    envelope_decay: u8
+}
+impl Pulse {
+    fn write(&mut self, reg: u16, value: u8) {
+        match reg {
+            0 => {
+                self.duty = value >> 6;
+                self.halt = value & 0x20 != 0;
+                self.constant_volume = value & 0x10 != 0;
+                self.volume = value & 0x0f;
+            },
+            2 => self.period = self.period & 0x700 | value as u16,
+            3 => {
+                self.period = self.period & 0xff | ((value & 0x07) as u16) << 8;
+                if self.enabled { self.length = LENGTH_TABLE[(value >> 3) as usize] }
+                self.duty_step = 0;
+                self.envelope_start = true;
+            },
+            _ => ()
+        }
+    }
+    fn set_enabled(&mut self, state: bool) {
+        self.enabled = state;
+        if !state { self.length = 0 }
+    }
+    fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.period;
+            self.duty_step = (self.duty_step + 1) & 7;
+        } else {
+            self.timer -= 1;
+        }
+    }
+    fn clock_frame(&mut self) {
+        if self.envelope_start {
+            self.envelope_start = false;
+            self.envelope_decay = 15;
+            self.envelope_divider = self.volume;
+        } else if self.envelope_divider == 0 {
+            self.envelope_divider = self.volume;
+            if self.envelope_decay > 0 {
+                self.envelope_decay -= 1;
+            } else if self.halt {
+                self.envelope_decay = 15;
+            }
+        } else {
+            self.envelope_divider -= 1;
+        }
+        if !self.halt && self.length > 0 { self.length -= 1 }
+    }
+    fn output(&self) -> u8 {
+        if self.length == 0 { return 0 }
+        if DUTY_TABLE[self.duty as usize] & (0x80 >> self.duty_step) == 0 { return 0 }
+        if self.constant_volume { self.volume } else { self.envelope_decay }
+    }
+}
+impl Snapshot for Pulse {
+    fn save(&self, w: &mut StateWriter) {
+        self.enabled.save(w);
+        self.duty.save(w);
+        self.duty_step.save(w);
+        self.halt.save(w);
+        self.constant_volume.save(w);
+        self.volume.save(w);
+        self.period.save(w);
+        self.timer.save(w);
+        self.length.save(w);
+        self.envelope_start.save(w);
+        self.envelope_divider.save(w);
+        self.envelope_decay.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.enabled.load(r)?;
+        self.duty.load(r)?;
+        self.duty_step.load(r)?;
+        self.halt.load(r)?;
+        self.constant_volume.load(r)?;
+        self.volume.load(r)?;
+        self.period.load(r)?;
+        self.timer.load(r)?;
+        self.length.load(r)?;
+        self.envelope_start.load(r)?;
+        self.envelope_divider.load(r)?;
+        self.envelope_decay.load(r)
+    }
+}
+
+#[derive(Default)]
+pub struct Mmc5Audio {
+    pulses: [Pulse; 2],
+    pcm: u8,
+    pcm_read_mode: bool,
+    pcm_irq_enabled: bool,
+    pcm_irq: bool,
+    frame_timer: u16,
+    odd_cycle: bool
+}
+impl Mmc5Audio {
+    pub fn new() -> Mmc5Audio {
+        Mmc5Audio { frame_timer: FRAME_PERIOD, ..Default::default() }
+    }
+    pub fn clock(&mut self) {
+        // pulse timers tick every other cpu cycle, like in the apu
+        self.odd_cycle = !self.odd_cycle;
+        if self.odd_cycle {
+            self.pulses.iter_mut().for_each(|p| p.clock_timer());
+        }
+        self.frame_timer -= 1;
+        if self.frame_timer == 0 {
+            self.frame_timer = FRAME_PERIOD;
+            self.pulses.iter_mut().for_each(|p| p.clock_frame());
+        }
+    }
+    pub fn read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x5010 => {
+                // reading acknowledges the pcm irq
+                let value = (self.pcm_irq as u8) << 7 | self.pcm_read_mode as u8;
+                self.pcm_irq = false;
+                Some(value)
+            },
+            0x5015 => Some(
+                ((self.pulses[1].length > 0) as u8) << 1 | (self.pulses[0].length > 0) as u8
+            ),
+            _ => None
+        }
+    }
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x5000..=0x5003 => self.pulses[0].write(addr - 0x5000, value),
+            0x5004..=0x5007 => self.pulses[1].write(addr - 0x5004, value),
+            0x5010 => {
+                self.pcm_read_mode = value & 1 != 0;
+                self.pcm_irq_enabled = value & 0x80 != 0;
+            },
+            0x5011 if !self.pcm_read_mode && value != 0 => self.pcm = value,
+            0x5015 => {
+                self.pulses[0].set_enabled(value & 1 != 0);
+                self.pulses[1].set_enabled(value & 2 != 0);
+            },
+            _ => ()
+        }
+    }
+    // in read mode the pcm channel samples cpu reads from $8000-$bfff,
+    // a zero byte raises the irq instead of being output
+    pub fn pcm_read(&mut self, value: u8) {
+        if !self.pcm_read_mode { return }
+        if value == 0 {
+            self.pcm_irq = true;
+        } else {
+            self.pcm = value;
+        }
+    }
+    pub fn irq(&self) -> bool {
+        self.pcm_irq && self.pcm_irq_enabled
+    }
+}
+impl ExpansionAudio for Mmc5Audio {
+    fn channels(&self) -> &'static [&'static str] {
+        &CHANNELS
+    }
+    fn channel_output(&self, channel: usize) -> f32 {
+        match channel {
+            0 | 1 => self.pulses[channel].output() as f32 * GAIN,
+            2 => self.pcm as f32 * GAIN,
+            _ => 0.0
+        }
+    }
+}
+impl Snapshot for Mmc5Audio {
+    fn save(&self, w: &mut StateWriter) {
+        self.pulses.save(w);
+        self.pcm.save(w);
+        self.pcm_read_mode.save(w);
+        self.pcm_irq_enabled.save(w);
+        self.pcm_irq.save(w);
+        self.frame_timer.save(w);
+        self.odd_cycle.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.pulses.load(r)?;
+        self.pcm.load(r)?;
+        self.pcm_read_mode.load(r)?;
+        self.pcm_irq_enabled.load(r)?;
+        self.pcm_irq.load(r)?;
+        self.frame_timer.load(r)?;
+        self.odd_cycle.load(r)
+    }
+}
\ No newline at end of file
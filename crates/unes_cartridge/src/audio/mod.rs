@@ -0,0 +1,27 @@
+// Expansion sound chips found on some boards.
+// Their output is mixed linearly on top of the 2A03 one, so all levels are
+// expressed on the apu mixer scale, where a single apu pulse channel
+// at full volume gives 95.88 / (8128 / 15 + 100) ~= 0.149.
+// Relative chip volumes follow the ones established by hardware comparisons
+// (and used by most emulators): eg. an MMC5 pulse is slightly quieter than
+// a 2A03 one, while a VRC6 pulse is about 1.5x louder.
+
+pub mod mmc5;
+pub mod n163;
+pub mod sunsoft5b;
+pub mod vrc6;
+pub mod vrc7;
+
+// converts the common integer mixing scale (full volume 2A03 pulse = 744)
+// into the apu mixer units
+pub(crate) const MIX_UNIT: f32 = 0.149_37 / 744.07;
+
+pub trait ExpansionAudio {
+    // names of the chip channels, in the order used by `channel_output`
+    fn channels(&self) -> &'static [&'static str];
+    // current output of a single channel
+    fn channel_output(&self, channel: usize) -> f32;
+    fn output(&self) -> f32 {
+        (0..self.channels().len()).map(|c| self.channel_output(c)).sum()
+    }
+}
\ No newline at end of file
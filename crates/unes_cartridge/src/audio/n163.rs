@@ -0,0 +1,105 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::{ExpansionAudio, MIX_UNIT};
+
+const CHANNELS: [&str; 8] = ["Wave 1", "Wave 2", "Wave 3", "Wave 4", "Wave 5", "Wave 6", "Wave 7", "Wave 8"];
+const GAIN: f32 = 20.0 * MIX_UNIT;
+
+// a single channel is updated every 15 cpu cycles
+const UPDATE_PERIOD: u8 = 15;
+
+// Namco 163 wavetable sound
+// The 128 bytes of internal ram hold both the 4 bit samples and,
+// from $40 up, the registers of the 8 channels (8 bytes each).
+// Only one channel is output at a time, so enabling more channels
+// lowers the sample rate and the volume of each one.
+pub struct N163Audio {
+    pub ram: [u8; 128],
+    // $f800, bit 7 enables auto increment
+    address: u8,
+    disabled: bool,
+    divider: u8,
+    current: usize,
+    outputs: [i16; 8]
+}
+impl Default for N163Audio {
+    fn default() -> N163Audio {
+        N163Audio { ram: [0; 128], address: 0, disabled: false, divider: 0, current: 7, outputs: [0; 8] }
+    }
+}
+impl N163Audio {
+    pub fn write_address(&mut self, value: u8) {
+        self.address = value;
+    }
+    pub fn read_data(&mut self) -> u8 {
+        let value = self.ram[self.address as usize & 0x7f];
+        self.increment();
+        value
+    }
+    pub fn write_data(&mut self, value: u8) {
+        self.ram[self.address as usize & 0x7f] = value;
+        self.increment();
+    }
+    fn increment(&mut self) {
+        if self.address & 0x80 != 0 {
+            self.address = 0x80 | self.address.wrapping_add(1) & 0x7f;
+        }
+    }
+    // $e000 bit 6
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.disabled = disabled;
+    }
+    fn active_channels(&self) -> usize {
+        ((self.ram[0x7f] >> 4) & 7) as usize + 1
+    }
+    pub fn clock(&mut self) {
+        if self.disabled { return }
+        self.divider += 1;
+        if self.divider < UPDATE_PERIOD { return }
+        self.divider = 0;
+
+        let base = 0x40 + self.current * 8;
+        let reg = |i: usize| self.ram[base + i] as u32;
+        let frequency = reg(0) | reg(2) << 8 | (reg(4) & 3) << 16;
+        let length = 256 - (reg(4) & 0xfc);
+        let phase = ((reg(1) | reg(3) << 8 | reg(5) << 16) + frequency) % (length << 16);
+        let index = ((phase >> 16) + reg(6)) as usize & 0xff;
+        let sample = (self.ram[index >> 1] >> ((index & 1) * 4)) & 0x0f;
+        self.outputs[self.current] = (sample as i16 - 8) * (reg(7) & 0x0f) as i16;
+        self.ram[base + 1] = phase as u8;
+        self.ram[base + 3] = (phase >> 8) as u8;
+        self.ram[base + 5] = (phase >> 16) as u8;
+
+        // channels run from 8 downwards
+        self.current = if self.current <= 8 - self.active_channels() { 7 } else { self.current - 1 };
+    }
+}
+impl ExpansionAudio for N163Audio {
+    fn channels(&self) -> &'static [&'static str] {
+        &CHANNELS
+    }
+    fn channel_output(&self, channel: usize) -> f32 {
+        let active = self.active_channels();
+        if self.disabled || channel >= 8 || channel < 8 - active { return 0.0 }
+        // the multiplexed output averages out over the active channels
+        self.outputs[channel] as f32 / active as f32 * GAIN
+    }
+}
+impl Snapshot for N163Audio {
+    fn save(&self, w: &mut StateWriter) {
+        self.ram.save(w);
+        self.address.save(w);
+        self.disabled.save(w);
+        self.divider.save(w);
+        self.current.save(w);
+        self.outputs.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ram.load(r)?;
+        self.address.load(r)?;
+        self.disabled.load(r)?;
+        self.divider.load(r)?;
+        self.current.load(r)?;
+        self.outputs.load(r)
+    }
+}
\ No newline at end of file
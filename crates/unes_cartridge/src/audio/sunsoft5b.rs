@@ -0,0 +1,235 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::{ExpansionAudio, MIX_UNIT};
+
+const CHANNELS: [&str; 3] = ["Square A", "Square B", "Square C"];
+const GAIN: f32 = 15.0 * MIX_UNIT;
+
+// tone, noise and envelope generators tick every 16 cpu cycles
+const TICK_PERIOD: u8 = 16;
+
+// 1.5dB per step, the 4 bit channel volumes use every other entry
+const VOLUME_TABLE: [u8; 32] = [
+    0, 1, 1, 1, 1, 1, 2, 2, 3, 3, 4, 5, 6, 7, 9, 11,
+    13, 15, 18, 22, 26, 31, 37, 44, 53, 63, 74, 89, 105, 125, 149, 177
+];
+
+#[derive(Default)]
+struct Tone {
+    period: u16,
+    timer: u16,
+    high: bool,
+    // $08-$0a
+    volume: u8,
+    envelope: bool
+}
+impl Tone {
+    fn tick(&mut self) {
+        self.timer += 1;
+        if self.timer >= self.period.max(1) {
+            self.timer = 0;
+            self.high = !self.high;
+        }
+    }
+}
+impl Snapshot for Tone {
+    fn save(&self, w: &mut StateWriter) {
+        self.period.save(w);
+        self.timer.save(w);
+        self.high.save(w);
+        self.volume.save(w);
+        self.envelope.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.period.load(r)?;
+        self.timer.load(r)?;
+        self.high.load(r)?;
+        self.volume.load(r)?;
+        self.envelope.load(r)
+    }
+}
+
+#[derive(Default)]
+struct Envelope {
+    period: u16,
+    timer: u16,
+    // $0d, bit 3 continue, bit 2 attack, bit 1 alternate, bit 0 hold
+    shape: u8,
+    step: u8,
+    rising: bool,
+    // level kept once the shape stops
+    held: Option<u8>
+}
+impl Envelope {
+    fn write_shape(&mut self, value: u8) {
+        self.shape = value & 0x0f;
+        self.step = 0;
+        self.timer = 0;
+        self.rising = value & 4 != 0;
+        self.held = None;
+    }
+    fn tick(&mut self) {
+        if self.held.is_some() { return }
+        self.timer += 1;
+        if self.timer < self.period.max(1) { return }
+        self.timer = 0;
+        if self.step < 31 {
+            self.step += 1;
+            return
+        }
+        // end of a ramp
+        let last = self.level();
+        if self.shape & 8 == 0 {
+            self.held = Some(0);
+        } else if self.shape & 1 != 0 {
+            self.held = Some(if self.shape & 2 != 0 { 31 - last } else { last });
+        } else {
+            if self.shape & 2 != 0 { self.rising = !self.rising }
+            self.step = 0;
+        }
+    }
+    fn level(&self) -> u8 {
+        match self.held {
+            Some(level) => level,
+            None if self.rising => self.step,
+            None => 31 - self.step
+        }
+    }
+}
+impl Snapshot for Envelope {
+    fn save(&self, w: &mut StateWriter) {
+        self.period.save(w);
+        self.timer.save(w);
+        self.shape.save(w);
+        self.step.save(w);
+        self.rising.save(w);
+        self.held.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.period.load(r)?;
+        self.timer.load(r)?;
+        self.shape.load(r)?;
+        self.step.load(r)?;
+        self.rising.load(r)?;
+        self.held.load(r)
+    }
+}
+
+// Sunsoft 5B, an AY-3-8910 variant: 3 square channels,
+// a shared noise generator and a shared envelope
+pub struct Sunsoft5bAudio {
+    address: u8,
+    tones: [Tone; 3],
+    noise_period: u8,
+    noise_timer: u8,
+    noise_prescaler: bool,
+    // 17 bit lfsr
+    noise: u32,
+    // $07, active low tone and noise enables
+    mixer: u8,
+    envelope: Envelope,
+    divider: u8
+}
+impl Default for Sunsoft5bAudio {
+    fn default() -> Sunsoft5bAudio {
+        Sunsoft5bAudio {
+            address: 0,
+            tones: Default::default(),
+            noise_period: 0,
+            noise_timer: 0,
+            noise_prescaler: false,
+            noise: 1,
+            mixer: 0,
+            envelope: Envelope::default(),
+            divider: 0
+        }
+    }
+}
+impl Sunsoft5bAudio {
+    pub fn write_address(&mut self, value: u8) {
+        self.address = value & 0x0f;
+    }
+    pub fn write_data(&mut self, value: u8) {
+        match self.address {
+            reg @ 0x00..=0x05 => {
+                let tone = &mut self.tones[reg as usize / 2];
+                tone.period = if reg & 1 == 0 {
+                    tone.period & 0xf00 | value as u16
+                } else {
+                    tone.period & 0xff | ((value & 0x0f) as u16) << 8
+                };
+            },
+            0x06 => self.noise_period = value & 0x1f,
+            0x07 => self.mixer = value,
+            reg @ 0x08..=0x0a => {
+                let tone = &mut self.tones[reg as usize - 8];
+                tone.volume = value & 0x0f;
+                tone.envelope = value & 0x10 != 0;
+            },
+            0x0b => self.envelope.period = self.envelope.period & 0xff00 | value as u16,
+            0x0c => self.envelope.period = self.envelope.period & 0xff | (value as u16) << 8,
+            0x0d => self.envelope.write_shape(value),
+            _ => ()
+        }
+    }
+    pub fn clock(&mut self) {
+        self.divider += 1;
+        if self.divider < TICK_PERIOD { return }
+        self.divider = 0;
+
+        self.tones.iter_mut().for_each(|t| t.tick());
+        self.envelope.tick();
+        // noise runs at half the rate of the tones
+        self.noise_prescaler = !self.noise_prescaler;
+        if self.noise_prescaler {
+            self.noise_timer += 1;
+            if self.noise_timer >= self.noise_period.max(1) {
+                self.noise_timer = 0;
+                let feedback = (self.noise ^ (self.noise >> 3)) & 1;
+                self.noise = (self.noise >> 1) | feedback << 16;
+            }
+        }
+    }
+}
+impl ExpansionAudio for Sunsoft5bAudio {
+    fn channels(&self) -> &'static [&'static str] {
+        &CHANNELS
+    }
+    fn channel_output(&self, channel: usize) -> f32 {
+        let Some(tone) = self.tones.get(channel) else { return 0.0 };
+        let tone_on = tone.high || self.mixer & (1 << channel) != 0;
+        let noise_on = self.noise & 1 != 0 || self.mixer & (8 << channel) != 0;
+        if !(tone_on && noise_on) { return 0.0 }
+        // fixed volumes map onto every other envelope level, 0 stays silent
+        let level = match (tone.envelope, tone.volume) {
+            (true, _) => self.envelope.level(),
+            (false, 0) => 0,
+            (false, volume) => volume * 2 + 1
+        };
+        VOLUME_TABLE[level as usize] as f32 * GAIN
+    }
+}
+impl Snapshot for Sunsoft5bAudio {
+    fn save(&self, w: &mut StateWriter) {
+        self.address.save(w);
+        self.tones.save(w);
+        self.noise_period.save(w);
+        self.noise_timer.save(w);
+        self.noise_prescaler.save(w);
+        self.noise.save(w);
+        self.mixer.save(w);
+        self.envelope.save(w);
+        self.divider.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.address.load(r)?;
+        self.tones.load(r)?;
+        self.noise_period.load(r)?;
+        self.noise_timer.load(r)?;
+        self.noise_prescaler.load(r)?;
+        self.noise.load(r)?;
+        self.mixer.load(r)?;
+        self.envelope.load(r)?;
+        self.divider.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,188 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::{ExpansionAudio, MIX_UNIT};
+
+const CHANNELS: [&str; 3] = ["Pulse 1", "Pulse 2", "Sawtooth"];
+const GAIN: f32 = 75.0 * MIX_UNIT;
+
+#[derive(Default)]
+struct Pulse {
+    enabled: bool,
+    // ignore the duty and output the volume constantly
+    digitized: bool,
+    duty: u8,
+    volume: u8,
+    period: u16,
+    timer: u16,
+    step: u8
+}
+impl Pulse {
+    fn write(&mut self, reg: u16, value: u8) {
+        match reg {
+            0 => {
+                self.digitized = value & 0x80 != 0;
+                self.duty = (value >> 4) & 7;
+                self.volume = value & 0x0f;
+            },
+            1 => self.period = self.period & 0xf00 | value as u16,
+            2 => {
+                self.period = self.period & 0xff | ((value & 0x0f) as u16) << 8;
+                self.enabled = value & 0x80 != 0;
+                if !self.enabled { self.step = 15 }
+            },
+            _ => ()
+        }
+    }
+    fn clock(&mut self, shift: u8) {
+        if !self.enabled { return }
+        if self.timer == 0 {
+            self.timer = self.period >> shift;
+            self.step = self.step.wrapping_sub(1) & 15;
+        } else {
+            self.timer -= 1;
+        }
+    }
+    fn output(&self) -> u8 {
+        if !self.enabled { return 0 }
+        if self.digitized || self.step <= self.duty { self.volume } else { 0 }
+    }
+}
+impl Snapshot for Pulse {
+    fn save(&self, w: &mut StateWriter) {
+        self.enabled.save(w);
+        self.digitized.save(w);
+        self.duty.save(w);
+        self.volume.save(w);
+        self.period.save(w);
+        self.timer.save(w);
+        self.step.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.enabled.load(r)?;
+        self.digitized.load(r)?;
+        self.duty.load(r)?;
+        self.volume.load(r)?;
+        self.period.load(r)?;
+        self.timer.load(r)?;
+        self.step.load(r)
+    }
+}
+
+#[derive(Default)]
+struct Sawtooth {
+    enabled: bool,
+    rate: u8,
+    period: u16,
+    timer: u16,
+    step: u8,
+    accumulator: u8
+}
+impl Sawtooth {
+    fn write(&mut self, reg: u16, value: u8) {
+        match reg {
+            0 => self.rate = value & 0x3f,
+            1 => self.period = self.period & 0xf00 | value as u16,
+            2 => {
+                self.period = self.period & 0xff | ((value & 0x0f) as u16) << 8;
+                self.enabled = value & 0x80 != 0;
+                if !self.enabled {
+                    self.step = 0;
+                    self.accumulator = 0;
+                }
+            },
+            _ => ()
+        }
+    }
+    fn clock(&mut self, shift: u8) {
+        if !self.enabled { return }
+        if self.timer > 0 {
+            self.timer -= 1;
+            return
+        }
+        self.timer = self.period >> shift;
+        // the accumulator grows on every other step and resets on the 14th
+        self.step += 1;
+        if self.step == 14 {
+            self.step = 0;
+            self.accumulator = 0;
+        } else if self.step & 1 == 0 {
+            self.accumulator = self.accumulator.wrapping_add(self.rate);
+        }
+    }
+    fn output(&self) -> u8 {
+        self.accumulator >> 3
+    }
+}
+impl Snapshot for Sawtooth {
+    fn save(&self, w: &mut StateWriter) {
+        self.enabled.save(w);
+        self.rate.save(w);
+        self.period.save(w);
+        self.timer.save(w);
+        self.step.save(w);
+        self.accumulator.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.enabled.load(r)?;
+        self.rate.load(r)?;
+        self.period.load(r)?;
+        self.timer.load(r)?;
+        self.step.load(r)?;
+        self.accumulator.load(r)
+    }
+}
+
+#[derive(Default)]
+pub struct Vrc6Audio {
+    pulses: [Pulse; 2],
+    sawtooth: Sawtooth,
+    halt: bool,
+    // frequency scaling from $9003
+    shift: u8
+}
+impl Vrc6Audio {
+    // expects the register already translated into $9000-$b002
+    pub fn write(&mut self, reg: u16, value: u8) {
+        match reg {
+            0x9000..=0x9002 => self.pulses[0].write(reg - 0x9000, value),
+            0x9003 => {
+                self.halt = value & 1 != 0;
+                self.shift = if value & 4 != 0 { 8 } else if value & 2 != 0 { 4 } else { 0 };
+            },
+            0xa000..=0xa002 => self.pulses[1].write(reg - 0xa000, value),
+            0xb000..=0xb002 => self.sawtooth.write(reg - 0xb000, value),
+            _ => ()
+        }
+    }
+    pub fn clock(&mut self) {
+        if self.halt { return }
+        self.pulses.iter_mut().for_each(|p| p.clock(self.shift));
+        self.sawtooth.clock(self.shift);
+    }
+}
+impl ExpansionAudio for Vrc6Audio {
+    fn channels(&self) -> &'static [&'static str] {
+        &CHANNELS
+    }
+    fn channel_output(&self, channel: usize) -> f32 {
+        match channel {
+            0 | 1 => self.pulses[channel].output() as f32 * GAIN,
+            2 => self.sawtooth.output() as f32 * GAIN,
+            _ => 0.0
+        }
+    }
+}
+impl Snapshot for Vrc6Audio {
+    fn save(&self, w: &mut StateWriter) {
+        self.pulses.save(w);
+        self.sawtooth.save(w);
+        self.halt.save(w);
+        self.shift.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.pulses.load(r)?;
+        self.sawtooth.load(r)?;
+        self.halt.load(r)?;
+        self.shift.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,383 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::{ExpansionAudio, MIX_UNIT};
+
+const CHANNELS: [&str; 6] = ["FM 1", "FM 2", "FM 3", "FM 4", "FM 5", "FM 6"];
+// channels are signed and peak at +-4094, a full volume sine swings
+// about twice as far as a full volume 2A03 pulse
+const GAIN: f32 = 0.18 * MIX_UNIT;
+
+// the chip computes a new sample every 36 cpu cycles (~49.7kHz)
+const SAMPLE_PERIOD: u8 = 36;
+
+// built-in instruments 1-15, instrument 0 is the custom one at $00-$07
+const PATCHES: [[u8; 8]; 15] = [
+    [0x03, 0x21, 0x05, 0x06, 0xe8, 0x81, 0x42, 0x27],
+    [0x13, 0x41, 0x14, 0x0d, 0xd8, 0xf6, 0x23, 0x12],
+    [0x11, 0x11, 0x08, 0x08, 0xfa, 0xb2, 0x20, 0x12],
+    [0x31, 0x61, 0x0c, 0x07, 0xa8, 0x64, 0x61, 0x27],
+    [0x32, 0x21, 0x1e, 0x06, 0xe1, 0x76, 0x01, 0x28],
+    [0x02, 0x01, 0x06, 0x00, 0xa3, 0xe2, 0xf4, 0xf4],
+    [0x21, 0x61, 0x1d, 0x07, 0x82, 0x81, 0x11, 0x07],
+    [0x23, 0x21, 0x22, 0x17, 0xa2, 0x72, 0x01, 0x17],
+    [0x35, 0x11, 0x25, 0x00, 0x40, 0x73, 0x72, 0x01],
+    [0xb5, 0x01, 0x0f, 0x0f, 0xa8, 0xa5, 0x51, 0x02],
+    [0x17, 0xc1, 0x24, 0x07, 0xf8, 0xf8, 0x22, 0x12],
+    [0x71, 0x23, 0x11, 0x06, 0x65, 0x74, 0x18, 0x16],
+    [0x01, 0x02, 0xd3, 0x05, 0xc9, 0x95, 0x03, 0x02],
+    [0x61, 0x63, 0x0c, 0x00, 0x94, 0xc0, 0x33, 0xf6],
+    [0x21, 0x72, 0x0d, 0x00, 0xc1, 0xd5, 0x56, 0x06]
+];
+
+// like the real chip, the operators work on attenuations:
+// a quarter sine wave as -log2(sin(x)) * 256 ...
+const LOG_SIN: [u16; 256] = [
+    2137, 1731, 1543, 1419, 1326, 1252, 1190, 1137, 1091, 1050, 1013, 979, 949, 920, 894, 869,
+    846, 825, 804, 785, 767, 749, 732, 717, 701, 687, 672, 659, 646, 633, 621, 609,
+    598, 587, 576, 566, 556, 546, 536, 527, 518, 509, 501, 492, 484, 476, 468, 461,
+    453, 446, 439, 432, 425, 418, 411, 405, 399, 392, 386, 380, 375, 369, 363, 358,
+    352, 347, 341, 336, 331, 326, 321, 316, 311, 307, 302, 297, 293, 289, 284, 280,
+    276, 271, 267, 263, 259, 255, 251, 248, 244, 240, 236, 233, 229, 226, 222, 219,
+    215, 212, 209, 205, 202, 199, 196, 193, 190, 187, 184, 181, 178, 175, 172, 169,
+    167, 164, 161, 159, 156, 153, 151, 148, 146, 143, 141, 138, 136, 134, 131, 129,
+    127, 125, 122, 120, 118, 116, 114, 112, 110, 108, 106, 104, 102, 100, 98, 96,
+    94, 92, 91, 89, 87, 85, 83, 82, 80, 78, 77, 75, 74, 72, 70, 69,
+    67, 66, 64, 63, 62, 60, 59, 57, 56, 55, 53, 52, 51, 49, 48, 47,
+    46, 45, 43, 42, 41, 40, 39, 38, 37, 36, 35, 34, 33, 32, 31, 30,
+    29, 28, 27, 26, 25, 24, 23, 23, 22, 21, 20, 20, 19, 18, 17, 17,
+    16, 15, 15, 14, 13, 13, 12, 12, 11, 10, 10, 9, 9, 8, 8, 7,
+    7, 7, 6, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2,
+    2, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0
+];
+// ... converted back to linear with (2^(x/256) - 1) * 1024
+const EXP: [u16; 256] = [
+    0, 3, 6, 8, 11, 14, 17, 20, 22, 25, 28, 31, 34, 37, 40, 42,
+    45, 48, 51, 54, 57, 60, 63, 66, 69, 72, 75, 78, 81, 84, 87, 90,
+    93, 96, 99, 102, 105, 108, 111, 114, 117, 120, 123, 126, 130, 133, 136, 139,
+    142, 145, 148, 152, 155, 158, 161, 164, 168, 171, 174, 177, 181, 184, 187, 190,
+    194, 197, 200, 204, 207, 210, 214, 217, 220, 224, 227, 231, 234, 237, 241, 244,
+    248, 251, 255, 258, 262, 265, 268, 272, 276, 279, 283, 286, 290, 293, 297, 300,
+    304, 308, 311, 315, 318, 322, 326, 329, 333, 337, 340, 344, 348, 352, 355, 359,
+    363, 367, 370, 374, 378, 382, 385, 389, 393, 397, 401, 405, 409, 412, 416, 420,
+    424, 428, 432, 436, 440, 444, 448, 452, 456, 460, 464, 468, 472, 476, 480, 484,
+    488, 492, 496, 501, 505, 509, 513, 517, 521, 526, 530, 534, 538, 542, 547, 551,
+    555, 560, 564, 568, 572, 577, 581, 585, 590, 594, 599, 603, 607, 612, 616, 621,
+    625, 630, 634, 639, 643, 648, 652, 657, 661, 666, 670, 675, 680, 684, 689, 693,
+    698, 703, 708, 712, 717, 722, 726, 731, 736, 741, 745, 750, 755, 760, 765, 770,
+    774, 779, 784, 789, 794, 799, 804, 809, 814, 819, 824, 829, 834, 839, 844, 849,
+    854, 859, 864, 869, 874, 880, 885, 890, 895, 900, 906, 911, 916, 921, 927, 932,
+    937, 942, 948, 953, 959, 964, 969, 975, 980, 986, 991, 996, 1002, 1007, 1013, 1018
+];
+
+// frequency multipliers, doubled so that 1/2 stays an integer
+const MULTIPLIERS: [u32; 16] = [1, 2, 4, 6, 8, 10, 12, 14, 16, 18, 20, 20, 24, 24, 30, 30];
+// key scale attenuation for the top 4 bits of the f-number, in 1/8 dB
+const KEY_SCALE: [i32; 16] = [0, 72, 96, 111, 120, 129, 135, 141, 144, 150, 153, 156, 159, 162, 165, 168];
+// envelope increments for the two low bits of the rate
+const ENVELOPE_STEPS: [[u8; 8]; 4] = [
+    [0, 1, 0, 1, 0, 1, 0, 1],
+    [0, 1, 0, 1, 1, 1, 0, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1],
+    [0, 1, 1, 1, 1, 1, 1, 1]
+];
+// vibrato offsets added to the f-number, by its top 3 bits
+const VIBRATO: [[i8; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 1, 0, 0, 0, -1, 0],
+    [0, 1, 2, 1, 0, -1, -2, -1],
+    [0, 1, 3, 1, 0, -1, -3, -1],
+    [0, 2, 4, 2, 0, -2, -4, -2],
+    [0, 2, 5, 2, 0, -2, -5, -2],
+    [0, 3, 6, 3, 0, -3, -6, -3],
+    [0, 3, 7, 3, 0, -3, -7, -3]
+];
+
+// envelope levels are 7 bit attenuations in 0.375dB steps
+const SILENT: u8 = 127;
+
+#[derive(Clone, Copy, PartialEq)]
+enum EnvelopeState {
+    Attack,
+    Decay,
+    Sustain,
+    Release
+}
+impl EnvelopeState {
+    fn from_index(index: u8) -> Result<EnvelopeState, StateError> {
+        match index {
+            0 => Ok(EnvelopeState::Attack),
+            1 => Ok(EnvelopeState::Decay),
+            2 => Ok(EnvelopeState::Sustain),
+            3 => Ok(EnvelopeState::Release),
+            _ => Err(StateError::InvalidData)
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Operator {
+    // 18 bit phase, the top 10 bits index a full sine period
+    phase: u32,
+    level: u8,
+    state: EnvelopeState,
+    // last two outputs, used for the modulator feedback
+    output: [i32; 2]
+}
+impl Default for Operator {
+    fn default() -> Operator {
+        Operator { phase: 0, level: SILENT, state: EnvelopeState::Release, output: [0; 2] }
+    }
+}
+impl Operator {
+    fn key_on(&mut self) {
+        self.phase = 0;
+        self.state = EnvelopeState::Attack;
+    }
+    fn clock_envelope(&mut self, rate: u8, sustain_level: u8, counter: u32) {
+        let step = envelope_step(rate, counter);
+        match self.state {
+            EnvelopeState::Attack => {
+                // exponential approach towards full volume
+                if rate >= 60 {
+                    self.level = 0;
+                } else if step > 0 {
+                    let delta = ((self.level as u32 * step) >> 3).max(1);
+                    self.level -= delta.min(self.level as u32) as u8;
+                }
+                if self.level == 0 { self.state = EnvelopeState::Decay }
+            },
+            EnvelopeState::Decay => {
+                self.level = (self.level as u32 + step).min(SILENT as u32) as u8;
+                if self.level >= sustain_level { self.state = EnvelopeState::Sustain }
+            },
+            _ => self.level = (self.level as u32 + step).min(SILENT as u32) as u8
+        }
+    }
+    fn output(&mut self, index: i32, half_sine: bool, attenuation: u32) -> i32 {
+        let output = if self.level >= SILENT { 0 } else { wave(index as u32 & 0x3ff, half_sine, attenuation) };
+        self.output = [output, self.output[0]];
+        output
+    }
+}
+impl Snapshot for Operator {
+    fn save(&self, w: &mut StateWriter) {
+        self.phase.save(w);
+        self.level.save(w);
+        (self.state as u8).save(w);
+        self.output.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.phase.load(r)?;
+        self.level.load(r)?;
+        self.state = EnvelopeState::from_index(r.read_u8()?)?;
+        self.output.load(r)
+    }
+}
+
+// effective rates are 0-63, the top 4 bits set how often the level moves
+fn envelope_step(rate: u8, counter: u32) -> u32 {
+    if rate < 4 { return 0 }
+    let (high, low) = ((rate >> 2) as u32, (rate & 3) as usize);
+    if high < 13 {
+        let shift = 13 - high;
+        if counter & ((1 << shift) - 1) != 0 { return 0 }
+        ENVELOPE_STEPS[low][(counter >> shift) as usize & 7] as u32
+    } else {
+        (1 + ENVELOPE_STEPS[low][counter as usize & 7] as u32) << (high - 13)
+    }
+}
+
+// `attenuation` is in envelope steps
+fn wave(index: u32, half_sine: bool, attenuation: u32) -> i32 {
+    let negative = index & 0x200 != 0;
+    if negative && half_sine { return 0 }
+    let quarter = if index & 0x100 != 0 { 0xff - (index & 0xff) } else { index & 0xff };
+    let log = LOG_SIN[quarter as usize] as u32 + (attenuation << 4);
+    let shift = log >> 8;
+    if shift > 11 { return 0 }
+    let linear = ((EXP[(log as usize & 0xff) ^ 0xff] as i32 + 1024) >> shift) << 1;
+    if negative { -linear } else { linear }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Channel {
+    fnum: u16,
+    block: u8,
+    // slow release after key off ($2x bit 5)
+    sustain: bool,
+    key: bool,
+    instrument: u8,
+    volume: u8,
+    // modulator and carrier
+    operators: [Operator; 2]
+}
+impl Channel {
+    fn write_key(&mut self, value: u8) {
+        let key = value & 0x10 != 0;
+        if key && !self.key {
+            self.operators.iter_mut().for_each(|op| op.key_on());
+        } else if !key && self.key {
+            self.operators.iter_mut().for_each(|op| op.state = EnvelopeState::Release);
+        }
+        self.key = key;
+        self.sustain = value & 0x20 != 0;
+        self.block = (value >> 1) & 7;
+        self.fnum = self.fnum & 0xff | ((value & 1) as u16) << 8;
+    }
+    fn sample(&mut self, patch: &[u8; 8], counter: u32, tremolo: u32, vibrato: usize) -> i32 {
+        let mut modulation = 0;
+        for i in 0..2 {
+            let flags = patch[i];
+            let sustained = flags & 0x20 != 0;
+
+            let mut fnum = self.fnum as i32;
+            if flags & 0x40 != 0 { fnum += VIBRATO[fnum as usize >> 6][vibrato] as i32 }
+            let increment = ((fnum as u32 * MULTIPLIERS[flags as usize & 0x0f]) << self.block) >> 2;
+
+            // key scaling of the envelope rates
+            let key = (self.block << 1 | (self.fnum >> 8) as u8) >> if flags & 0x10 != 0 { 0 } else { 2 };
+            let rate = match self.operators[i].state {
+                EnvelopeState::Attack => patch[4 + i] >> 4,
+                EnvelopeState::Decay => patch[4 + i] & 0x0f,
+                EnvelopeState::Sustain if sustained => 0,
+                EnvelopeState::Sustain => patch[6 + i] & 0x0f,
+                EnvelopeState::Release if self.sustain => 5,
+                EnvelopeState::Release if sustained => patch[6 + i] & 0x0f,
+                EnvelopeState::Release => 7
+            };
+            let rate = if rate == 0 { 0 } else { (rate * 4 + key).min(63) };
+            let operator = &mut self.operators[i];
+            operator.clock_envelope(rate, (patch[6 + i] >> 4) * 8, counter);
+            operator.phase = (operator.phase + increment) & 0x3ffff;
+
+            // key scale level, 6dB per octave at its highest setting
+            let ksl = (patch[2 + i] >> 6) as i32;
+            let scale = 2 * KEY_SCALE[self.fnum as usize >> 5] - 48 * (7 - self.block as i32);
+            let scale = if ksl == 0 || scale <= 0 { 0 } else { (scale >> (3 - ksl)) / 3 };
+            let level = if i == 0 { (patch[2] & 0x3f) as u32 * 2 } else { self.volume as u32 * 8 };
+            let tremolo = if flags & 0x80 != 0 { tremolo } else { 0 };
+            let attenuation = (operator.level as u32 + level + scale as u32 + tremolo).min(SILENT as u32);
+
+            let index = (operator.phase >> 8) as i32;
+            if i == 0 {
+                let feedback = patch[3] & 7;
+                let feedback = if feedback == 0 {
+                    0
+                } else {
+                    (operator.output[0] + operator.output[1]) >> (9 - feedback)
+                };
+                modulation = operator.output(index + feedback, patch[3] & 0x08 != 0, attenuation);
+            } else {
+                return operator.output(index + modulation, patch[3] & 0x10 != 0, attenuation)
+            }
+        }
+        0
+    }
+}
+impl Snapshot for Channel {
+    fn save(&self, w: &mut StateWriter) {
+        self.fnum.save(w);
+        self.block.save(w);
+        self.sustain.save(w);
+        self.key.save(w);
+        self.instrument.save(w);
+        self.volume.save(w);
+        self.operators.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.fnum.load(r)?;
+        self.block.load(r)?;
+        self.sustain.load(r)?;
+        self.key.load(r)?;
+        self.instrument.load(r)?;
+        self.volume.load(r)?;
+        self.operators.load(r)
+    }
+}
+
+// Konami VRC7 sound, a cut down YM2413 (OPLL): 6 channels of 2 operator FM,
+// no rhythm mode and its own set of built-in instruments
+#[derive(Default)]
+pub struct Vrc7Audio {
+    custom: [u8; 8],
+    address: u8,
+    channels: [Channel; 6],
+    silenced: bool,
+    divider: u8,
+    // sample counter driving the envelopes and the lfos
+    counter: u32,
+    outputs: [i32; 6]
+}
+impl Vrc7Audio {
+    pub fn write_address(&mut self, value: u8) {
+        self.address = value;
+    }
+    pub fn write_data(&mut self, value: u8) {
+        let channel = (self.address & 0x0f) as usize;
+        match self.address {
+            0x00..=0x07 => self.custom[self.address as usize] = value,
+            0x10..=0x15 => self.channels[channel].fnum = self.channels[channel].fnum & 0x100 | value as u16,
+            0x20..=0x25 => self.channels[channel].write_key(value),
+            0x30..=0x35 => {
+                self.channels[channel].instrument = value >> 4;
+                self.channels[channel].volume = value & 0x0f;
+            },
+            _ => ()
+        }
+    }
+    // $e000 bit 6 holds the sound chip in reset
+    pub fn set_silenced(&mut self, silenced: bool) {
+        if silenced && !self.silenced {
+            self.channels = [Channel::default(); 6];
+            self.outputs = [0; 6];
+        }
+        self.silenced = silenced;
+    }
+    pub fn clock(&mut self) {
+        if self.silenced { return }
+        self.divider += 1;
+        if self.divider < SAMPLE_PERIOD { return }
+        self.divider = 0;
+        self.counter = self.counter.wrapping_add(1);
+
+        // tremolo is a 4.8dB triangle at ~3.7Hz, vibrato cycles at ~6.1Hz
+        let step = (self.counter >> 8) % 52;
+        let tremolo = if step < 26 { step } else { 51 - step } >> 1;
+        let vibrato = (self.counter >> 10) as usize & 7;
+        for (channel, output) in self.channels.iter_mut().zip(self.outputs.iter_mut()) {
+            let patch = match channel.instrument {
+                0 => self.custom,
+                n => PATCHES[n as usize - 1]
+            };
+            *output = channel.sample(&patch, self.counter, tremolo, vibrato);
+        }
+    }
+}
+impl ExpansionAudio for Vrc7Audio {
+    fn channels(&self) -> &'static [&'static str] {
+        &CHANNELS
+    }
+    fn channel_output(&self, channel: usize) -> f32 {
+        self.outputs.get(channel).map_or(0.0, |&o| o as f32 * GAIN)
+    }
+}
+impl Snapshot for Vrc7Audio {
+    fn save(&self, w: &mut StateWriter) {
+        self.custom.save(w);
+        self.address.save(w);
+        self.channels.save(w);
+        self.silenced.save(w);
+        self.divider.save(w);
+        self.counter.save(w);
+        self.outputs.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.custom.load(r)?;
+        self.address.load(r)?;
+        self.channels.load(r)?;
+        self.silenced.load(r)?;
+        self.divider.load(r)?;
+        self.counter.load(r)?;
+        self.outputs.load(r)
+    }
+}
\ No newline at end of file
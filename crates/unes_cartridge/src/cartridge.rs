@@ -0,0 +1,60 @@
+use alloc::boxed::Box;
+use core::fmt;
+
+use crate::header::Header;
+use crate::mappers::{self, Mapper};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CartridgeError {
+    InvalidHeader,
+    Truncated,
+    // the header says there is no prg rom
+    NoPrgRom,
+    UnsupportedMapper(u16)
+}
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "not an iNES file"),
+            Self::Truncated => write!(f, "rom file is truncated"),
+            Self::NoPrgRom => write!(f, "rom has no prg rom"),
+            Self::UnsupportedMapper(id) => write!(f, "mapper {} is not supported", id)
+        }
+    }
+}
+
+pub struct Cartridge {
+    pub header: Header,
+    pub mapper: Box<dyn Mapper>
+}
+impl Cartridge {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Cartridge, CartridgeError> {
+        let header = Header::parse(bytes)?;
+        if header.prg_rom_size == 0 { return Err(CartridgeError::NoPrgRom) }
+        let prg_start = header.prg_rom_offset();
+        let chr_start = header.chr_rom_offset().ok_or(CartridgeError::Truncated)?;
+        let chr_end = chr_start.checked_add(header.chr_rom_size).ok_or(CartridgeError::Truncated)?;
+        if bytes.len() < chr_end { return Err(CartridgeError::Truncated) }
+
+        let mapper = mappers::create(
+            &header,
+            bytes[prg_start..chr_start].to_vec(),
+            bytes[chr_start..chr_end].to_vec()
+        )?;
+        Ok(Cartridge { header, mapper })
+    }
+    // battery backed ram (or eeprom) contents, None without a battery
+    pub fn save_data(&self) -> Option<&[u8]> {
+        self.mapper.save_ram().map(|ram| &ram[..])
+    }
+    pub fn load_save_data(&mut self, data: &[u8]) {
+        if let Some(ram) = self.mapper.save_ram_mut() { ram.load(data) }
+    }
+    // whether the save data changed since it was last loaded or saved
+    pub fn save_dirty(&self) -> bool {
+        self.mapper.save_ram().is_some_and(|ram| ram.is_dirty())
+    }
+    pub fn mark_saved(&mut self) {
+        if let Some(ram) = self.mapper.save_ram_mut() { ram.mark_clean() }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,116 @@
+use crate::cartridge::CartridgeError;
+use crate::mirroring::Mirroring;
+use crate::timing::Timing;
+
+pub const HEADER_SIZE: usize = 16;
+pub const TRAINER_SIZE: usize = 512;
+const MAGIC: [u8; 4] = [b'N', b'E', b'S', 0x1a];
+
+// iNES / NES 2.0 file header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub nes2: bool,
+    pub mapper: u16,
+    pub submapper: u8,
+    pub prg_rom_size: usize,
+    pub chr_rom_size: usize,
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+    pub mirroring: Mirroring,
+    pub battery: bool,
+    pub trainer: bool,
+    pub timing: Timing
+}
+impl Header {
+    pub fn parse(bytes: &[u8]) -> Result<Header, CartridgeError> {
+        if bytes.len() < HEADER_SIZE { return Err(CartridgeError::Truncated) }
+        if bytes[0..4] != MAGIC { return Err(CartridgeError::InvalidHeader) }
+
+        let flags_6 = bytes[6];
+        let flags_7 = bytes[7];
+        let nes2 = flags_7 & 0x0c == 0x08;
+        let mirroring = match flags_6 & 0b1001 {
+            0b1000 | 0b1001 => Mirroring::FourScreen,
+            0b0001 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal
+        };
+        let battery = flags_6 & 0b10 != 0;
+        let trainer = flags_6 & 0b100 != 0;
+
+        if nes2 {
+            return Ok(Header {
+                nes2,
+                mapper: (flags_6 >> 4) as u16
+                    | (flags_7 & 0xf0) as u16
+                    | ((bytes[8] & 0x0f) as u16) << 8,
+                submapper: bytes[8] >> 4,
+                prg_rom_size: nes2_rom_size(bytes[4], bytes[9] & 0x0f, 0x4000),
+                chr_rom_size: nes2_rom_size(bytes[5], bytes[9] >> 4, 0x2000),
+                prg_ram_size: nes2_ram_size(bytes[10] & 0x0f),
+                prg_nvram_size: nes2_ram_size(bytes[10] >> 4),
+                chr_ram_size: nes2_ram_size(bytes[11] & 0x0f),
+                chr_nvram_size: nes2_ram_size(bytes[11] >> 4),
+                mirroring,
+                battery,
+                trainer,
+                timing: Timing::from_nes2(bytes[12])
+            })
+        }
+
+        // old dumps often carry garbage (eg. "DiskDude!") in bytes 7-15,
+        // in that case the upper mapper nibble cannot be trusted
+        let dirty = flags_7 & 0x0c == 0 && bytes[12..16].iter().any(|&b| b != 0);
+        let mapper_high = if dirty { 0 } else { flags_7 & 0xf0 };
+        let chr_rom_size = bytes[5] as usize * 0x2000;
+        let prg_ram_size = if dirty || bytes[8] == 0 { 0x2000 } else { bytes[8] as usize * 0x2000 };
+        Ok(Header {
+            nes2,
+            mapper: (mapper_high | flags_6 >> 4) as u16,
+            submapper: 0,
+            prg_rom_size: bytes[4] as usize * 0x4000,
+            chr_rom_size,
+            // iNES 1 cannot tell battery backed ram apart from the volatile one
+            prg_ram_size: if battery { 0 } else { prg_ram_size },
+            prg_nvram_size: if battery { prg_ram_size } else { 0 },
+            chr_ram_size: if chr_rom_size == 0 { 0x2000 } else { 0 },
+            chr_nvram_size: 0,
+            mirroring,
+            battery,
+            trainer,
+            // byte 9 bit 0, hardly ever set
+            timing: if !dirty && bytes[9] & 1 != 0 { Timing::Pal } else { Timing::Ntsc }
+        })
+    }
+    // offset of the prg rom data in the file
+    pub fn prg_rom_offset(&self) -> usize {
+        if self.trainer { HEADER_SIZE + TRAINER_SIZE } else { HEADER_SIZE }
+    }
+    // None when the prg rom size is past any file (the NES 2.0 exponent notation saturates)
+    pub fn chr_rom_offset(&self) -> Option<usize> {
+        self.prg_rom_offset().checked_add(self.prg_rom_size)
+    }
+    // chr ram of a board without chr rom, battery backed or not
+    // (a NES 2.0 header that gives no size at all still gets 8KB)
+    pub fn chr_ram_len(&self) -> usize {
+        match self.chr_ram_size + self.chr_nvram_size {
+            0 => 0x2000,
+            len => len
+        }
+    }
+}
+
+fn nes2_rom_size(lsb: u8, msb: u8, unit: usize) -> usize {
+    if msb == 0x0f {
+        // exponent-multiplier notation: 2^E * (MM * 2 + 1)
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = (lsb & 0b11) as usize * 2 + 1;
+        return 2usize.saturating_pow(exponent).saturating_mul(multiplier)
+    }
+    ((msb as usize) << 8 | lsb as usize) * unit
+}
+
+fn nes2_ram_size(shift: u8) -> usize {
+    if shift == 0 { 0 } else { 64 << shift }
+}
\ No newline at end of file
@@ -0,0 +1,23 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+pub mod audio;
+mod cartridge;
+mod header;
+pub mod mappers;
+mod mirroring;
+#[cfg(feature = "std")]
+mod sav;
+mod save_ram;
+mod tests;
+mod timing;
+mod utils;
+
+pub use cartridge::{Cartridge, CartridgeError};
+pub use header::Header;
+pub use mappers::Mapper;
+pub use mirroring::Mirroring;
+#[cfg(feature = "std")]
+pub use sav::sav_path;
+pub use save_ram::SaveRam;
+pub use timing::Timing;
\ No newline at end of file
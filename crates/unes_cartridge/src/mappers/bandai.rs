@@ -0,0 +1,179 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mappers::eeprom::Eeprom;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+use crate::utils::{bank_addr, chr_memory};
+
+// Bandai FCG boards, mapper 16 and 159
+// Submapper 4 is the FCG-1/2, with its registers at $6000-$7fff and no eeprom.
+// Submapper 5 is the LZ93D50 at $8000-$ffff with a 24C02, mapper 159 the same
+// chip with a 24C01. Without a submapper both register ranges are decoded.
+pub struct Bandai {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    low_registers: bool,
+    high_registers: bool,
+    // the LZ93D50 reloads the counter from a latch when the irq is enabled,
+    // the FCG-1/2 writes the counter directly
+    irq_latched: bool,
+
+    prg_bank: u8,
+    chr_banks: [u8; 8],
+    mirroring: Mirroring,
+    irq_enabled: bool,
+    irq_counter: u16,
+    irq_latch: u16,
+    irq_pending: bool,
+    eeprom: Option<Eeprom>
+}
+impl Bandai {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Bandai {
+        let chr_ram = chr_rom.is_empty();
+        let (fcg, lz93d50) = match (header.mapper, header.submapper) {
+            (16, 4) => (true, false),
+            (16, 5) | (159, _) => (false, true),
+            _ => (true, true)
+        };
+        Bandai {
+            prg_rom,
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            low_registers: fcg,
+            high_registers: lz93d50,
+            irq_latched: lz93d50,
+            prg_bank: 0,
+            chr_banks: [0; 8],
+            mirroring: Mirroring::Vertical,
+            irq_enabled: false,
+            irq_counter: 0,
+            irq_latch: 0,
+            irq_pending: false,
+            eeprom: match (header.mapper, lz93d50) {
+                (159, _) => Some(Eeprom::new_24c01()),
+                (_, true) => Some(Eeprom::new_24c02()),
+                _ => None
+            }
+        }
+    }
+    fn chr_addr(&self, addr: u16) -> usize {
+        let bank = self.chr_banks[addr as usize >> 10];
+        bank_addr(bank as usize, 0x400, addr, self.chr.len())
+    }
+    fn write_register(&mut self, reg: u16, value: u8) {
+        match reg {
+            0x0..=0x7 => self.chr_banks[reg as usize] = value,
+            0x8 => self.prg_bank = value & 0x0f,
+            0x9 => self.mirroring = match value & 3 {
+                0 => Mirroring::Vertical,
+                1 => Mirroring::Horizontal,
+                2 => Mirroring::SingleScreenA,
+                _ => Mirroring::SingleScreenB
+            },
+            0xa => {
+                self.irq_enabled = value & 1 != 0;
+                self.irq_pending = false;
+                if self.irq_latched { self.irq_counter = self.irq_latch }
+            },
+            0xb | 0xc => {
+                let target = if self.irq_latched { &mut self.irq_latch } else { &mut self.irq_counter };
+                *target = if reg == 0xb {
+                    *target & 0xff00 | value as u16
+                } else {
+                    *target & 0xff | (value as u16) << 8
+                };
+            },
+            // bit 5 scl, bit 6 sda, bit 7 releases sda so the eeprom can drive it
+            0xd => if let Some(eeprom) = &mut self.eeprom {
+                eeprom.write_lines(value & 0x20 != 0, value & 0xc0 != 0);
+            },
+            _ => ()
+        }
+    }
+}
+impl Mapper for Bandai {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            // the eeprom data line shows up on bit 4, the rest is open bus
+            0x6000..=0x7fff => self.eeprom.as_ref().map(|e| (e.output() as u8) << 4),
+            0x8000..=0xffff => {
+                // the last bank is fixed at $c000
+                let bank = match addr {
+                    0x8000..=0xbfff => self.prg_bank as usize,
+                    _ => (self.prg_rom.len() / 0x4000).saturating_sub(1)
+                };
+                Some(self.prg_rom[bank_addr(bank, 0x4000, addr, self.prg_rom.len())])
+            },
+            _ => None
+        }
+    }
+    fn driven_bits(&self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7fff => 0x10,
+            _ => 0xff
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        let decoded = match addr {
+            0x6000..=0x7fff => self.low_registers,
+            0x8000..=0xffff => self.high_registers,
+            _ => false
+        };
+        if decoded { self.write_register(addr & 0x0f, value) }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[self.chr_addr(addr)]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let offset = self.chr_addr(addr);
+        self.chr[offset] = value;
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        self.eeprom.as_ref().map(|e| &e.data)
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        self.eeprom.as_mut().map(|e| &mut e.data)
+    }
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+    fn clock(&mut self) {
+        if !self.irq_enabled { return }
+        self.irq_counter = self.irq_counter.wrapping_sub(1);
+        if self.irq_counter == 0 { self.irq_pending = true }
+    }
+    fn irq(&self) -> bool {
+        self.irq_pending
+    }
+}
+impl Snapshot for Bandai {
+    fn save(&self, w: &mut StateWriter) {
+        if self.chr_ram { self.chr.save(w) }
+        self.prg_bank.save(w);
+        self.chr_banks.save(w);
+        self.mirroring.save(w);
+        self.irq_enabled.save(w);
+        self.irq_counter.save(w);
+        self.irq_latch.save(w);
+        self.irq_pending.save(w);
+        if let Some(eeprom) = &self.eeprom { eeprom.save(w) }
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        if self.chr_ram { self.chr.load(r)? }
+        self.prg_bank.load(r)?;
+        self.chr_banks.load(r)?;
+        self.mirroring.load(r)?;
+        self.irq_enabled.load(r)?;
+        self.irq_counter.load(r)?;
+        self.irq_latch.load(r)?;
+        self.irq_pending.load(r)?;
+        if let Some(eeprom) = &mut self.eeprom { eeprom.load(r)? }
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mirroring::Mirroring;
+use crate::utils::{bank_offset, chr_memory};
+
+// CPROM, mapper 13
+// 32KB of prg rom and 16KB of chr ram, banked in 4KB: $0000 always shows
+// the first bank, $1000 the one selected by a write to $8000-$ffff.
+// The board has bus conflicts.
+pub struct Cprom {
+    prg_rom: Vec<u8>,
+    chr_ram: Vec<u8>,
+    mirroring: Mirroring,
+    chr_bank: u8
+}
+impl Cprom {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Cprom {
+        let mut chr_ram = chr_memory(header, chr_rom);
+        // iNES 1 headers only ever ask for 8KB
+        if chr_ram.len() < 0x4000 { chr_ram.resize(0x4000, 0) }
+        Cprom { prg_rom, chr_ram, mirroring: header.mirroring, chr_bank: 0 }
+    }
+    fn chr_addr(&self, addr: u16) -> usize {
+        let bank = if addr < 0x1000 { 0 } else { self.chr_bank as usize };
+        bank_offset(bank, 0x1000, self.chr_ram.len()) + (addr as usize & 0x0fff)
+    }
+}
+impl Mapper for Cprom {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x8000..=0xffff => Some(self.prg_rom[(addr as usize - 0x8000) % self.prg_rom.len()]),
+            _ => None
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        if let Some(rom) = self.cpu_read(addr) { self.chr_bank = value & rom & 3 }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr_ram[self.chr_addr(addr)]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        let offset = self.chr_addr(addr);
+        self.chr_ram[offset] = value;
+    }
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
+impl Snapshot for Cprom {
+    fn save(&self, w: &mut StateWriter) {
+        self.chr_ram.save(w);
+        self.chr_bank.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.chr_ram.load(r)?;
+        self.chr_bank.load(r)
+    }
+}
\ No newline at end of file
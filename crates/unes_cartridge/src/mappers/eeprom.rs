@@ -0,0 +1,176 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::save_ram::SaveRam;
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    // waiting for a start condition
+    Idle,
+    Device,
+    Address,
+    Write,
+    Read
+}
+impl State {
+    fn from_index(index: u8) -> Result<State, StateError> {
+        match index {
+            0 => Ok(State::Idle),
+            1 => Ok(State::Device),
+            2 => Ok(State::Address),
+            3 => Ok(State::Write),
+            4 => Ok(State::Read),
+            _ => Err(StateError::InvalidData)
+        }
+    }
+}
+
+// serial (i2c) eeprom found on the Bandai boards
+// The 24C02 (256 bytes) expects a device select byte before the address,
+// the 24C01 (128 bytes) starts with the address and sends every byte lsb first.
+pub struct Eeprom {
+    pub data: SaveRam,
+    small: bool,
+    state: State,
+    scl: bool,
+    sda: bool,
+    shift: u8,
+    bits: u8,
+    // the 9th clock of a byte
+    ack: bool,
+    address: u8,
+    output: bool
+}
+impl Eeprom {
+    pub fn new_24c01() -> Eeprom {
+        Eeprom::new(128, true)
+    }
+    pub fn new_24c02() -> Eeprom {
+        Eeprom::new(256, false)
+    }
+    fn new(size: usize, small: bool) -> Eeprom {
+        Eeprom {
+            data: SaveRam::new(size, true),
+            small,
+            state: State::Idle,
+            scl: false,
+            sda: true,
+            shift: 0,
+            bits: 0,
+            ack: false,
+            address: 0,
+            output: true
+        }
+    }
+    // sda as driven by the eeprom (open drain, true when released)
+    pub fn output(&self) -> bool {
+        self.output
+    }
+    pub fn write_lines(&mut self, scl: bool, sda: bool) {
+        if self.scl && scl && sda != self.sda {
+            // sda only changes while scl is high for start and stop conditions
+            self.output = true;
+            self.ack = false;
+            self.bits = 0;
+            self.state = match sda {
+                false if self.small => State::Address,
+                false => State::Device,
+                true => State::Idle
+            };
+        } else if !self.scl && scl {
+            self.rising_edge(sda);
+        } else if self.scl && !scl {
+            self.falling_edge();
+        }
+        self.scl = scl;
+        self.sda = sda;
+    }
+    fn rising_edge(&mut self, sda: bool) {
+        if self.ack {
+            self.ack = false;
+            self.bits = 0;
+            return
+        }
+        match self.state {
+            State::Idle => (),
+            State::Read if self.bits < 8 => self.bits += 1,
+            // no acknowledge from the cpu ends the read
+            State::Read if sda => self.state = State::Idle,
+            State::Read => {
+                self.next_address();
+                self.bits = 0;
+            },
+            _ => {
+                self.shift = if self.small {
+                    self.shift >> 1 | (sda as u8) << 7
+                } else {
+                    self.shift << 1 | sda as u8
+                };
+                self.bits += 1;
+                if self.bits == 8 { self.receive(self.shift) }
+            }
+        }
+    }
+    fn falling_edge(&mut self) {
+        self.output = match self.state {
+            _ if self.ack => false,
+            State::Read if self.bits < 8 => {
+                let byte = self.data[self.address as usize];
+                let bit = if self.small { self.bits } else { 7 - self.bits };
+                byte & (1 << bit) != 0
+            },
+            _ => true
+        };
+    }
+    fn receive(&mut self, byte: u8) {
+        self.ack = true;
+        self.state = match self.state {
+            State::Device if byte & 0xf0 != 0xa0 => {
+                // another device on the bus
+                self.ack = false;
+                State::Idle
+            },
+            State::Device if byte & 1 != 0 => State::Read,
+            State::Device => State::Address,
+            State::Address if self.small => {
+                self.address = byte & 0x7f;
+                if byte & 0x80 != 0 { State::Read } else { State::Write }
+            },
+            State::Address => {
+                self.address = byte;
+                State::Write
+            },
+            _ => {
+                self.data[self.address as usize] = byte;
+                self.next_address();
+                State::Write
+            }
+        };
+    }
+    fn next_address(&mut self) {
+        self.address = ((self.address as usize + 1) % self.data.len()) as u8;
+    }
+}
+impl Snapshot for Eeprom {
+    fn save(&self, w: &mut StateWriter) {
+        self.data.save(w);
+        (self.state as u8).save(w);
+        self.scl.save(w);
+        self.sda.save(w);
+        self.shift.save(w);
+        self.bits.save(w);
+        self.ack.save(w);
+        self.address.save(w);
+        self.output.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        Snapshot::load(&mut self.data, r)?;
+        self.state = State::from_index(r.read_u8()?)?;
+        self.scl.load(r)?;
+        self.sda.load(r)?;
+        self.shift.load(r)?;
+        self.bits.load(r)?;
+        self.ack.load(r)?;
+        self.address.load(r)?;
+        self.output.load(r)
+    }
+}
\ No newline at end of file
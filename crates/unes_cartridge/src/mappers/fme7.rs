@@ -0,0 +1,179 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::ExpansionAudio;
+use crate::audio::sunsoft5b::Sunsoft5bAudio;
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+use crate::utils::{bank_addr, chr_memory};
+
+// Sunsoft FME-7 and 5A/5B, mapper 69
+// Registers are written through a command ($8000) / parameter ($a000) pair.
+// Only the 5B has the sound chip, on the other boards it is just never written.
+pub struct Fme7 {
+    prg_rom: Vec<u8>,
+    prg_ram: SaveRam,
+    chr: Vec<u8>,
+    chr_ram: bool,
+
+    command: u8,
+    chr_banks: [u8; 8],
+    // command 8: bit 7 ram enable, bit 6 ram instead of rom, low bits the bank
+    prg_6000: u8,
+    prg_banks: [u8; 3],
+    mirroring: Mirroring,
+    irq_enabled: bool,
+    irq_counter_enabled: bool,
+    irq_counter: u16,
+    irq_pending: bool,
+    audio: Sunsoft5bAudio
+}
+impl Fme7 {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Fme7 {
+        let chr_ram = chr_rom.is_empty();
+        Fme7 {
+            prg_rom,
+            prg_ram: SaveRam::prg(header),
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            command: 0,
+            chr_banks: [0; 8],
+            prg_6000: 0,
+            prg_banks: [0; 3],
+            mirroring: Mirroring::Vertical,
+            irq_enabled: false,
+            irq_counter_enabled: false,
+            irq_counter: 0,
+            irq_pending: false,
+            audio: Sunsoft5bAudio::default()
+        }
+    }
+    fn prg_addr(&self, addr: u16) -> usize {
+        let bank = match addr {
+            0x6000..=0x7fff => (self.prg_6000 & 0x3f) as usize,
+            0xe000..=0xffff => (self.prg_rom.len() / 0x2000).saturating_sub(1),
+            _ => self.prg_banks[(addr as usize - 0x8000) >> 13] as usize
+        };
+        bank_addr(bank, 0x2000, addr, self.prg_rom.len())
+    }
+    fn ram_selected(&self) -> bool {
+        self.prg_6000 & 0x40 != 0
+    }
+    fn ram_enabled(&self) -> bool {
+        self.ram_selected() && self.prg_6000 & 0x80 != 0 && !self.prg_ram.is_empty()
+    }
+    fn ram_addr(&self, addr: u16) -> usize {
+        bank_addr((self.prg_6000 & 0x3f) as usize, 0x2000, addr, self.prg_ram.len())
+    }
+    fn chr_addr(&self, addr: u16) -> usize {
+        let bank = self.chr_banks[addr as usize >> 10];
+        bank_addr(bank as usize, 0x400, addr, self.chr.len())
+    }
+    fn write_parameter(&mut self, value: u8) {
+        match self.command {
+            0x0..=0x7 => self.chr_banks[self.command as usize] = value,
+            0x8 => self.prg_6000 = value,
+            0x9..=0xb => self.prg_banks[self.command as usize - 9] = value & 0x3f,
+            0xc => self.mirroring = match value & 3 {
+                0 => Mirroring::Vertical,
+                1 => Mirroring::Horizontal,
+                2 => Mirroring::SingleScreenA,
+                _ => Mirroring::SingleScreenB
+            },
+            0xd => {
+                self.irq_enabled = value & 1 != 0;
+                self.irq_counter_enabled = value & 0x80 != 0;
+                self.irq_pending = false;
+            },
+            0xe => self.irq_counter = self.irq_counter & 0xff00 | value as u16,
+            _ => self.irq_counter = self.irq_counter & 0xff | (value as u16) << 8
+        }
+    }
+}
+impl Mapper for Fme7 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7fff if self.ram_enabled() => Some(self.prg_ram[self.ram_addr(addr)]),
+            // selected but disabled ram is open bus
+            0x6000..=0x7fff if self.ram_selected() => None,
+            0x6000..=0xffff => Some(self.prg_rom[self.prg_addr(addr)]),
+            _ => None
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x6000..=0x7fff if self.ram_enabled() => {
+                let offset = self.ram_addr(addr);
+                self.prg_ram[offset] = value;
+            },
+            0x8000..=0x9fff => self.command = value & 0x0f,
+            0xa000..=0xbfff => self.write_parameter(value),
+            0xc000..=0xdfff => self.audio.write_address(value),
+            0xe000..=0xffff => self.audio.write_data(value),
+            _ => ()
+        }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[self.chr_addr(addr)]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let offset = self.chr_addr(addr);
+        self.chr[offset] = value;
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        Some(&self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        Some(&mut self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+    fn clock(&mut self) {
+        if self.irq_counter_enabled {
+            self.irq_counter = self.irq_counter.wrapping_sub(1);
+            if self.irq_counter == 0xffff && self.irq_enabled { self.irq_pending = true }
+        }
+        self.audio.clock();
+    }
+    fn irq(&self) -> bool {
+        self.irq_pending
+    }
+    fn audio(&self) -> Option<&dyn ExpansionAudio> {
+        Some(&self.audio)
+    }
+}
+impl Snapshot for Fme7 {
+    fn save(&self, w: &mut StateWriter) {
+        self.prg_ram.save(w);
+        if self.chr_ram { self.chr.save(w) }
+        self.command.save(w);
+        self.chr_banks.save(w);
+        self.prg_6000.save(w);
+        self.prg_banks.save(w);
+        self.mirroring.save(w);
+        self.irq_enabled.save(w);
+        self.irq_counter_enabled.save(w);
+        self.irq_counter.save(w);
+        self.irq_pending.save(w);
+        self.audio.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        Snapshot::load(&mut self.prg_ram, r)?;
+        if self.chr_ram { self.chr.load(r)? }
+        self.command.load(r)?;
+        self.chr_banks.load(r)?;
+        self.prg_6000.load(r)?;
+        self.prg_banks.load(r)?;
+        self.mirroring.load(r)?;
+        self.irq_enabled.load(r)?;
+        self.irq_counter_enabled.load(r)?;
+        self.irq_counter.load(r)?;
+        self.irq_pending.load(r)?;
+        self.audio.load(r)
+    }
+}
\ No newline at end of file
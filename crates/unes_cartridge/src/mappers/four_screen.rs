@@ -0,0 +1,79 @@
+use alloc::boxed::Box;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::ExpansionAudio;
+use crate::mappers::Mapper;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+
+// boards with 2KB of extra vram, wired so that each of the four
+// nametables has its own memory (header flag 6 bit 3)
+// The ciram holds $2000 and $2400, the board $2800 and $2c00,
+// the mirroring of the wrapped board is ignored.
+pub struct FourScreen {
+    inner: Box<dyn Mapper>,
+    vram: [u8; 0x800]
+}
+impl FourScreen {
+    pub fn new(inner: Box<dyn Mapper>) -> FourScreen {
+        FourScreen { inner, vram: [0; 0x800] }
+    }
+}
+impl Mapper for FourScreen {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        self.inner.cpu_read(addr)
+    }
+    fn driven_bits(&self, addr: u16) -> u8 {
+        self.inner.driven_bits(addr)
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        self.inner.cpu_write(addr, value);
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.inner.ppu_read(addr)
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        self.inner.ppu_write(addr, value);
+    }
+    fn nametable_read(&mut self, addr: u16, ciram: &[u8]) -> u8 {
+        match addr & 0x0800 {
+            0 => ciram[addr as usize & 0x7ff],
+            _ => self.vram[addr as usize & 0x7ff]
+        }
+    }
+    fn nametable_write(&mut self, addr: u16, value: u8, ciram: &mut [u8]) {
+        match addr & 0x0800 {
+            0 => ciram[addr as usize & 0x7ff] = value,
+            _ => self.vram[addr as usize & 0x7ff] = value
+        }
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        self.inner.save_ram()
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        self.inner.save_ram_mut()
+    }
+    fn mirroring(&self) -> Mirroring {
+        Mirroring::FourScreen
+    }
+    fn clock(&mut self) {
+        self.inner.clock();
+    }
+    fn irq(&self) -> bool {
+        self.inner.irq()
+    }
+    fn audio(&self) -> Option<&dyn ExpansionAudio> {
+        self.inner.audio()
+    }
+}
+impl Snapshot for FourScreen {
+    fn save(&self, w: &mut StateWriter) {
+        self.inner.save(w);
+        self.vram.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.inner.load(r)?;
+        self.vram.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,508 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::ExpansionAudio;
+use crate::audio::mmc5::Mmc5Audio;
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+use crate::utils::{bank_addr, chr_memory};
+
+const EXRAM_SIZE: usize = 0x400;
+// the ppu performs 32 background tile fetches per scanline (tiles 2-33,
+// as 0 and 1 are prefetched on the previous line), then 16 garbage
+// nametable fetches during the sprite pattern loads
+const BG_FETCHES: u8 = 32;
+const SPRITE_FETCHES_END: u8 = 48;
+
+// what the mapper substitutes for the background tile being fetched
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TileFetch {
+    Normal,
+    // extended attribute mode, holds the exram byte for the tile
+    ExAttr(u8),
+    // vertical split region, the tile comes from exram
+    Split { tile: u8, column: u8, y: u8 }
+}
+impl Snapshot for TileFetch {
+    fn save(&self, w: &mut StateWriter) {
+        match *self {
+            TileFetch::Normal => w.write_u8(0),
+            TileFetch::ExAttr(value) => w.write_bytes(&[1, value]),
+            TileFetch::Split { tile, column, y } => w.write_bytes(&[2, tile, column, y])
+        }
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = match r.read_u8()? {
+            0 => TileFetch::Normal,
+            1 => TileFetch::ExAttr(r.read_u8()?),
+            2 => TileFetch::Split { tile: r.read_u8()?, column: r.read_u8()?, y: r.read_u8()? },
+            _ => return Err(StateError::InvalidData)
+        };
+        Ok(())
+    }
+}
+
+// mapper 5
+pub struct Mmc5 {
+    prg_rom: Vec<u8>,
+    prg_ram: SaveRam,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    exram: [u8; EXRAM_SIZE],
+
+    prg_mode: u8,
+    chr_mode: u8,
+    prg_ram_protect: [u8; 2],
+    exram_mode: u8,
+    nametable_mapping: u8,
+    fill_tile: u8,
+    fill_attr: u8,
+    prg_ram_bank: u8,
+    // $5114-$5117
+    prg_banks: [u8; 4],
+    // $5120-$5127, used for sprites in 8x16 mode
+    chr_banks_a: [u16; 8],
+    // $5128-$512b, used for the background in 8x16 mode
+    chr_banks_b: [u16; 4],
+    chr_upper: u8,
+    last_chr_write_b: bool,
+
+    split_control: u8,
+    split_scroll: u8,
+    split_bank: u8,
+    split_y: u8,
+
+    irq_compare: u8,
+    irq_enabled: bool,
+    irq_pending: bool,
+    multiplicand: u8,
+    multiplier: u8,
+
+    // ppu state snooped from the cpu and ppu buses
+    sprites_8x16: bool,
+    in_frame: bool,
+    scanline: u8,
+    last_ppu_addr: u16,
+    ppu_addr_matches: u8,
+    idle_cycles: u8,
+    nametable_fetches: u8,
+    tile_fetch: TileFetch,
+
+    audio: Mmc5Audio
+}
+impl Mmc5 {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Mmc5 {
+        let chr_ram = chr_rom.is_empty();
+        // iNES 1 headers rarely state the ram size, so give those
+        // the maximum of two 32KB chips
+        let prg_ram_size = if header.nes2 {
+            header.prg_ram_size + header.prg_nvram_size
+        } else {
+            0x10000
+        };
+        Mmc5 {
+            prg_rom,
+            prg_ram: SaveRam::new(prg_ram_size, header.battery),
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            exram: [0; EXRAM_SIZE],
+            prg_mode: 3,
+            chr_mode: 0,
+            prg_ram_protect: [0; 2],
+            exram_mode: 0,
+            nametable_mapping: 0,
+            fill_tile: 0,
+            fill_attr: 0,
+            prg_ram_bank: 0,
+            prg_banks: [0, 0, 0, 0xff],
+            chr_banks_a: [0; 8],
+            chr_banks_b: [0; 4],
+            chr_upper: 0,
+            last_chr_write_b: false,
+            split_control: 0,
+            split_scroll: 0,
+            split_bank: 0,
+            split_y: 0,
+            irq_compare: 0,
+            irq_enabled: false,
+            irq_pending: false,
+            multiplicand: 0xff,
+            multiplier: 0xff,
+            sprites_8x16: false,
+            in_frame: false,
+            scanline: 0,
+            last_ppu_addr: 0,
+            ppu_addr_matches: 0,
+            idle_cycles: 0,
+            nametable_fetches: 0,
+            tile_fetch: TileFetch::Normal,
+            audio: Mmc5Audio::new()
+        }
+    }
+    // returns the chip (true for rom) and the byte offset for a $6000-$ffff address
+    fn prg_addr(&self, addr: u16) -> (bool, usize) {
+        if addr < 0x8000 {
+            return (false, bank_addr(self.prg_ram_bank as usize & 7, 0x2000, addr, self.prg_ram.len()))
+        }
+        // (register index, window size in 8KB units)
+        let (reg, size) = match (self.prg_mode, addr) {
+            (0, _) => (3, 4),
+            (1, 0x8000..=0xbfff) => (1, 2),
+            (1, _) => (3, 2),
+            (2, 0x8000..=0xbfff) => (1, 2),
+            (2, 0xc000..=0xdfff) => (2, 1),
+            (2, _) => (3, 1),
+            _ => ((addr as usize - 0x8000) >> 13, 1)
+        };
+        let value = self.prg_banks[reg];
+        // $5117 always maps rom
+        let rom = reg == 3 || value & 0x80 != 0;
+        // the banks of a window are counted in windows
+        let window = size * 0x2000;
+        if rom {
+            let bank = (value & 0x7f) as usize / size;
+            (true, bank_addr(bank, window, addr, self.prg_rom.len()))
+        } else {
+            let bank = (value & 0x07) as usize / size;
+            (false, bank_addr(bank, window, addr, self.prg_ram.len()))
+        }
+    }
+    fn prg_ram_writable(&self) -> bool {
+        self.prg_ram_protect == [0b10, 0b01]
+    }
+    fn fetching_sprites(&self) -> bool {
+        self.in_frame
+            && self.nametable_fetches > BG_FETCHES
+            && self.nametable_fetches <= SPRITE_FETCHES_END
+    }
+    fn chr_addr(&self, addr: u16) -> usize {
+        let use_b = if self.sprites_8x16 && self.in_frame {
+            !self.fetching_sprites()
+        } else {
+            self.sprites_8x16 && self.last_chr_write_b
+        };
+        let slot_size = 0x2000 >> self.chr_mode;
+        // each slot uses the register of its last 1KB
+        let (bank, addr) = if use_b {
+            // the background set covers 4KB and repeats in both halves
+            let addr = (if self.chr_mode == 0 { addr } else { addr & 0xfff }) as usize;
+            let slot = addr / slot_size;
+            (self.chr_banks_b[(((slot + 1) << (3 - self.chr_mode)) - 1) & 3], addr)
+        } else {
+            let slot = addr as usize / slot_size;
+            (self.chr_banks_a[((slot + 1) << (3 - self.chr_mode)) - 1], addr as usize)
+        };
+        bank_addr(bank as usize, slot_size, addr as u16, self.chr.len())
+    }
+    // the ppu is considered to start a new scanline after reading the
+    // same nametable address three times in a row (dummy fetches at the end of a line)
+    fn observe_ppu_read(&mut self, addr: u16) {
+        self.idle_cycles = 0;
+        if addr >= 0x2000 && addr == self.last_ppu_addr {
+            self.ppu_addr_matches += 1;
+            if self.ppu_addr_matches == 2 { self.start_scanline() }
+        } else {
+            self.ppu_addr_matches = 0;
+        }
+        self.last_ppu_addr = addr;
+    }
+    fn start_scanline(&mut self) {
+        if !self.in_frame {
+            self.in_frame = true;
+            self.scanline = 0;
+            self.irq_pending = false;
+            self.split_y = self.split_scroll;
+        } else {
+            self.scanline = self.scanline.wrapping_add(1);
+            if self.scanline == self.irq_compare { self.irq_pending = true }
+            self.split_y = next_split_y(self.split_y);
+        }
+        self.nametable_fetches = 0;
+    }
+    fn leave_frame(&mut self) {
+        self.in_frame = false;
+        self.ppu_addr_matches = 0;
+        self.tile_fetch = TileFetch::Normal;
+    }
+    // called on the nametable byte fetch of every background tile
+    fn start_tile_fetch(&mut self, addr: u16) {
+        let index = self.nametable_fetches;
+        self.nametable_fetches = self.nametable_fetches.saturating_add(1);
+        self.tile_fetch = TileFetch::Normal;
+        if !self.in_frame || (BG_FETCHES..SPRITE_FETCHES_END).contains(&index) { return }
+
+        // tiles 0 and 1 are fetched at the end of the previous line
+        let (column, y) = if index < BG_FETCHES {
+            (index + 2, self.split_y)
+        } else {
+            (index - SPRITE_FETCHES_END, next_split_y(self.split_y))
+        };
+        if self.in_split(column) {
+            let tile = self.exram[(y as usize / 8) * 32 + column as usize];
+            self.tile_fetch = TileFetch::Split { tile, column, y };
+        } else if self.exram_mode == 1 {
+            self.tile_fetch = TileFetch::ExAttr(self.exram[addr as usize & 0x3ff]);
+        }
+    }
+    fn in_split(&self, column: u8) -> bool {
+        if self.split_control & 0x80 == 0 || self.exram_mode > 1 { return false }
+        let threshold = self.split_control & 0x1f;
+        if self.split_control & 0x40 == 0 { column < threshold } else { column >= threshold }
+    }
+    fn write_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x5100 => self.prg_mode = value & 3,
+            0x5101 => self.chr_mode = value & 3,
+            0x5102 => self.prg_ram_protect[0] = value & 3,
+            0x5103 => self.prg_ram_protect[1] = value & 3,
+            0x5104 => self.exram_mode = value & 3,
+            0x5105 => self.nametable_mapping = value,
+            0x5106 => self.fill_tile = value,
+            0x5107 => self.fill_attr = value & 3,
+            0x5113 => self.prg_ram_bank = value,
+            0x5114..=0x5117 => self.prg_banks[addr as usize - 0x5114] = value,
+            0x5120..=0x5127 => {
+                self.chr_banks_a[addr as usize - 0x5120] = (self.chr_upper as u16) << 8 | value as u16;
+                self.last_chr_write_b = false;
+            },
+            0x5128..=0x512b => {
+                self.chr_banks_b[addr as usize - 0x5128] = (self.chr_upper as u16) << 8 | value as u16;
+                self.last_chr_write_b = true;
+            },
+            0x5130 => self.chr_upper = value & 3,
+            0x5200 => self.split_control = value,
+            0x5201 => self.split_scroll = value,
+            0x5202 => self.split_bank = value,
+            0x5203 => self.irq_compare = value,
+            0x5204 => self.irq_enabled = value & 0x80 != 0,
+            0x5205 => self.multiplicand = value,
+            0x5206 => self.multiplier = value,
+            _ => ()
+        }
+    }
+}
+impl Mapper for Mmc5 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x5010 | 0x5015 => self.audio.read(addr),
+            0x5204 => {
+                let value = (self.irq_pending as u8) << 7 | (self.in_frame as u8) << 6;
+                self.irq_pending = false;
+                Some(value)
+            },
+            0x5205 => Some((self.multiplicand as u16 * self.multiplier as u16) as u8),
+            0x5206 => Some(((self.multiplicand as u16 * self.multiplier as u16) >> 8) as u8),
+            0x5c00..=0x5fff if self.exram_mode >= 2 => Some(self.exram[addr as usize - 0x5c00]),
+            0x6000..=0xffff => {
+                // the cpu fetching the nmi vector marks the end of the frame
+                if addr == 0xfffa || addr == 0xfffb { self.leave_frame() }
+                let (rom, offset) = self.prg_addr(addr);
+                let value = if rom {
+                    self.prg_rom[offset]
+                } else if self.prg_ram.is_empty() {
+                    return None
+                } else {
+                    self.prg_ram[offset]
+                };
+                if (0x8000..0xc000).contains(&addr) { self.audio.pcm_read(value) }
+                Some(value)
+            },
+            _ => None
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        match addr {
+            // ppuctrl and ppumask are snooped from the cpu bus
+            0x2000 => self.sprites_8x16 = value & 0x20 != 0,
+            0x2001 if value & 0x18 == 0 => self.leave_frame(),
+            0x5000..=0x5015 => self.audio.write(addr, value),
+            0x5100..=0x5206 => self.write_register(addr, value),
+            0x5c00..=0x5fff => {
+                let offset = addr as usize - 0x5c00;
+                match self.exram_mode {
+                    // while the ppu is not rendering, writes in the nametable modes store 0
+                    0 | 1 => self.exram[offset] = if self.in_frame { value } else { 0 },
+                    2 => self.exram[offset] = value,
+                    _ => ()
+                }
+            },
+            0x6000..=0xffff => {
+                let (rom, offset) = self.prg_addr(addr);
+                if !rom && self.prg_ram_writable() && !self.prg_ram.is_empty() {
+                    self.prg_ram[offset] = value;
+                }
+            },
+            _ => ()
+        }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.observe_ppu_read(addr);
+        if self.in_frame && !self.fetching_sprites() {
+            match self.tile_fetch {
+                TileFetch::Split { tile, y, .. } => {
+                    let offset = (tile as u16) << 4
+                        | (addr & 8)
+                        | (y as u16 & 7);
+                    return self.chr[bank_addr(self.split_bank as usize, 0x1000, offset, self.chr.len())]
+                },
+                TileFetch::ExAttr(ex) => {
+                    let bank = (self.chr_upper as usize) << 6 | (ex & 0x3f) as usize;
+                    return self.chr[bank_addr(bank, 0x1000, addr, self.chr.len())]
+                },
+                TileFetch::Normal => ()
+            }
+        }
+        self.chr[self.chr_addr(addr)]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let offset = self.chr_addr(addr);
+        self.chr[offset] = value;
+    }
+    fn nametable_read(&mut self, addr: u16, ciram: &[u8]) -> u8 {
+        self.observe_ppu_read(addr);
+        let offset = addr as usize & 0x3ff;
+        let attribute = offset >= 0x3c0;
+        if !attribute { self.start_tile_fetch(addr) }
+
+        match self.tile_fetch {
+            TileFetch::Split { tile, column, y } => {
+                if !attribute { return tile }
+                let attr = self.exram[0x3c0 + (y as usize / 32) * 8 + column as usize / 4];
+                let shift = ((y >> 2) & 4) | (column & 2);
+                return ((attr >> shift) & 3) * 0x55
+            },
+            TileFetch::ExAttr(ex) if attribute => return (ex >> 6) * 0x55,
+            _ => ()
+        }
+
+        let table = (addr >> 10) & 3;
+        match (self.nametable_mapping >> (table * 2)) & 3 {
+            0 => ciram[offset],
+            1 => ciram[0x400 | offset],
+            2 => if self.exram_mode <= 1 { self.exram[offset] } else { 0 },
+            _ => if attribute { self.fill_attr * 0x55 } else { self.fill_tile }
+        }
+    }
+    fn nametable_write(&mut self, addr: u16, value: u8, ciram: &mut [u8]) {
+        let offset = addr as usize & 0x3ff;
+        let table = (addr >> 10) & 3;
+        match (self.nametable_mapping >> (table * 2)) & 3 {
+            0 => ciram[offset] = value,
+            1 => ciram[0x400 | offset] = value,
+            2 if self.exram_mode <= 1 => self.exram[offset] = value,
+            _ => ()
+        }
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        Some(&self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        Some(&mut self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn mirroring(&self) -> Mirroring {
+        // only meaningful for the common setups, the real routing
+        // is done per nametable in `nametable_read`
+        match self.nametable_mapping {
+            0x44 => Mirroring::Vertical,
+            0x50 => Mirroring::Horizontal,
+            0x55 => Mirroring::SingleScreenB,
+            _ => Mirroring::SingleScreenA
+        }
+    }
+    fn clock(&mut self) {
+        self.audio.clock();
+        // no ppu reads for a few cpu cycles mean rendering has stopped
+        if self.in_frame {
+            self.idle_cycles += 1;
+            if self.idle_cycles >= 3 { self.leave_frame() }
+        }
+    }
+    fn irq(&self) -> bool {
+        (self.irq_pending && self.irq_enabled) || self.audio.irq()
+    }
+    fn audio(&self) -> Option<&dyn ExpansionAudio> {
+        Some(&self.audio)
+    }
+}
+impl Snapshot for Mmc5 {
+    fn save(&self, w: &mut StateWriter) {
+        self.prg_ram.save(w);
+        if self.chr_ram { self.chr.save(w) }
+        self.exram.save(w);
+        self.prg_mode.save(w);
+        self.chr_mode.save(w);
+        self.prg_ram_protect.save(w);
+        self.exram_mode.save(w);
+        self.nametable_mapping.save(w);
+        self.fill_tile.save(w);
+        self.fill_attr.save(w);
+        self.prg_ram_bank.save(w);
+        self.prg_banks.save(w);
+        self.chr_banks_a.save(w);
+        self.chr_banks_b.save(w);
+        self.chr_upper.save(w);
+        self.last_chr_write_b.save(w);
+        self.split_control.save(w);
+        self.split_scroll.save(w);
+        self.split_bank.save(w);
+        self.split_y.save(w);
+        self.irq_compare.save(w);
+        self.irq_enabled.save(w);
+        self.irq_pending.save(w);
+        self.multiplicand.save(w);
+        self.multiplier.save(w);
+        self.sprites_8x16.save(w);
+        self.in_frame.save(w);
+        self.scanline.save(w);
+        self.last_ppu_addr.save(w);
+        self.ppu_addr_matches.save(w);
+        self.idle_cycles.save(w);
+        self.nametable_fetches.save(w);
+        self.tile_fetch.save(w);
+        self.audio.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        Snapshot::load(&mut self.prg_ram, r)?;
+        if self.chr_ram { self.chr.load(r)? }
+        self.exram.load(r)?;
+        self.prg_mode.load(r)?;
+        self.chr_mode.load(r)?;
+        self.prg_ram_protect.load(r)?;
+        self.exram_mode.load(r)?;
+        self.nametable_mapping.load(r)?;
+        self.fill_tile.load(r)?;
+        self.fill_attr.load(r)?;
+        self.prg_ram_bank.load(r)?;
+        self.prg_banks.load(r)?;
+        self.chr_banks_a.load(r)?;
+        self.chr_banks_b.load(r)?;
+        self.chr_upper.load(r)?;
+        self.last_chr_write_b.load(r)?;
+        self.split_control.load(r)?;
+        self.split_scroll.load(r)?;
+        self.split_bank.load(r)?;
+        self.split_y.load(r)?;
+        self.irq_compare.load(r)?;
+        self.irq_enabled.load(r)?;
+        self.irq_pending.load(r)?;
+        self.multiplicand.load(r)?;
+        self.multiplier.load(r)?;
+        self.sprites_8x16.load(r)?;
+        self.in_frame.load(r)?;
+        self.scanline.load(r)?;
+        self.last_ppu_addr.load(r)?;
+        self.ppu_addr_matches.load(r)?;
+        self.idle_cycles.load(r)?;
+        self.nametable_fetches.load(r)?;
+        self.tile_fetch.load(r)?;
+        self.audio.load(r)
+    }
+}
+
+fn next_split_y(y: u8) -> u8 {
+    if y == 239 { 0 } else { y.wrapping_add(1) }
+}
\ No newline at end of file
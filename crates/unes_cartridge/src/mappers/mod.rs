@@ -0,0 +1,92 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use unes_state::Snapshot;
+
+use crate::audio::ExpansionAudio;
+use crate::cartridge::CartridgeError;
+use crate::header::Header;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+
+mod bandai;
+mod cprom;
+mod eeprom;
+mod fme7;
+mod four_screen;
+mod mmc5;
+mod n163;
+mod nrom;
+mod uxrom;
+mod vrc4;
+mod vrc6;
+mod vrc7;
+mod vrc_irq;
+
+pub use bandai::Bandai;
+pub use cprom::Cprom;
+pub use fme7::Fme7;
+pub use four_screen::FourScreen;
+pub use mmc5::Mmc5;
+pub use n163::N163;
+pub use nrom::Nrom;
+pub use uxrom::Uxrom;
+pub use vrc4::Vrc4;
+pub use vrc6::Vrc6;
+pub use vrc7::Vrc7;
+
+// `Snapshot` covers the registers and ram, not the roms or what the header configures
+pub trait Mapper: Snapshot {
+    // cpu reads in the $4020-$ffff range,
+    // None when the board does not drive the data bus (open bus)
+    fn cpu_read(&mut self, addr: u16) -> Option<u8>;
+    // the data lines a `cpu_read` of `addr` drives, the others keep the open bus value
+    fn driven_bits(&self, _addr: u16) -> u8 { 0xff }
+    // every cpu write is forwarded here, so boards can snoop
+    // on the ppu registers as well
+    fn cpu_write(&mut self, addr: u16, value: u8);
+    // ppu pattern table access ($0000-$1fff)
+    fn ppu_read(&mut self, addr: u16) -> u8;
+    fn ppu_write(&mut self, addr: u16, value: u8);
+    // ppu nametable access ($2000-$2fff),
+    // `ciram` is the 2KB of nametable ram inside the console
+    fn nametable_read(&mut self, addr: u16, ciram: &[u8]) -> u8 {
+        ciram[self.mirroring().ciram_addr(addr)]
+    }
+    fn nametable_write(&mut self, addr: u16, value: u8, ciram: &mut [u8]) {
+        ciram[self.mirroring().ciram_addr(addr)] = value;
+    }
+    // battery backed memory, None when the board has nothing to persist
+    fn save_ram(&self) -> Option<&SaveRam> { None }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> { None }
+    fn mirroring(&self) -> Mirroring;
+    // advances the board by a single cpu cycle
+    fn clock(&mut self) {}
+    // state of the cartridge irq line
+    fn irq(&self) -> bool { false }
+    fn audio(&self) -> Option<&dyn ExpansionAudio> { None }
+}
+
+pub fn create(
+    header: &Header,
+    prg_rom: Vec<u8>,
+    chr_rom: Vec<u8>
+) -> Result<Box<dyn Mapper>, CartridgeError> {
+    let mapper: Box<dyn Mapper> = match header.mapper {
+        0 => Box::new(Nrom::new(header, prg_rom, chr_rom)),
+        2 => Box::new(Uxrom::new(header, prg_rom, chr_rom)),
+        5 => Box::new(Mmc5::new(header, prg_rom, chr_rom)),
+        13 => Box::new(Cprom::new(header, prg_rom, chr_rom)),
+        16 | 159 => Box::new(Bandai::new(header, prg_rom, chr_rom)),
+        19 => Box::new(N163::new(header, prg_rom, chr_rom)),
+        21 | 22 | 23 | 25 => Box::new(Vrc4::new(header, prg_rom, chr_rom)),
+        24 | 26 => Box::new(Vrc6::new(header, prg_rom, chr_rom)),
+        69 => Box::new(Fme7::new(header, prg_rom, chr_rom)),
+        85 => Box::new(Vrc7::new(header, prg_rom, chr_rom)),
+        id => return Err(CartridgeError::UnsupportedMapper(id))
+    };
+    if header.mirroring == Mirroring::FourScreen {
+        return Ok(Box::new(FourScreen::new(mapper)))
+    }
+    Ok(mapper)
+}
\ No newline at end of file
@@ -0,0 +1,191 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::ExpansionAudio;
+use crate::audio::n163::N163Audio;
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+use crate::utils::{bank_addr, chr_memory};
+
+// Namco 163, mapper 19
+pub struct N163 {
+    prg_rom: Vec<u8>,
+    prg_ram: SaveRam,
+    chr: Vec<u8>,
+    chr_ram: bool,
+
+    prg_banks: [u8; 3],
+    chr_banks: [u8; 8],
+    // $c000-$d800, values $e0 and up select ciram
+    nametable_banks: [u8; 4],
+    // $f800, also gates prg ram writes
+    write_protect: u8,
+    irq_counter: u16,
+    irq_enabled: bool,
+    irq_pending: bool,
+    audio: N163Audio
+}
+impl N163 {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> N163 {
+        let chr_ram = chr_rom.is_empty();
+        N163 {
+            prg_rom,
+            prg_ram: SaveRam::prg(header),
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            prg_banks: [0; 3],
+            chr_banks: [0; 8],
+            nametable_banks: [0xe0, 0xe1, 0xe0, 0xe1],
+            write_protect: 0,
+            irq_counter: 0,
+            irq_enabled: false,
+            irq_pending: false,
+            audio: N163Audio::default()
+        }
+    }
+    fn prg_addr(&self, addr: u16) -> usize {
+        let slot = (addr as usize - 0x8000) >> 13;
+        let bank = match slot {
+            3 => (self.prg_rom.len() / 0x2000).saturating_sub(1),
+            _ => self.prg_banks[slot] as usize
+        };
+        bank_addr(bank, 0x2000, addr, self.prg_rom.len())
+    }
+    fn prg_ram_writable(&self, addr: u16) -> bool {
+        let window = (addr - 0x6000) >> 11;
+        self.write_protect & 0xf0 == 0x40 && self.write_protect & (1 << window) == 0
+    }
+    fn chr_addr(&self, addr: u16) -> usize {
+        // pattern tables mapped to ciram (banks $e0 and up, unless disabled
+        // through $e800 bits 6 and 7) are not supported, those read from chr rom
+        let bank = self.chr_banks[addr as usize >> 10];
+        bank_addr(bank as usize, 0x400, addr, self.chr.len())
+    }
+}
+impl Mapper for N163 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x4800..=0x4fff => Some(self.audio.read_data()),
+            0x5000..=0x57ff => Some(self.irq_counter as u8),
+            0x5800..=0x5fff => Some((self.irq_counter >> 8) as u8 | (self.irq_enabled as u8) << 7),
+            0x6000..=0x7fff if !self.prg_ram.is_empty() => {
+                Some(self.prg_ram[(addr as usize - 0x6000) % self.prg_ram.len()])
+            },
+            0x8000..=0xffff => Some(self.prg_rom[self.prg_addr(addr)]),
+            _ => None
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x4800..=0x4fff => self.audio.write_data(value),
+            0x5000..=0x57ff => {
+                self.irq_counter = self.irq_counter & 0x7f00 | value as u16;
+                self.irq_pending = false;
+            },
+            0x5800..=0x5fff => {
+                self.irq_counter = self.irq_counter & 0xff | ((value & 0x7f) as u16) << 8;
+                self.irq_enabled = value & 0x80 != 0;
+                self.irq_pending = false;
+            },
+            0x6000..=0x7fff if !self.prg_ram.is_empty() && self.prg_ram_writable(addr) => {
+                let len = self.prg_ram.len();
+                self.prg_ram[(addr as usize - 0x6000) % len] = value;
+            },
+            0x8000..=0xbfff => self.chr_banks[(addr as usize - 0x8000) >> 11] = value,
+            0xc000..=0xdfff => self.nametable_banks[(addr as usize - 0xc000) >> 11] = value,
+            0xe000..=0xe7ff => {
+                self.prg_banks[0] = value & 0x3f;
+                self.audio.set_disabled(value & 0x40 != 0);
+            },
+            0xe800..=0xefff => self.prg_banks[1] = value & 0x3f,
+            0xf000..=0xf7ff => self.prg_banks[2] = value & 0x3f,
+            0xf800..=0xffff => {
+                self.write_protect = value;
+                self.audio.write_address(value);
+            },
+            _ => ()
+        }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[self.chr_addr(addr)]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let offset = self.chr_addr(addr);
+        self.chr[offset] = value;
+    }
+    fn nametable_read(&mut self, addr: u16, ciram: &[u8]) -> u8 {
+        let bank = self.nametable_banks[(addr as usize >> 10) & 3];
+        if bank >= 0xe0 {
+            ciram[(bank as usize & 1) << 10 | addr as usize & 0x3ff]
+        } else {
+            self.chr[bank_addr(bank as usize, 0x400, addr, self.chr.len())]
+        }
+    }
+    fn nametable_write(&mut self, addr: u16, value: u8, ciram: &mut [u8]) {
+        let bank = self.nametable_banks[(addr as usize >> 10) & 3];
+        if bank >= 0xe0 {
+            ciram[(bank as usize & 1) << 10 | addr as usize & 0x3ff] = value;
+        } else if self.chr_ram {
+            let offset = bank_addr(bank as usize, 0x400, addr, self.chr.len());
+            self.chr[offset] = value;
+        }
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        Some(&self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        Some(&mut self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn mirroring(&self) -> Mirroring {
+        // closest fixed layout, nametable accesses go through the bank registers
+        match self.nametable_banks.map(|bank| bank & 1) {
+            [0, 0, 0, 0] => Mirroring::SingleScreenA,
+            [1, 1, 1, 1] => Mirroring::SingleScreenB,
+            [0, 0, _, _] => Mirroring::Horizontal,
+            _ => Mirroring::Vertical
+        }
+    }
+    fn clock(&mut self) {
+        if self.irq_enabled && self.irq_counter < 0x7fff {
+            self.irq_counter += 1;
+            if self.irq_counter == 0x7fff { self.irq_pending = true }
+        }
+        self.audio.clock();
+    }
+    fn irq(&self) -> bool {
+        self.irq_pending
+    }
+    fn audio(&self) -> Option<&dyn ExpansionAudio> {
+        Some(&self.audio)
+    }
+}
+impl Snapshot for N163 {
+    fn save(&self, w: &mut StateWriter) {
+        self.prg_ram.save(w);
+        if self.chr_ram { self.chr.save(w) }
+        self.prg_banks.save(w);
+        self.chr_banks.save(w);
+        self.nametable_banks.save(w);
+        self.write_protect.save(w);
+        self.irq_counter.save(w);
+        self.irq_enabled.save(w);
+        self.irq_pending.save(w);
+        self.audio.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        Snapshot::load(&mut self.prg_ram, r)?;
+        if self.chr_ram { self.chr.load(r)? }
+        self.prg_banks.load(r)?;
+        self.chr_banks.load(r)?;
+        self.nametable_banks.load(r)?;
+        self.write_protect.load(r)?;
+        self.irq_counter.load(r)?;
+        self.irq_enabled.load(r)?;
+        self.irq_pending.load(r)?;
+        self.audio.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,77 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+use crate::utils::chr_memory;
+
+// mapper 0, no bank switching
+pub struct Nrom {
+    prg_rom: Vec<u8>,
+    prg_ram: SaveRam,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    mirroring: Mirroring
+}
+impl Nrom {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Nrom {
+        let chr_ram = chr_rom.is_empty();
+        Nrom {
+            prg_rom,
+            prg_ram: SaveRam::prg(header),
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            mirroring: header.mirroring
+        }
+    }
+}
+impl Mapper for Nrom {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7fff if !self.prg_ram.is_empty() => {
+                Some(self.prg_ram[(addr as usize - 0x6000) % self.prg_ram.len()])
+            },
+            // 16KB boards mirror the rom at $c000
+            0x8000..=0xffff => Some(self.prg_rom[(addr as usize - 0x8000) % self.prg_rom.len()]),
+            _ => None
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        if let 0x6000..=0x7fff = addr {
+            if self.prg_ram.is_empty() { return }
+            let len = self.prg_ram.len();
+            self.prg_ram[(addr as usize - 0x6000) % len] = value;
+        }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[addr as usize % self.chr.len()]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let len = self.chr.len();
+        self.chr[addr as usize % len] = value;
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        Some(&self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        Some(&mut self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
+impl Snapshot for Nrom {
+    fn save(&self, w: &mut StateWriter) {
+        self.prg_ram.save(w);
+        if self.chr_ram { self.chr.save(w) }
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        Snapshot::load(&mut self.prg_ram, r)?;
+        if self.chr_ram { self.chr.load(r)? }
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mirroring::Mirroring;
+use crate::utils::{bank_addr, chr_memory};
+
+// UxROM, mapper 2
+// A switchable 16KB bank at $8000 and the last one fixed at $c000, the
+// pattern tables are usually chr ram. Submapper 2 has bus conflicts: the
+// rom drives the data bus during the write, so the value gets ANDed with it.
+pub struct Uxrom {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    mirroring: Mirroring,
+    bus_conflicts: bool,
+    prg_bank: u8
+}
+impl Uxrom {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Uxrom {
+        let chr_ram = chr_rom.is_empty();
+        Uxrom {
+            prg_rom,
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            mirroring: header.mirroring,
+            bus_conflicts: header.submapper == 2,
+            prg_bank: 0
+        }
+    }
+}
+impl Mapper for Uxrom {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        let bank = match addr {
+            0x8000..=0xbfff => self.prg_bank as usize,
+            0xc000..=0xffff => (self.prg_rom.len() / 0x4000).saturating_sub(1),
+            _ => return None
+        };
+        Some(self.prg_rom[bank_addr(bank, 0x4000, addr, self.prg_rom.len())])
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        if addr < 0x8000 { return }
+        let rom = if self.bus_conflicts { self.cpu_read(addr).unwrap_or(0xff) } else { 0xff };
+        self.prg_bank = value & rom;
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[addr as usize % self.chr.len()]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let len = self.chr.len();
+        self.chr[addr as usize % len] = value;
+    }
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
+impl Snapshot for Uxrom {
+    fn save(&self, w: &mut StateWriter) {
+        if self.chr_ram { self.chr.save(w) }
+        self.prg_bank.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        if self.chr_ram { self.chr.load(r)? }
+        self.prg_bank.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,189 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mappers::vrc_irq::VrcIrq;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+use crate::utils::{bank_addr, chr_memory};
+
+// Konami VRC2 and VRC4 (mappers 21, 22, 23 and 25)
+// The boards differ in which cpu address lines select the register
+// within each $x000 group. The submapper picks the exact wiring,
+// without one both candidate lines of the mapper number are decoded.
+pub struct Vrc4 {
+    prg_rom: Vec<u8>,
+    prg_ram: SaveRam,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    vrc2: bool,
+    // address bits feeding register select lines 0 and 1
+    select_masks: (u16, u16),
+    // VRC2a drops the lowest bit of the chr banks
+    chr_shift: u8,
+
+    prg_banks: [u8; 2],
+    prg_swap: bool,
+    chr_banks: [u16; 8],
+    mirroring: Mirroring,
+    // VRC2 boards without ram have a single bit latch at $6000
+    latch: u8,
+    irq: VrcIrq
+}
+impl Vrc4 {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Vrc4 {
+        let (vrc2, select_masks) = match (header.mapper, header.submapper) {
+            (21, 1) => (false, (0x002, 0x004)),
+            (21, 2) => (false, (0x040, 0x080)),
+            (21, _) => (false, (0x042, 0x084)),
+            (22, _) => (true, (0x002, 0x001)),
+            (23, 1) => (false, (0x001, 0x002)),
+            (23, 2) => (false, (0x004, 0x008)),
+            (23, 3) => (true, (0x001, 0x002)),
+            (23, _) => (false, (0x005, 0x00a)),
+            (25, 1) => (false, (0x002, 0x001)),
+            (25, 2) => (false, (0x008, 0x004)),
+            (25, 3) => (true, (0x002, 0x001)),
+            _ => (false, (0x00a, 0x005))
+        };
+        let chr_ram = chr_rom.is_empty();
+        Vrc4 {
+            prg_rom,
+            prg_ram: SaveRam::prg(header),
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            vrc2,
+            select_masks,
+            chr_shift: if header.mapper == 22 { 1 } else { 0 },
+            prg_banks: [0; 2],
+            prg_swap: false,
+            chr_banks: [0; 8],
+            mirroring: Mirroring::Vertical,
+            latch: 0,
+            irq: VrcIrq::default()
+        }
+    }
+    // translates the board specific wiring into $x000-$x003
+    fn register(&self, addr: u16) -> u16 {
+        let low = (addr & self.select_masks.0 != 0) as u16;
+        let high = (addr & self.select_masks.1 != 0) as u16;
+        addr & 0xf000 | high << 1 | low
+    }
+    fn prg_addr(&self, addr: u16) -> usize {
+        // under 16KB the fixed banks fall back to the first one
+        let last = (self.prg_rom.len() / 0x2000).saturating_sub(1);
+        let bank = match (addr, self.prg_swap) {
+            (0x8000..=0x9fff, false) | (0xc000..=0xdfff, true) => self.prg_banks[0] as usize,
+            (0x8000..=0x9fff, true) | (0xc000..=0xdfff, false) => last.saturating_sub(1),
+            (0xa000..=0xbfff, _) => self.prg_banks[1] as usize,
+            _ => last
+        };
+        bank_addr(bank, 0x2000, addr, self.prg_rom.len())
+    }
+    fn chr_addr(&self, addr: u16) -> usize {
+        let bank = self.chr_banks[addr as usize >> 10] >> self.chr_shift;
+        bank_addr(bank as usize, 0x400, addr, self.chr.len())
+    }
+    fn write_register(&mut self, reg: u16, value: u8) {
+        match reg {
+            0x8000..=0x8003 => self.prg_banks[0] = value & 0x1f,
+            0x9000 | 0x9001 => {
+                // the VRC2 only switches between vertical and horizontal
+                let mask = if self.vrc2 { 1 } else { 3 };
+                self.mirroring = match value & mask {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenA,
+                    _ => Mirroring::SingleScreenB
+                };
+            },
+            0x9002 | 0x9003 if !self.vrc2 => self.prg_swap = value & 2 != 0,
+            0xa000..=0xa003 => self.prg_banks[1] = value & 0x1f,
+            0xb000..=0xefff => {
+                // two registers per 1KB bank, low and high nibble
+                let index = (((reg >> 12) - 0xb) * 2 + ((reg >> 1) & 1)) as usize;
+                let bank = self.chr_banks[index];
+                self.chr_banks[index] = if reg & 1 == 0 {
+                    bank & 0x1f0 | (value & 0x0f) as u16
+                } else {
+                    bank & 0x0f | ((value & 0x1f) as u16) << 4
+                };
+            },
+            0xf000 if !self.vrc2 => self.irq.write_latch_low(value),
+            0xf001 if !self.vrc2 => self.irq.write_latch_high(value),
+            0xf002 if !self.vrc2 => self.irq.write_control(value),
+            0xf003 if !self.vrc2 => self.irq.acknowledge(),
+            _ => ()
+        }
+    }
+}
+impl Mapper for Vrc4 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7fff if !self.prg_ram.is_empty() => {
+                Some(self.prg_ram[(addr as usize - 0x6000) % self.prg_ram.len()])
+            },
+            0x6000..=0x6fff if self.vrc2 => Some(self.latch),
+            0x8000..=0xffff => Some(self.prg_rom[self.prg_addr(addr)]),
+            _ => None
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x6000..=0x7fff if !self.prg_ram.is_empty() => {
+                let len = self.prg_ram.len();
+                self.prg_ram[(addr as usize - 0x6000) % len] = value;
+            },
+            0x6000..=0x6fff if self.vrc2 => self.latch = value & 1,
+            0x8000..=0xffff => self.write_register(self.register(addr), value),
+            _ => ()
+        }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[self.chr_addr(addr)]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let offset = self.chr_addr(addr);
+        self.chr[offset] = value;
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        Some(&self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        Some(&mut self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+    fn clock(&mut self) {
+        self.irq.clock();
+    }
+    fn irq(&self) -> bool {
+        self.irq.pending
+    }
+}
+impl Snapshot for Vrc4 {
+    fn save(&self, w: &mut StateWriter) {
+        self.prg_ram.save(w);
+        if self.chr_ram { self.chr.save(w) }
+        self.prg_banks.save(w);
+        self.prg_swap.save(w);
+        self.chr_banks.save(w);
+        self.mirroring.save(w);
+        self.latch.save(w);
+        self.irq.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        Snapshot::load(&mut self.prg_ram, r)?;
+        if self.chr_ram { self.chr.load(r)? }
+        self.prg_banks.load(r)?;
+        self.prg_swap.load(r)?;
+        self.chr_banks.load(r)?;
+        self.mirroring.load(r)?;
+        self.latch.load(r)?;
+        self.irq.load(r)
+    }
+}
\ No newline at end of file
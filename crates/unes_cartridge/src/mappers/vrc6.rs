@@ -0,0 +1,174 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::ExpansionAudio;
+use crate::audio::vrc6::Vrc6Audio;
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mappers::vrc_irq::VrcIrq;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+use crate::utils::{bank_addr, chr_memory};
+
+// Konami VRC6, mapper 24 (VRC6a) and 26 (VRC6b, with A0 and A1 swapped)
+pub struct Vrc6 {
+    prg_rom: Vec<u8>,
+    prg_ram: SaveRam,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    swapped_lines: bool,
+
+    prg_16k_bank: u8,
+    prg_8k_bank: u8,
+    chr_banks: [u8; 8],
+    // $b003
+    ppu_mode: u8,
+    irq: VrcIrq,
+    audio: Vrc6Audio
+}
+impl Vrc6 {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Vrc6 {
+        let chr_ram = chr_rom.is_empty();
+        Vrc6 {
+            prg_rom,
+            prg_ram: SaveRam::prg(header),
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            swapped_lines: header.mapper == 26,
+            prg_16k_bank: 0,
+            prg_8k_bank: 0,
+            chr_banks: [0; 8],
+            ppu_mode: 0,
+            irq: VrcIrq::default(),
+            audio: Vrc6Audio::default()
+        }
+    }
+    fn register(&self, addr: u16) -> u16 {
+        if self.swapped_lines {
+            addr & 0xf000 | (addr & 1) << 1 | (addr >> 1) & 1
+        } else {
+            addr & 0xf003
+        }
+    }
+    fn prg_addr(&self, addr: u16) -> usize {
+        match addr {
+            0x8000..=0xbfff => bank_addr(self.prg_16k_bank as usize, 0x4000, addr, self.prg_rom.len()),
+            0xc000..=0xdfff => bank_addr(self.prg_8k_bank as usize, 0x2000, addr, self.prg_rom.len()),
+            _ => bank_addr((self.prg_rom.len() / 0x2000).saturating_sub(1), 0x2000, addr, self.prg_rom.len())
+        }
+    }
+    fn prg_ram_enabled(&self) -> bool {
+        self.ppu_mode & 0x80 != 0 && !self.prg_ram.is_empty()
+    }
+    fn chr_addr(&self, addr: u16) -> usize {
+        let slot = addr as usize >> 10;
+        // 2KB banks use the register value for the first 1KB, and either
+        // the next bank or the same one (depending on bit 5) for the second
+        let two_kb = |reg: u8| {
+            if self.ppu_mode & 0x20 != 0 {
+                (reg & 0xfe) | (slot & 1) as u8
+            } else {
+                reg
+            }
+        };
+        let bank = match self.ppu_mode & 3 {
+            0 => self.chr_banks[slot],
+            1 => two_kb(self.chr_banks[slot / 2]),
+            _ if slot < 4 => self.chr_banks[slot],
+            _ => two_kb(self.chr_banks[4 + (slot - 4) / 2])
+        };
+        bank_addr(bank as usize, 0x400, addr, self.chr.len())
+    }
+}
+impl Mapper for Vrc6 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7fff if self.prg_ram_enabled() => {
+                Some(self.prg_ram[(addr as usize - 0x6000) % self.prg_ram.len()])
+            },
+            0x8000..=0xffff => Some(self.prg_rom[self.prg_addr(addr)]),
+            _ => None
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        if addr < 0x6000 { return }
+        if addr < 0x8000 {
+            if self.prg_ram_enabled() {
+                let len = self.prg_ram.len();
+                self.prg_ram[(addr as usize - 0x6000) % len] = value;
+            }
+            return
+        }
+        match self.register(addr) {
+            0x8000..=0x8003 => self.prg_16k_bank = value & 0x0f,
+            reg @ 0x9000..=0xb002 => self.audio.write(reg, value),
+            0xb003 => self.ppu_mode = value,
+            0xc000..=0xc003 => self.prg_8k_bank = value & 0x1f,
+            reg @ 0xd000..=0xe003 => {
+                let index = ((reg >> 12) - 0xd) * 4 + (reg & 3);
+                self.chr_banks[index as usize] = value;
+            },
+            0xf000 => self.irq.write_latch(value),
+            0xf001 => self.irq.write_control(value),
+            0xf002 => self.irq.acknowledge(),
+            _ => ()
+        }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[self.chr_addr(addr)]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let offset = self.chr_addr(addr);
+        self.chr[offset] = value;
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        Some(&self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        Some(&mut self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn mirroring(&self) -> Mirroring {
+        // nametables sourced from chr rom (bit 4) are not supported,
+        // those setups fall back to ciram with the same layout
+        match (self.ppu_mode >> 2) & 3 {
+            0 => Mirroring::Vertical,
+            1 => Mirroring::Horizontal,
+            2 => Mirroring::SingleScreenA,
+            _ => Mirroring::SingleScreenB
+        }
+    }
+    fn clock(&mut self) {
+        self.irq.clock();
+        self.audio.clock();
+    }
+    fn irq(&self) -> bool {
+        self.irq.pending
+    }
+    fn audio(&self) -> Option<&dyn ExpansionAudio> {
+        Some(&self.audio)
+    }
+}
+impl Snapshot for Vrc6 {
+    fn save(&self, w: &mut StateWriter) {
+        self.prg_ram.save(w);
+        if self.chr_ram { self.chr.save(w) }
+        self.prg_16k_bank.save(w);
+        self.prg_8k_bank.save(w);
+        self.chr_banks.save(w);
+        self.ppu_mode.save(w);
+        self.irq.save(w);
+        self.audio.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        Snapshot::load(&mut self.prg_ram, r)?;
+        if self.chr_ram { self.chr.load(r)? }
+        self.prg_16k_bank.load(r)?;
+        self.prg_8k_bank.load(r)?;
+        self.chr_banks.load(r)?;
+        self.ppu_mode.load(r)?;
+        self.irq.load(r)?;
+        self.audio.load(r)
+    }
+}
\ No newline at end of file
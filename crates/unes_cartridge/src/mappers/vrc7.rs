@@ -0,0 +1,165 @@
+use alloc::vec::Vec;
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::audio::ExpansionAudio;
+use crate::audio::vrc7::Vrc7Audio;
+use crate::header::Header;
+use crate::mappers::Mapper;
+use crate::mappers::vrc_irq::VrcIrq;
+use crate::mirroring::Mirroring;
+use crate::save_ram::SaveRam;
+use crate::utils::{bank_addr, chr_memory};
+
+// Konami VRC7, mapper 85
+// The second register of each $x000 group sits on A4 for VRC7a (submapper 2)
+// and on A3 for VRC7b (submapper 1), without a submapper both are decoded.
+// The sound registers are always at $9010 and $9030.
+pub struct Vrc7 {
+    prg_rom: Vec<u8>,
+    prg_ram: SaveRam,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    select_mask: u16,
+
+    prg_banks: [u8; 3],
+    chr_banks: [u8; 8],
+    // $e000
+    control: u8,
+    irq: VrcIrq,
+    audio: Vrc7Audio
+}
+impl Vrc7 {
+    pub fn new(header: &Header, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Vrc7 {
+        let chr_ram = chr_rom.is_empty();
+        Vrc7 {
+            prg_rom,
+            prg_ram: SaveRam::prg(header),
+            chr: chr_memory(header, chr_rom),
+            chr_ram,
+            select_mask: match header.submapper {
+                1 => 0x08,
+                2 => 0x10,
+                _ => 0x18
+            },
+            prg_banks: [0; 3],
+            chr_banks: [0; 8],
+            control: 0,
+            irq: VrcIrq::default(),
+            audio: Vrc7Audio::default()
+        }
+    }
+    fn register(&self, addr: u16) -> u16 {
+        addr & 0xf000 | if addr & self.select_mask != 0 { 0x10 } else { 0 }
+    }
+    fn prg_addr(&self, addr: u16) -> usize {
+        let slot = (addr as usize - 0x8000) >> 13;
+        let bank = match slot {
+            3 => (self.prg_rom.len() / 0x2000).saturating_sub(1),
+            _ => self.prg_banks[slot] as usize
+        };
+        bank_addr(bank, 0x2000, addr, self.prg_rom.len())
+    }
+    fn prg_ram_enabled(&self) -> bool {
+        self.control & 0x80 != 0 && !self.prg_ram.is_empty()
+    }
+    fn chr_addr(&self, addr: u16) -> usize {
+        let bank = self.chr_banks[addr as usize >> 10];
+        bank_addr(bank as usize, 0x400, addr, self.chr.len())
+    }
+}
+impl Mapper for Vrc7 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7fff if self.prg_ram_enabled() => {
+                Some(self.prg_ram[(addr as usize - 0x6000) % self.prg_ram.len()])
+            },
+            0x8000..=0xffff => Some(self.prg_rom[self.prg_addr(addr)]),
+            _ => None
+        }
+    }
+    fn cpu_write(&mut self, addr: u16, value: u8) {
+        if addr < 0x6000 { return }
+        if addr < 0x8000 {
+            if self.prg_ram_enabled() {
+                let len = self.prg_ram.len();
+                self.prg_ram[(addr as usize - 0x6000) % len] = value;
+            }
+            return
+        }
+        match addr & 0xf030 {
+            0x9010 => return self.audio.write_address(value),
+            0x9030 => return self.audio.write_data(value),
+            _ => ()
+        }
+        match self.register(addr) {
+            0x8000 => self.prg_banks[0] = value & 0x3f,
+            0x8010 => self.prg_banks[1] = value & 0x3f,
+            0x9000 => self.prg_banks[2] = value & 0x3f,
+            reg @ 0xa000..=0xd010 => {
+                let index = ((reg >> 12) - 0xa) * 2 + ((reg >> 4) & 1);
+                self.chr_banks[index as usize] = value;
+            },
+            0xe000 => {
+                self.control = value;
+                self.audio.set_silenced(value & 0x40 != 0);
+            },
+            0xe010 => self.irq.write_latch(value),
+            0xf000 => self.irq.write_control(value),
+            0xf010 => self.irq.acknowledge(),
+            _ => ()
+        }
+    }
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[self.chr_addr(addr)]
+    }
+    fn ppu_write(&mut self, addr: u16, value: u8) {
+        if !self.chr_ram { return }
+        let offset = self.chr_addr(addr);
+        self.chr[offset] = value;
+    }
+    fn save_ram(&self) -> Option<&SaveRam> {
+        Some(&self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn save_ram_mut(&mut self) -> Option<&mut SaveRam> {
+        Some(&mut self.prg_ram).filter(|ram| ram.battery)
+    }
+    fn mirroring(&self) -> Mirroring {
+        match self.control & 3 {
+            0 => Mirroring::Vertical,
+            1 => Mirroring::Horizontal,
+            2 => Mirroring::SingleScreenA,
+            _ => Mirroring::SingleScreenB
+        }
+    }
+    fn clock(&mut self) {
+        self.irq.clock();
+        self.audio.clock();
+    }
+    fn irq(&self) -> bool {
+        self.irq.pending
+    }
+    fn audio(&self) -> Option<&dyn ExpansionAudio> {
+        Some(&self.audio)
+    }
+}
+impl Snapshot for Vrc7 {
+    fn save(&self, w: &mut StateWriter) {
+        self.prg_ram.save(w);
+        if self.chr_ram { self.chr.save(w) }
+        self.prg_banks.save(w);
+        self.chr_banks.save(w);
+        self.control.save(w);
+        self.irq.save(w);
+        self.audio.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        Snapshot::load(&mut self.prg_ram, r)?;
+        if self.chr_ram { self.chr.load(r)? }
+        self.prg_banks.load(r)?;
+        self.chr_banks.load(r)?;
+        self.control.load(r)?;
+        self.irq.load(r)?;
+        self.audio.load(r)
+    }
+}
\ No newline at end of file
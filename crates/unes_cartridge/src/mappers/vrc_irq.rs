@@ -0,0 +1,80 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// irq counter shared by the Konami VRC4, VRC6 and VRC7
+// in scanline mode a prescaler approximates a scanline as 341/3 cpu cycles
+#[derive(Default)]
+pub struct VrcIrq {
+    latch: u8,
+    counter: u8,
+    prescaler: i16,
+    enabled: bool,
+    enabled_after_ack: bool,
+    cycle_mode: bool,
+    pub pending: bool
+}
+impl VrcIrq {
+    pub fn write_latch(&mut self, value: u8) {
+        self.latch = value;
+    }
+    // the VRC4 exposes the latch as two nibbles
+    pub fn write_latch_low(&mut self, value: u8) {
+        self.latch = self.latch & 0xf0 | value & 0x0f;
+    }
+    pub fn write_latch_high(&mut self, value: u8) {
+        self.latch = self.latch & 0x0f | value << 4;
+    }
+    pub fn write_control(&mut self, value: u8) {
+        self.enabled_after_ack = value & 1 != 0;
+        self.enabled = value & 2 != 0;
+        self.cycle_mode = value & 4 != 0;
+        if self.enabled {
+            self.counter = self.latch;
+            self.prescaler = 341;
+        }
+        self.pending = false;
+    }
+    pub fn acknowledge(&mut self) {
+        self.pending = false;
+        self.enabled = self.enabled_after_ack;
+    }
+    pub fn clock(&mut self) {
+        if !self.enabled { return }
+        if self.cycle_mode {
+            self.clock_counter();
+            return
+        }
+        self.prescaler -= 3;
+        if self.prescaler <= 0 {
+            self.prescaler += 341;
+            self.clock_counter();
+        }
+    }
+    fn clock_counter(&mut self) {
+        if self.counter == 0xff {
+            self.counter = self.latch;
+            self.pending = true;
+        } else {
+            self.counter += 1;
+        }
+    }
+}
+impl Snapshot for VrcIrq {
+    fn save(&self, w: &mut StateWriter) {
+        self.latch.save(w);
+        self.counter.save(w);
+        self.prescaler.save(w);
+        self.enabled.save(w);
+        self.enabled_after_ack.save(w);
+        self.cycle_mode.save(w);
+        self.pending.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.latch.load(r)?;
+        self.counter.load(r)?;
+        self.prescaler.load(r)?;
+        self.enabled.load(r)?;
+        self.enabled_after_ack.load(r)?;
+        self.cycle_mode.load(r)?;
+        self.pending.load(r)
+    }
+}
\ No newline at end of file
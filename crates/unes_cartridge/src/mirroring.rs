@@ -0,0 +1,43 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+    SingleScreenA,
+    SingleScreenB,
+    // the cartridge provides the ram for the lower two nametables
+    FourScreen
+}
+impl Mirroring {
+    // ciram page (0 or 1) backing one of the four logical nametables
+    pub fn ciram_page(&self, table: u16) -> u16 {
+        match self {
+            Self::Horizontal => (table >> 1) & 1,
+            Self::Vertical | Self::FourScreen => table & 1,
+            Self::SingleScreenA => 0,
+            Self::SingleScreenB => 1
+        }
+    }
+    // offset into the 2KB ciram for a $2000-$2fff ppu address
+    pub fn ciram_addr(&self, addr: u16) -> usize {
+        let table = (addr >> 10) & 3;
+        (self.ciram_page(table) as usize) << 10 | (addr & 0x3ff) as usize
+    }
+}
+impl Snapshot for Mirroring {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u8(*self as u8);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = match r.read_u8()? {
+            0 => Mirroring::Horizontal,
+            1 => Mirroring::Vertical,
+            2 => Mirroring::SingleScreenA,
+            3 => Mirroring::SingleScreenB,
+            4 => Mirroring::FourScreen,
+            _ => return Err(StateError::InvalidData)
+        };
+        Ok(())
+    }
+}
\ No newline at end of file
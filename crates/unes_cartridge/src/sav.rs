@@ -0,0 +1,34 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cartridge::Cartridge;
+
+// `<rom>.sav`, next to the rom file
+pub fn sav_path(rom: &Path) -> PathBuf {
+    rom.with_extension("sav")
+}
+
+impl Cartridge {
+    // loads a save file into the battery backed memory,
+    // returns false when there is no battery or no file yet
+    pub fn load_sav(&mut self, path: &Path) -> io::Result<bool> {
+        if self.save_data().is_none() { return Ok(false) }
+        match fs::read(path) {
+            Ok(data) => {
+                self.load_save_data(&data);
+                Ok(true)
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e)
+        }
+    }
+    // writes the save file if the memory changed since the last load or flush,
+    // returns whether anything was written
+    pub fn flush_sav(&mut self, path: &Path) -> io::Result<bool> {
+        if !self.save_dirty() { return Ok(false) }
+        if let Some(data) = self.save_data() { fs::write(path, data)? }
+        self.mark_saved();
+        Ok(true)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,59 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::header::Header;
+
+// cartridge ram (or eeprom), derefs into a byte slice.
+// Mutable access flags it dirty so frontends know when to flush it.
+pub struct SaveRam {
+    data: Vec<u8>,
+    // survives power off
+    pub battery: bool,
+    dirty: bool
+}
+impl SaveRam {
+    pub fn new(size: usize, battery: bool) -> SaveRam {
+        SaveRam { data: vec![0; size], battery, dirty: false }
+    }
+    // the prg ram described by the header
+    pub fn prg(header: &Header) -> SaveRam {
+        let size = header.prg_ram_size + header.prg_nvram_size;
+        SaveRam::new(size, header.battery && size > 0)
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+    pub fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+    // restores previously saved contents, a size mismatch copies what fits
+    pub fn load(&mut self, data: &[u8]) {
+        let len = data.len().min(self.data.len());
+        self.data[..len].copy_from_slice(&data[..len]);
+        self.dirty = false;
+    }
+}
+impl Deref for SaveRam {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+impl DerefMut for SaveRam {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.dirty = true;
+        &mut self.data
+    }
+}
+// loading flags it dirty, the save file no longer matches
+impl Snapshot for SaveRam {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_block(self);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        r.read_block(self)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Cartridge, Header};
+    use crate::tests::{rom, with_small_prg, with_submapper};
+
+    #[test]
+    fn test_chr_ram_writes() {
+        let mut cart = Cartridge::from_bytes(&rom(0, 1, 0)).unwrap();
+        cart.mapper.ppu_write(0x1234, 0x56);
+        assert!(cart.mapper.ppu_read(0x1234) == 0x56);
+        // chr rom ignores them
+        let mut cart = Cartridge::from_bytes(&rom(0, 1, 1)).unwrap();
+        cart.mapper.ppu_write(0x1234, 0x56);
+        assert!(cart.mapper.ppu_read(0x1234) == 0x04);
+    }
+    #[test]
+    fn test_nes2_chr_ram_size() {
+        let mut bytes = with_submapper(rom(2, 2, 0), 0);
+        // 16KB of ram and 8KB battery backed
+        bytes[11] = 0x78;
+        let header = Header::parse(&bytes).unwrap();
+        assert!(header.chr_ram_size == 0x4000 && header.chr_nvram_size == 0x2000);
+        assert!(header.chr_ram_len() == 0x6000);
+        bytes[11] = 0;
+        assert!(Header::parse(&bytes).unwrap().chr_ram_len() == 0x2000);
+    }
+    #[test]
+    fn test_uxrom() {
+        let mut cart = Cartridge::from_bytes(&rom(2, 8, 0)).unwrap();
+        assert!(cart.mapper.cpu_read(0xc000) == Some(112));
+        cart.mapper.cpu_write(0x8000, 3);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(48) && cart.mapper.cpu_read(0xbfff) == Some(63));
+        assert!(cart.mapper.cpu_read(0xffff) == Some(127));
+        cart.mapper.ppu_write(0x0000, 0x12);
+        assert!(cart.mapper.ppu_read(0x0000) == 0x12);
+    }
+    #[test]
+    fn test_uxrom_bus_conflicts() {
+        // the rom at $8400 reads 1 in bank 0
+        let mut cart = Cartridge::from_bytes(&with_submapper(rom(2, 8, 0), 2)).unwrap();
+        cart.mapper.cpu_write(0x8400, 3);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(16));
+        let mut cart = Cartridge::from_bytes(&with_submapper(rom(2, 8, 0), 1)).unwrap();
+        cart.mapper.cpu_write(0x8400, 3);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(48));
+    }
+    #[test]
+    fn test_uxrom_small_prg() {
+        let mut cart = Cartridge::from_bytes(&with_small_prg(rom(2, 1, 0), 0x2000)).unwrap();
+        cart.mapper.cpu_write(0x8000, 1);
+        assert!(cart.mapper.cpu_read(0x8400) == Some(1));
+        assert!(cart.mapper.cpu_read(0xfc00) == Some(7));
+    }
+    #[test]
+    fn test_cprom_chr_banks() {
+        let mut cart = Cartridge::from_bytes(&rom(13, 2, 0)).unwrap();
+        for bank in 0..4 {
+            // $fc00 reads 31, no conflict on the low bits
+            cart.mapper.cpu_write(0xfc00, bank);
+            cart.mapper.ppu_write(0x1000, bank + 1);
+        }
+        cart.mapper.cpu_write(0xfc00, 2);
+        assert!(cart.mapper.ppu_read(0x1000) == 3);
+        // the lower half is always bank 0
+        cart.mapper.cpu_write(0xfc00, 0);
+        cart.mapper.ppu_write(0x0010, 0x99);
+        assert!(cart.mapper.ppu_read(0x1000) == 1 && cart.mapper.ppu_read(0x0010) == 0x99);
+        // $8400 reads 1
+        cart.mapper.cpu_write(0x8400, 2);
+        assert!(cart.mapper.ppu_read(0x1000) == 1);
+    }
+}
\ No newline at end of file
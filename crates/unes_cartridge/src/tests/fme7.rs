@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Cartridge, Mirroring};
+    use crate::tests::{rom, with_small_prg};
+
+    fn cart() -> Cartridge {
+        Cartridge::from_bytes(&rom(69, 16, 32)).unwrap()
+    }
+    fn command(cart: &mut Cartridge, command: u8, value: u8) {
+        cart.mapper.cpu_write(0x8000, command);
+        cart.mapper.cpu_write(0xa000, value);
+    }
+    fn write_sound(cart: &mut Cartridge, reg: u8, value: u8) {
+        cart.mapper.cpu_write(0xc000, reg);
+        cart.mapper.cpu_write(0xe000, value);
+    }
+
+    #[test]
+    fn test_fme7_prg_banks() {
+        let mut cart = cart();
+        command(&mut cart, 0x9, 1);
+        command(&mut cart, 0xa, 2);
+        command(&mut cart, 0xb, 3);
+        command(&mut cart, 0x8, 4);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(32));
+        assert!(cart.mapper.cpu_read(0x8000) == Some(8));
+        assert!(cart.mapper.cpu_read(0xa000) == Some(16));
+        assert!(cart.mapper.cpu_read(0xc000) == Some(24));
+        assert!(cart.mapper.cpu_read(0xe000) == Some(248));
+    }
+    #[test]
+    fn test_fme7_prg_ram() {
+        let mut cart = cart();
+        // selected but disabled
+        command(&mut cart, 0x8, 0x40);
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000).is_none());
+        command(&mut cart, 0x8, 0xc0);
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0x12));
+    }
+    #[test]
+    fn test_fme7_chr_and_mirroring() {
+        let mut cart = cart();
+        command(&mut cart, 0x3, 77);
+        assert!(cart.mapper.ppu_read(0x0c00) == 77);
+        command(&mut cart, 0xc, 1);
+        assert!(cart.mapper.mirroring() == Mirroring::Horizontal);
+        command(&mut cart, 0xc, 2);
+        assert!(cart.mapper.mirroring() == Mirroring::SingleScreenA);
+    }
+    #[test]
+    fn test_fme7_irq() {
+        let mut cart = cart();
+        command(&mut cart, 0xe, 2);
+        command(&mut cart, 0xf, 0);
+        command(&mut cart, 0xd, 0x81);
+        for _ in 0..2 { cart.mapper.clock() }
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+        command(&mut cart, 0xd, 0x81);
+        assert!(!cart.mapper.irq());
+        // the counter keeps running without raising the irq
+        command(&mut cart, 0xe, 0);
+        command(&mut cart, 0xd, 0x80);
+        for _ in 0..2 { cart.mapper.clock() }
+        assert!(!cart.mapper.irq());
+    }
+    #[test]
+    fn test_5b_tone() {
+        let mut cart = cart();
+        write_sound(&mut cart, 0x00, 2);
+        write_sound(&mut cart, 0x07, 0x3e);
+        write_sound(&mut cart, 0x08, 0x0f);
+        // 2 * 16 cycles per half period
+        let high = (0..256).filter(|_| {
+            cart.mapper.clock();
+            cart.mapper.audio().unwrap().channel_output(0) > 0.0
+        }).count();
+        assert!(high == 128);
+        assert!(cart.mapper.audio().unwrap().channel_output(1) == 0.0);
+    }
+    #[test]
+    fn test_5b_volume() {
+        let level = |volume| {
+            let mut cart = cart();
+            write_sound(&mut cart, 0x07, 0x3f);
+            write_sound(&mut cart, 0x08, volume);
+            cart.mapper.audio().unwrap().channel_output(0)
+        };
+        assert!(level(0) == 0.0);
+        // 3dB per volume step
+        let ratio = level(14) / level(15);
+        assert!(ratio > 0.68 && ratio < 0.73);
+    }
+    #[test]
+    fn test_5b_envelope() {
+        let mut cart = cart();
+        write_sound(&mut cart, 0x07, 0x3f);
+        write_sound(&mut cart, 0x08, 0x10);
+        write_sound(&mut cart, 0x0b, 1);
+        // rise once and hold the top level
+        write_sound(&mut cart, 0x0d, 0x0d);
+        let levels = (0..40).map(|_| {
+            for _ in 0..16 { cart.mapper.clock() }
+            cart.mapper.audio().unwrap().channel_output(0)
+        }).collect::<alloc::vec::Vec<_>>();
+        assert!(levels.windows(2).all(|w| w[1] >= w[0]));
+        assert!(levels[30] == levels[39] && levels[39] > 0.0);
+        // fall once and stay silent
+        write_sound(&mut cart, 0x0d, 0x00);
+        for _ in 0..40 * 16 { cart.mapper.clock() }
+        assert!(cart.mapper.audio().unwrap().channel_output(0) == 0.0);
+    }
+    #[test]
+    fn test_fme7_small_prg() {
+        let mut cart = Cartridge::from_bytes(&with_small_prg(rom(69, 1, 32), 0x1000)).unwrap();
+        command(&mut cart, 9, 3);
+        assert!(cart.mapper.cpu_read(0x8400) == Some(1));
+        assert!(cart.mapper.cpu_read(0xfc00) == Some(3));
+    }
+}
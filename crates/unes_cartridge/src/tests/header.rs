@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Cartridge, CartridgeError, Header, Mirroring, Timing};
+    use crate::tests::{rom, with_submapper};
+
+    #[test]
+    fn test_ines_header() {
+        let mut bytes = rom(5, 2, 1);
+        bytes[6] |= 0b11;
+        let header = Header::parse(&bytes).unwrap();
+        assert!(!header.nes2);
+        assert!(header.mapper == 5);
+        assert!(header.prg_rom_size == 0x8000);
+        assert!(header.chr_rom_size == 0x2000);
+        assert!(header.mirroring == Mirroring::Vertical);
+        assert!(header.battery);
+        assert!(header.prg_nvram_size == 0x2000);
+        assert!(header.prg_ram_size == 0);
+    }
+    #[test]
+    fn test_four_screen_header() {
+        let mut bytes = rom(0, 1, 1);
+        bytes[6] |= 0b1001;
+        let header = Header::parse(&bytes).unwrap();
+        assert!(header.mirroring == Mirroring::FourScreen);
+        let cart = Cartridge::from_bytes(&bytes).unwrap();
+        assert!(cart.mapper.mirroring() == Mirroring::FourScreen);
+    }
+    #[test]
+    fn test_ines_dirty_header() {
+        let mut bytes = rom(0, 1, 1);
+        bytes[7] = 0x40;
+        bytes[12..16].copy_from_slice(b"Disk");
+        let header = Header::parse(&bytes).unwrap();
+        assert!(header.mapper == 0);
+    }
+    #[test]
+    fn test_nes2_header() {
+        let mut bytes = rom(0, 1, 0);
+        bytes[6] = 0x50;
+        bytes[7] = 0x08;
+        bytes[8] = 0x21;
+        bytes[10] = 0x70;
+        bytes[11] = 0x07;
+        let header = Header::parse(&bytes).unwrap();
+        assert!(header.nes2);
+        assert!(header.mapper == 0x105);
+        assert!(header.submapper == 2);
+        assert!(header.prg_nvram_size == 0x2000);
+        assert!(header.chr_ram_size == 0x2000);
+        assert!(header.mirroring == Mirroring::Horizontal);
+    }
+    #[test]
+    fn test_timing() {
+        let mut bytes = rom(0, 1, 0);
+        assert!(Header::parse(&bytes).unwrap().timing == Timing::Ntsc);
+        bytes[9] = 0x01;
+        assert!(Header::parse(&bytes).unwrap().timing == Timing::Pal);
+        bytes[9] = 0;
+        bytes[7] = 0x08;
+        for (value, timing) in [(0, Timing::Ntsc), (1, Timing::Pal), (2, Timing::MultiRegion), (3, Timing::Dendy)] {
+            bytes[12] = value;
+            assert!(Header::parse(&bytes).unwrap().timing == timing);
+        }
+    }
+    #[test]
+    fn test_nes2_exponent_size() {
+        let mut bytes = rom(0, 1, 0);
+        bytes[7] = 0x08;
+        // 2^4 * 3
+        bytes[4] = 0b0001_0001;
+        bytes[9] = 0x0f;
+        let header = Header::parse(&bytes).unwrap();
+        assert!(header.prg_rom_size == 48);
+    }
+    #[test]
+    fn test_invalid_magic() {
+        let mut bytes = rom(0, 1, 1);
+        bytes[0] = b'X';
+        assert!(Cartridge::from_bytes(&bytes).err() == Some(CartridgeError::InvalidHeader));
+    }
+    #[test]
+    fn test_truncated_rom() {
+        let bytes = rom(0, 2, 1);
+        assert!(Cartridge::from_bytes(&bytes[..0x4000]).err() == Some(CartridgeError::Truncated));
+    }
+    #[test]
+    fn test_no_prg_rom() {
+        let bytes = rom(0, 0, 1);
+        assert!(Cartridge::from_bytes(&bytes).err() == Some(CartridgeError::NoPrgRom));
+    }
+    #[test]
+    fn test_huge_rom_sizes() {
+        // NES 2.0 exponent notation, 2^63 * 7 bytes saturates
+        let mut bytes = with_submapper(rom(0, 1, 1), 0);
+        (bytes[4], bytes[9]) = (0xff, 0x0f);
+        assert!(Cartridge::from_bytes(&bytes).err() == Some(CartridgeError::Truncated));
+        let mut bytes = with_submapper(rom(0, 1, 1), 0);
+        (bytes[5], bytes[9]) = (0xff, 0xf0);
+        assert!(Cartridge::from_bytes(&bytes).err() == Some(CartridgeError::Truncated));
+    }
+    #[test]
+    fn test_unsupported_mapper() {
+        let bytes = rom(0xff, 1, 1);
+        assert!(Cartridge::from_bytes(&bytes).err() == Some(CartridgeError::UnsupportedMapper(0xff)));
+    }
+    #[test]
+    fn test_nrom_mirrored_prg() {
+        let mut cart = Cartridge::from_bytes(&rom(0, 1, 1)).unwrap();
+        assert!(cart.mapper.cpu_read(0x8400) == Some(1));
+        assert!(cart.mapper.cpu_read(0xc400) == Some(1));
+        assert!(cart.mapper.ppu_read(0x1c00) == 7);
+    }
+}
\ No newline at end of file
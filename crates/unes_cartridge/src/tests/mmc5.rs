@@ -0,0 +1,327 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Cartridge, Mapper};
+    use crate::tests::{rom, with_submapper};
+
+    fn mmc5() -> Cartridge {
+        // 256KB prg, 256KB chr
+        Cartridge::from_bytes(&rom(5, 16, 32)).unwrap()
+    }
+    fn fetch_tile(mapper: &mut dyn Mapper, ciram: &[u8], column: u16) -> (u8, u8, u8) {
+        let tile = mapper.nametable_read(0x2000 | column, ciram);
+        let attr = mapper.nametable_read(0x23c0 | (column / 4), ciram);
+        let pattern = mapper.ppu_read((tile as u16) << 4);
+        mapper.ppu_read((tile as u16) << 4 | 8);
+        (tile, attr, pattern)
+    }
+    // mimics the ppu fetch pattern at the end of a line:
+    // tiles 0 and 1 of the next line and two dummy nametable reads
+    fn end_line(mapper: &mut dyn Mapper, ciram: &[u8]) {
+        fetch_tile(mapper, ciram, 0);
+        fetch_tile(mapper, ciram, 1);
+        mapper.nametable_read(0x2002, ciram);
+        mapper.nametable_read(0x2002, ciram);
+    }
+    // full scanline, returns the (tile, attribute, pattern) fetched for tiles 2-33
+    fn scanline(mapper: &mut dyn Mapper, ciram: &[u8]) -> [(u8, u8, u8); 32] {
+        let mut tiles = [(0, 0, 0); 32];
+        for (i, tile) in tiles.iter_mut().enumerate() {
+            *tile = fetch_tile(mapper, ciram, (i as u16 + 2) % 32);
+        }
+        for _ in 0..8 {
+            mapper.nametable_read(0x2000, ciram);
+            mapper.nametable_read(0x2000, ciram);
+            mapper.ppu_read(0x1ff0);
+            mapper.ppu_read(0x1ff8);
+        }
+        end_line(mapper, ciram);
+        tiles
+    }
+
+    #[test]
+    fn test_prg_default_last_bank() {
+        let mut cart = mmc5();
+        // 8KB bank 31 -> 1KB marker 248
+        assert!(cart.mapper.cpu_read(0xe000) == Some(248));
+        assert!(cart.mapper.cpu_read(0xffff) == Some(255));
+    }
+    #[test]
+    fn test_prg_mode_0() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5100, 0);
+        cart.mapper.cpu_write(0x5117, 0x87);
+        // the low 2 bits are ignored in 32KB mode
+        assert!(cart.mapper.cpu_read(0x8000) == Some(32));
+        assert!(cart.mapper.cpu_read(0xe000) == Some(56));
+    }
+    #[test]
+    fn test_prg_mode_2() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5100, 2);
+        cart.mapper.cpu_write(0x5115, 0x85);
+        cart.mapper.cpu_write(0x5116, 0x89);
+        cart.mapper.cpu_write(0x5117, 0x8b);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(32));
+        assert!(cart.mapper.cpu_read(0xa000) == Some(40));
+        assert!(cart.mapper.cpu_read(0xc000) == Some(72));
+        assert!(cart.mapper.cpu_read(0xe000) == Some(88));
+    }
+    #[test]
+    fn test_prg_ram_protect() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0));
+        cart.mapper.cpu_write(0x5102, 2);
+        cart.mapper.cpu_write(0x5103, 1);
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0x12));
+    }
+    #[test]
+    fn test_prg_ram_in_rom_window() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5102, 2);
+        cart.mapper.cpu_write(0x5103, 1);
+        cart.mapper.cpu_write(0x5113, 3);
+        cart.mapper.cpu_write(0x6010, 0x34);
+        // ram bank 3 mapped to $8000 in mode 3
+        cart.mapper.cpu_write(0x5114, 0x03);
+        assert!(cart.mapper.cpu_read(0x8010) == Some(0x34));
+        cart.mapper.cpu_write(0x8011, 0x56);
+        assert!(cart.mapper.cpu_read(0x6011) == Some(0x56));
+    }
+    #[test]
+    fn test_small_memories() {
+        // 16KB prg, 4KB prg ram and 2KB chr ram
+        let mut bytes = with_submapper(rom(5, 1, 0), 0);
+        (bytes[10], bytes[11]) = (0x06, 0x05);
+        let mut cart = Cartridge::from_bytes(&bytes).unwrap();
+        // mirrored across the 32KB window
+        cart.mapper.cpu_write(0x5100, 0);
+        assert!(cart.mapper.cpu_read(0x8400) == Some(1) && cart.mapper.cpu_read(0xfc00) == Some(15));
+        cart.mapper.cpu_write(0x5102, 2);
+        cart.mapper.cpu_write(0x5103, 1);
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x7000) == Some(0x12));
+        cart.mapper.cpu_write(0x5101, 0);
+        cart.mapper.ppu_write(0x0000, 0x34);
+        assert!(cart.mapper.ppu_read(0x1800) == 0x34);
+    }
+    #[test]
+    fn test_chr_1k_banks() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5101, 3);
+        cart.mapper.cpu_write(0x5120, 10);
+        cart.mapper.cpu_write(0x5127, 200);
+        assert!(cart.mapper.ppu_read(0x0000) == 10);
+        assert!(cart.mapper.ppu_read(0x1c00) == 200);
+    }
+    #[test]
+    fn test_chr_upper_bits() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5101, 3);
+        cart.mapper.cpu_write(0x5130, 1);
+        cart.mapper.cpu_write(0x5120, 1);
+        // bank 257 wraps around the 256KB chr
+        assert!(cart.mapper.ppu_read(0x0000) == 1);
+    }
+    #[test]
+    fn test_chr_sets_8x16() {
+        let mut cart = mmc5();
+        let ciram = [0; 0x800];
+        cart.mapper.cpu_write(0x2000, 0x20);
+        cart.mapper.cpu_write(0x2001, 0x18);
+        cart.mapper.cpu_write(0x5101, 3);
+        // sprites use the A set
+        for i in 0..8 { cart.mapper.cpu_write(0x5120 + i, 100 + i as u8) }
+        // background the B set
+        for i in 0..4 { cart.mapper.cpu_write(0x5128 + i, 50 + i as u8) }
+
+        // outside of the frame the last written set is used
+        assert!(cart.mapper.ppu_read(0x1400) == 51);
+
+        end_line(cart.mapper.as_mut(), &ciram);
+        for column in 2..34 {
+            assert!(fetch_tile(cart.mapper.as_mut(), &ciram, column % 32).2 == 50);
+        }
+        // first sprite fetch
+        cart.mapper.nametable_read(0x2000, &ciram);
+        assert!(cart.mapper.ppu_read(0x1400) == 105);
+    }
+    #[test]
+    fn test_exram_nametable_and_fill() {
+        let mut cart = mmc5();
+        let mut ciram = [0; 0x800];
+        // nt0: ciram a, nt1: ciram b, nt2: exram, nt3: fill
+        cart.mapper.cpu_write(0x5105, 0b11_10_01_00);
+        cart.mapper.cpu_write(0x5106, 0x42);
+        cart.mapper.cpu_write(0x5107, 2);
+        cart.mapper.nametable_write(0x2005, 1, &mut ciram);
+        cart.mapper.nametable_write(0x2405, 2, &mut ciram);
+        cart.mapper.nametable_write(0x2805, 3, &mut ciram);
+        assert!(ciram[0x005] == 1);
+        assert!(ciram[0x405] == 2);
+        assert!(cart.mapper.nametable_read(0x2805, &ciram) == 3);
+        assert!(cart.mapper.nametable_read(0x2c05, &ciram) == 0x42);
+        assert!(cart.mapper.nametable_read(0x2fc5, &ciram) == 0xaa);
+    }
+    #[test]
+    fn test_exram_cpu_access() {
+        let mut cart = mmc5();
+        // not readable in the nametable modes
+        assert!(cart.mapper.cpu_read(0x5c00).is_none());
+        cart.mapper.cpu_write(0x5104, 2);
+        cart.mapper.cpu_write(0x5c00, 0x77);
+        assert!(cart.mapper.cpu_read(0x5c00) == Some(0x77));
+        // read only
+        cart.mapper.cpu_write(0x5104, 3);
+        cart.mapper.cpu_write(0x5c00, 0x11);
+        assert!(cart.mapper.cpu_read(0x5c00) == Some(0x77));
+        // nametable mode writes outside of rendering store 0
+        cart.mapper.cpu_write(0x5104, 0);
+        cart.mapper.cpu_write(0x5c00, 0x11);
+        cart.mapper.cpu_write(0x5104, 2);
+        assert!(cart.mapper.cpu_read(0x5c00) == Some(0));
+    }
+    #[test]
+    fn test_extended_attributes() {
+        let mut cart = mmc5();
+        let ciram = [0; 0x800];
+        cart.mapper.cpu_write(0x5104, 2);
+        // tile 5: palette 3, 4KB chr bank 7
+        cart.mapper.cpu_write(0x5c05, 0b11_000111);
+        cart.mapper.cpu_write(0x5104, 1);
+        cart.mapper.cpu_write(0x2001, 0x18);
+
+        end_line(cart.mapper.as_mut(), &ciram);
+        let tiles = scanline(cart.mapper.as_mut(), &ciram);
+        // tile 5 is the 4th fetch
+        assert!(tiles[3].1 == 0xff);
+        assert!(tiles[3].2 == 28);
+        assert!(tiles[4].1 == 0);
+        assert!(tiles[4].2 == 0);
+    }
+    #[test]
+    fn test_scanline_irq() {
+        let mut cart = mmc5();
+        let ciram = [0; 0x800];
+        cart.mapper.cpu_write(0x2001, 0x18);
+        cart.mapper.cpu_write(0x5203, 3);
+        cart.mapper.cpu_write(0x5204, 0x80);
+        assert!(cart.mapper.cpu_read(0x5204) == Some(0));
+
+        end_line(cart.mapper.as_mut(), &ciram);
+        for _ in 0..3 {
+            assert!(!cart.mapper.irq());
+            scanline(cart.mapper.as_mut(), &ciram);
+        }
+        // fired on the 4th line start
+        cart.mapper.nametable_read(0x2002, &ciram);
+        assert!(cart.mapper.irq());
+        assert!(cart.mapper.cpu_read(0x5204) == Some(0xc0));
+        assert!(!cart.mapper.irq());
+    }
+    #[test]
+    fn test_leave_frame_when_idle() {
+        let mut cart = mmc5();
+        let ciram = [0; 0x800];
+        end_line(cart.mapper.as_mut(), &ciram);
+        cart.mapper.nametable_read(0x2002, &ciram);
+        assert!(cart.mapper.cpu_read(0x5204) == Some(0x40));
+        for _ in 0..3 { cart.mapper.clock() }
+        assert!(cart.mapper.cpu_read(0x5204) == Some(0));
+    }
+    #[test]
+    fn test_nmi_vector_leaves_frame() {
+        let mut cart = mmc5();
+        let ciram = [0; 0x800];
+        end_line(cart.mapper.as_mut(), &ciram);
+        cart.mapper.nametable_read(0x2002, &ciram);
+        cart.mapper.cpu_read(0xfffa);
+        assert!(cart.mapper.cpu_read(0x5204) == Some(0));
+    }
+    #[test]
+    fn test_vertical_split() {
+        let mut cart = mmc5();
+        let ciram = [0; 0x800];
+        cart.mapper.cpu_write(0x5104, 2);
+        // split tile row 1, column 3
+        cart.mapper.cpu_write(0x5c00 + 32 + 3, 0x80);
+        // palette 1 for the top right quadrant of the first attribute area
+        cart.mapper.cpu_write(0x5fc0, 0b0000_0100);
+        cart.mapper.cpu_write(0x5104, 1);
+        // left split, up to tile 4, starting at y = 8
+        cart.mapper.cpu_write(0x5200, 0x84);
+        cart.mapper.cpu_write(0x5201, 8);
+        cart.mapper.cpu_write(0x5202, 2);
+
+        end_line(cart.mapper.as_mut(), &ciram);
+        let tiles = scanline(cart.mapper.as_mut(), &ciram);
+        // column 3 is the 2nd fetch of the line
+        assert!(tiles[1].0 == 0x80);
+        assert!(tiles[1].1 == 0x55);
+        // tile 0x80 of the 4KB bank 2 -> 1KB bank 10
+        assert!(tiles[1].2 == 10);
+        // column 4 is outside of the split
+        assert!(tiles[2].0 == 0);
+        assert!(tiles[2].2 == 0);
+    }
+    #[test]
+    fn test_multiplier() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5205, 200);
+        cart.mapper.cpu_write(0x5206, 100);
+        assert!(cart.mapper.cpu_read(0x5205) == Some(0x20));
+        assert!(cart.mapper.cpu_read(0x5206) == Some(0x4e));
+    }
+    #[test]
+    fn test_pulse_output() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5015, 0x01);
+        // 50% duty, constant volume 10
+        cart.mapper.cpu_write(0x5000, 0b1011_1010);
+        cart.mapper.cpu_write(0x5002, 0x10);
+        cart.mapper.cpu_write(0x5003, 0x08);
+        assert!(cart.mapper.cpu_read(0x5015) == Some(1));
+
+        let audio_levels = (0..0x200).map(|_| {
+            cart.mapper.clock();
+            cart.mapper.audio().unwrap().channel_output(0)
+        });
+        let max = audio_levels.fold(0.0f32, |a, b| a.max(b));
+        assert!(max > 0.08 && max < 0.09);
+        // pulse 2 stays disabled
+        assert!(cart.mapper.audio().unwrap().channel_output(1) == 0.0);
+    }
+    #[test]
+    fn test_pulse_length_counter() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5015, 0x02);
+        cart.mapper.cpu_write(0x5004, 0x10);
+        // length index 3 -> 2 frames
+        cart.mapper.cpu_write(0x5007, 0x18);
+        assert!(cart.mapper.cpu_read(0x5015) == Some(2));
+        for _ in 0..7457 * 2 { cart.mapper.clock() }
+        assert!(cart.mapper.cpu_read(0x5015) == Some(0));
+    }
+    #[test]
+    fn test_pcm_write_mode() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5011, 0x80);
+        let level = cart.mapper.audio().unwrap().channel_output(2);
+        assert!(level > 1.0 && level < 1.2);
+        // zero writes are ignored
+        cart.mapper.cpu_write(0x5011, 0);
+        assert!(cart.mapper.audio().unwrap().channel_output(2) == level);
+    }
+    #[test]
+    fn test_pcm_read_mode_irq() {
+        let mut cart = mmc5();
+        cart.mapper.cpu_write(0x5010, 0x81);
+        cart.mapper.cpu_write(0x5114, 0x80);
+        // bank 0 starts with zeroes
+        cart.mapper.cpu_read(0x8000);
+        assert!(cart.mapper.irq());
+        assert!(cart.mapper.cpu_read(0x5010) == Some(0x81));
+        assert!(!cart.mapper.irq());
+    }
+}
\ No newline at end of file
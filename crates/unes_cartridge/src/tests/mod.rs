@@ -0,0 +1,44 @@
+mod chr_ram;
+mod fme7;
+mod header;
+mod mmc5;
+mod n163;
+mod save;
+mod state;
+mod vrc;
+mod vrc7;
+
+#[cfg(test)]
+use alloc::vec::Vec;
+
+// builds an iNES 1 image, every 1KB of prg and chr is filled with its bank number
+#[cfg(test)]
+pub fn rom(mapper: u8, prg_16k: u8, chr_8k: u8) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&[b'N', b'E', b'S', 0x1a, prg_16k, chr_8k, mapper << 4, mapper & 0xf0]);
+    bytes.extend_from_slice(&[0; 8]);
+    for bank in 0..prg_16k as usize * 16 {
+        bytes.extend((0..0x400).map(|_| bank as u8));
+    }
+    for bank in 0..chr_8k as usize * 8 {
+        bytes.extend((0..0x400).map(|_| bank as u8));
+    }
+    bytes
+}
+// turns an image from `rom` into NES 2.0 with the given submapper
+#[cfg(test)]
+pub fn with_submapper(mut bytes: Vec<u8>, submapper: u8) -> Vec<u8> {
+    bytes[7] = bytes[7] & 0xf0 | 0x08;
+    bytes[8] = submapper << 4;
+    bytes
+}
+// turns an image from `rom` with 16KB of prg into NES 2.0 with the first `len`
+// (a smaller power of two) bytes of it
+#[cfg(test)]
+pub fn with_small_prg(mut bytes: Vec<u8>, len: usize) -> Vec<u8> {
+    bytes[4] = (len.trailing_zeros() as u8) << 2;
+    bytes[7] = bytes[7] & 0xf0 | 0x08;
+    bytes[9] = bytes[9] & 0xf0 | 0x0f;
+    bytes.drain(0x10 + len..0x4010);
+    bytes
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use crate::Cartridge;
+    use crate::tests::{rom, with_small_prg};
+
+    fn cart() -> Cartridge {
+        Cartridge::from_bytes(&rom(19, 16, 32)).unwrap()
+    }
+    fn write_ram(cart: &mut Cartridge, addr: u8, values: &[u8]) {
+        cart.mapper.cpu_write(0xf800, 0x80 | addr);
+        values.iter().for_each(|&v| cart.mapper.cpu_write(0x4800, v));
+    }
+    fn output(cart: &mut Cartridge, channel: usize) -> f32 {
+        for _ in 0..15 { cart.mapper.clock() }
+        cart.mapper.audio().unwrap().channel_output(channel)
+    }
+    // channel 8 plays a 4 sample wave (0, 15, 8, 8), one sample per update
+    fn wave(cart: &mut Cartridge, channels: u8) {
+        write_ram(cart, 0x00, &[0xf0, 0x88]);
+        write_ram(cart, 0x78, &[0x00, 0x00, 0x00, 0x00, 0xfd, 0x00, 0x00, 0x0f | (channels - 1) << 4]);
+    }
+
+    #[test]
+    fn test_n163_prg_banks() {
+        let mut cart = cart();
+        cart.mapper.cpu_write(0xe000, 1);
+        cart.mapper.cpu_write(0xe800, 2);
+        cart.mapper.cpu_write(0xf000, 3);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(8));
+        assert!(cart.mapper.cpu_read(0xa000) == Some(16));
+        assert!(cart.mapper.cpu_read(0xc000) == Some(24));
+        assert!(cart.mapper.cpu_read(0xe000) == Some(248));
+    }
+    #[test]
+    fn test_n163_chr_and_nametables() {
+        let mut cart = cart();
+        for i in 0..8u16 {
+            cart.mapper.cpu_write(0x8000 + i * 0x800, 50 + i as u8);
+        }
+        assert!(cart.mapper.ppu_read(0x0000) == 50);
+        assert!(cart.mapper.ppu_read(0x1c00) == 57);
+
+        let mut ciram = [0u8; 0x800];
+        ciram[0x400] = 0xaa;
+        cart.mapper.cpu_write(0xc000, 0xe1);
+        assert!(cart.mapper.nametable_read(0x2000, &ciram) == 0xaa);
+        // nametable from chr rom
+        cart.mapper.cpu_write(0xc800, 9);
+        assert!(cart.mapper.nametable_read(0x2400, &ciram) == 9);
+    }
+    #[test]
+    fn test_n163_ram_write_protect() {
+        let mut cart = cart();
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0));
+        // $6000-$67ff stays protected
+        cart.mapper.cpu_write(0xf800, 0x41);
+        cart.mapper.cpu_write(0x6000, 0x12);
+        cart.mapper.cpu_write(0x6800, 0x34);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0));
+        assert!(cart.mapper.cpu_read(0x6800) == Some(0x34));
+    }
+    #[test]
+    fn test_n163_irq() {
+        let mut cart = cart();
+        cart.mapper.cpu_write(0x5000, 0xfe);
+        cart.mapper.cpu_write(0x5800, 0xff);
+        assert!(cart.mapper.cpu_read(0x5800) == Some(0xff));
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+        // the counter stops at $7fff
+        cart.mapper.clock();
+        assert!(cart.mapper.cpu_read(0x5000) == Some(0xff));
+        cart.mapper.cpu_write(0x5800, 0x80);
+        assert!(!cart.mapper.irq());
+    }
+    #[test]
+    fn test_n163_sound_ram_port() {
+        let mut cart = cart();
+        write_ram(&mut cart, 0x7e, &[1, 2, 3]);
+        // wraps around the 128 bytes
+        cart.mapper.cpu_write(0xf800, 0x80 | 0x7e);
+        assert!(cart.mapper.cpu_read(0x4800) == Some(1));
+        assert!(cart.mapper.cpu_read(0x4800) == Some(2));
+        assert!(cart.mapper.cpu_read(0x4800) == Some(3));
+        // no auto increment
+        cart.mapper.cpu_write(0xf800, 0x7e);
+        assert!(cart.mapper.cpu_read(0x4800) == Some(1));
+        assert!(cart.mapper.cpu_read(0x4800) == Some(1));
+    }
+    #[test]
+    fn test_n163_wave() {
+        let mut cart = cart();
+        wave(&mut cart, 1);
+        let levels = [0; 4].map(|_| output(&mut cart, 7));
+        assert!(levels[0] > 0.0);
+        assert!(levels[1] == 0.0 && levels[2] == 0.0);
+        // (15 - 8) * 15 against (0 - 8) * 15
+        assert!((levels[3] / levels[0] + 120.0 / 105.0).abs() < 1e-5);
+    }
+    #[test]
+    fn test_n163_multiplexing() {
+        let mut single = cart();
+        wave(&mut single, 1);
+        let mut double = cart();
+        wave(&mut double, 2);
+        let level = output(&mut single, 7);
+        // channel 7 updates first, channel 8 gets half the volume
+        assert!(output(&mut double, 7) == level / 2.0);
+        assert!(double.mapper.audio().unwrap().channel_output(5) == 0.0);
+        // and half the update rate
+        assert!(output(&mut double, 7) == level / 2.0);
+        assert!(output(&mut double, 7) == 0.0);
+    }
+    #[test]
+    fn test_n163_sound_disable() {
+        let mut cart = cart();
+        wave(&mut cart, 1);
+        cart.mapper.cpu_write(0xe000, 0x40);
+        assert!(output(&mut cart, 7) == 0.0);
+    }
+    #[test]
+    fn test_n163_small_prg() {
+        let mut cart = Cartridge::from_bytes(&with_small_prg(rom(19, 1, 32), 0x1000)).unwrap();
+        cart.mapper.cpu_write(0xe000, 3);
+        assert!(cart.mapper.cpu_read(0x8400) == Some(1));
+        assert!(cart.mapper.cpu_read(0xfc00) == Some(3));
+    }
+}
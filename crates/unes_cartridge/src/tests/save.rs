@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod tests {
+    use crate::Cartridge;
+    use crate::tests::{rom, with_small_prg, with_submapper};
+
+    fn battery_rom(mapper: u8) -> Cartridge {
+        let mut bytes = rom(mapper, 2, 1);
+        bytes[6] |= 0b10;
+        Cartridge::from_bytes(&bytes).unwrap()
+    }
+
+    // bit banging the Bandai eeprom through $800d
+    fn lines(cart: &mut Cartridge, scl: bool, sda: bool) {
+        cart.mapper.cpu_write(0x800d, (scl as u8) << 5 | (sda as u8) << 6);
+    }
+    fn start(cart: &mut Cartridge) {
+        lines(cart, true, true);
+        lines(cart, true, false);
+        lines(cart, false, false);
+    }
+    fn stop(cart: &mut Cartridge) {
+        lines(cart, false, false);
+        lines(cart, true, false);
+        lines(cart, true, true);
+    }
+    fn sda(cart: &mut Cartridge) -> bool {
+        cart.mapper.cpu_read(0x6000).unwrap() & 0x10 != 0
+    }
+    // returns whether the eeprom acknowledged the byte
+    fn send(cart: &mut Cartridge, byte: u8, lsb_first: bool) -> bool {
+        for i in 0..8 {
+            let bit = if lsb_first { byte >> i & 1 } else { byte >> (7 - i) & 1 } != 0;
+            lines(cart, false, bit);
+            lines(cart, true, bit);
+            lines(cart, false, bit);
+        }
+        cart.mapper.cpu_write(0x800d, 0x80);
+        cart.mapper.cpu_write(0x800d, 0xa0);
+        let ack = !sda(cart);
+        cart.mapper.cpu_write(0x800d, 0x80);
+        ack
+    }
+    fn receive(cart: &mut Cartridge, lsb_first: bool, ack: bool) -> u8 {
+        let mut byte = 0;
+        for i in 0..8 {
+            cart.mapper.cpu_write(0x800d, 0x80);
+            cart.mapper.cpu_write(0x800d, 0xa0);
+            let bit = sda(cart) as u8;
+            byte |= if lsb_first { bit << i } else { bit << (7 - i) };
+        }
+        cart.mapper.cpu_write(0x800d, 0x80);
+        lines(cart, false, !ack);
+        lines(cart, true, !ack);
+        lines(cart, false, !ack);
+        byte
+    }
+
+    #[test]
+    fn test_battery_ram() {
+        let mut cart = battery_rom(0);
+        assert!(cart.save_data().unwrap().len() == 0x2000);
+        assert!(!cart.save_dirty());
+        cart.mapper.cpu_write(0x6010, 0x42);
+        assert!(cart.save_dirty());
+        assert!(cart.save_data().unwrap()[0x10] == 0x42);
+        cart.mark_saved();
+        assert!(!cart.save_dirty());
+        // reads do not dirty the ram
+        cart.mapper.cpu_read(0x6010);
+        assert!(!cart.save_dirty());
+    }
+    #[test]
+    fn test_load_save_data() {
+        let mut cart = battery_rom(24);
+        cart.load_save_data(&[1, 2, 3]);
+        assert!(!cart.save_dirty());
+        cart.mapper.cpu_write(0xb003, 0x80);
+        assert!(cart.mapper.cpu_read(0x6002) == Some(3));
+    }
+    #[test]
+    fn test_no_battery() {
+        let mut cart = Cartridge::from_bytes(&rom(0, 2, 1)).unwrap();
+        cart.mapper.cpu_write(0x6000, 0x42);
+        assert!(cart.save_data().is_none());
+        assert!(!cart.save_dirty());
+    }
+    #[test]
+    fn test_24c02() {
+        let mut cart = Cartridge::from_bytes(&with_submapper(rom(16, 2, 1), 5)).unwrap();
+        assert!(cart.save_data().unwrap().len() == 256);
+        start(&mut cart);
+        assert!(send(&mut cart, 0xa0, false));
+        assert!(send(&mut cart, 0x10, false));
+        assert!(send(&mut cart, 0x5a, false));
+        assert!(send(&mut cart, 0xc3, false));
+        stop(&mut cart);
+        assert!(cart.save_dirty());
+        assert!(cart.save_data().unwrap()[0x10..0x12] == [0x5a, 0xc3]);
+
+        // random read: dummy write of the address, then a restart
+        start(&mut cart);
+        send(&mut cart, 0xa0, false);
+        send(&mut cart, 0x10, false);
+        start(&mut cart);
+        assert!(send(&mut cart, 0xa1, false));
+        assert!(receive(&mut cart, false, true) == 0x5a);
+        assert!(receive(&mut cart, false, false) == 0xc3);
+        stop(&mut cart);
+        // other devices are ignored
+        start(&mut cart);
+        assert!(!send(&mut cart, 0x50, false));
+    }
+    #[test]
+    fn test_24c01() {
+        let mut cart = Cartridge::from_bytes(&rom(159, 2, 1)).unwrap();
+        assert!(cart.save_data().unwrap().len() == 128);
+        start(&mut cart);
+        assert!(send(&mut cart, 0x05, true));
+        assert!(send(&mut cart, 0x81, true));
+        stop(&mut cart);
+        assert!(cart.save_data().unwrap()[5] == 0x81);
+        start(&mut cart);
+        assert!(send(&mut cart, 0x85, true));
+        assert!(receive(&mut cart, true, false) == 0x81);
+        stop(&mut cart);
+    }
+    #[test]
+    fn test_bandai_banks_and_irq() {
+        let mut cart = Cartridge::from_bytes(&with_submapper(rom(16, 16, 32), 4)).unwrap();
+        // FCG-1/2 registers at $6000, the counter is written directly
+        cart.mapper.cpu_write(0x6008, 3);
+        cart.mapper.cpu_write(0x6002, 9);
+        cart.mapper.cpu_write(0x800b, 1);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(48));
+        assert!(cart.mapper.cpu_read(0xc000) == Some(240));
+        assert!(cart.mapper.ppu_read(0x0800) == 9);
+        assert!(cart.save_data().is_none());
+        cart.mapper.cpu_write(0x600b, 2);
+        cart.mapper.cpu_write(0x600a, 1);
+        cart.mapper.clock();
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+        cart.mapper.cpu_write(0x600a, 0);
+        assert!(!cart.mapper.irq());
+    }
+    #[test]
+    fn test_lz93d50_irq_latch() {
+        let mut cart = Cartridge::from_bytes(&with_submapper(rom(16, 16, 32), 5)).unwrap();
+        cart.mapper.cpu_write(0x800b, 3);
+        cart.mapper.cpu_write(0x800c, 0);
+        // the latch is copied on enable
+        cart.mapper.cpu_write(0x800a, 1);
+        for _ in 0..2 { cart.mapper.clock() }
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+    }
+    #[test]
+    fn test_bandai_eeprom_bit() {
+        let mut cart = Cartridge::from_bytes(&rom(159, 2, 1)).unwrap();
+        // the other bits are left to the open bus
+        assert!(cart.mapper.cpu_read(0x6000).unwrap() & 0xef == 0);
+        assert!(cart.mapper.driven_bits(0x6000) == 0x10 && cart.mapper.driven_bits(0x8000) == 0xff);
+    }
+    #[test]
+    fn test_bandai_small_prg() {
+        let mut cart = Cartridge::from_bytes(&with_submapper(with_small_prg(rom(16, 1, 1), 0x2000), 4)).unwrap();
+        cart.mapper.cpu_write(0x8008, 1);
+        assert!(cart.mapper.cpu_read(0x8400) == Some(1));
+        assert!(cart.mapper.cpu_read(0xc400) == Some(1));
+        assert!(cart.mapper.cpu_read(0xfc00) == Some(7));
+    }
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_sav_file() {
+        extern crate std;
+        use std::path::Path;
+        use crate::sav_path;
+
+        assert!(sav_path(Path::new("games/zelda.nes")) == Path::new("games/zelda.sav"));
+        let path = std::env::temp_dir().join(std::format!("unes_test_{}.sav", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut cart = battery_rom(0);
+        assert!(!cart.load_sav(&path).unwrap());
+        assert!(!cart.flush_sav(&path).unwrap());
+        cart.mapper.cpu_write(0x6000, 0x99);
+        assert!(cart.flush_sav(&path).unwrap());
+        assert!(!cart.save_dirty());
+
+        let mut cart = battery_rom(0);
+        assert!(cart.load_sav(&path).unwrap());
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0x99));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use unes_state::{Chunk, State, StateWriter};
+
+    use crate::Cartridge;
+    use crate::tests::rom;
+
+    fn save(cart: &Cartridge) -> alloc::vec::Vec<u8> {
+        let mut w = StateWriter::new();
+        w.chunk(Chunk::CARTRIDGE, |w| cart.mapper.save(w));
+        w.finish()
+    }
+    fn load(cart: &mut Cartridge, data: &[u8]) {
+        let state = State::parse(data).unwrap();
+        cart.mapper.load(&mut state.chunk(Chunk::CARTRIDGE).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_vrc6_state() {
+        let mut cart = Cartridge::from_bytes(&rom(24, 16, 32)).unwrap();
+        cart.mapper.cpu_write(0x8000, 3);
+        cart.mapper.cpu_write(0xd000, 9);
+        // prg ram enabled
+        cart.mapper.cpu_write(0xb003, 0x80);
+        cart.mapper.cpu_write(0x6000, 0x5a);
+        // irq every cpu cycle from $fe
+        cart.mapper.cpu_write(0xf000, 0xfe);
+        cart.mapper.cpu_write(0xf001, 0x06);
+        cart.mapper.clock();
+        let state = save(&cart);
+        cart.mapper.cpu_write(0x8000, 0);
+        cart.mapper.cpu_write(0xd000, 0);
+        cart.mapper.cpu_write(0x6000, 0);
+        cart.mapper.cpu_write(0xf001, 0);
+        load(&mut cart, &state);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(48));
+        assert!(cart.mapper.ppu_read(0x0000) == 9);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0x5a));
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+    }
+    #[test]
+    fn test_chr_ram_and_four_screen() {
+        let mut bytes = rom(0, 1, 0);
+        bytes[6] |= 0x08;
+        let mut cart = Cartridge::from_bytes(&bytes).unwrap();
+        cart.mapper.ppu_write(0x0123, 0x11);
+        cart.mapper.nametable_write(0x2c00, 0x22, &mut [0; 0x800]);
+        let state = save(&cart);
+        let mut other = Cartridge::from_bytes(&bytes).unwrap();
+        load(&mut other, &state);
+        assert!(other.mapper.ppu_read(0x0123) == 0x11);
+        assert!(other.mapper.nametable_read(0x2c00, &[0; 0x800]) == 0x22);
+    }
+}
\ No newline at end of file
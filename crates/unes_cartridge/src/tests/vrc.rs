@@ -0,0 +1,253 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Cartridge, Mirroring};
+    use crate::tests::{rom, with_small_prg, with_submapper};
+
+    fn cart(mapper: u8, submapper: u8) -> Cartridge {
+        // 256KB prg, 256KB chr
+        Cartridge::from_bytes(&with_submapper(rom(mapper, 16, 32), submapper)).unwrap()
+    }
+
+    #[test]
+    fn test_vrc4_prg_banks() {
+        let mut cart = cart(23, 1);
+        cart.mapper.cpu_write(0x8000, 5);
+        cart.mapper.cpu_write(0xa000, 6);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(40));
+        assert!(cart.mapper.cpu_read(0xa000) == Some(48));
+        assert!(cart.mapper.cpu_read(0xc000) == Some(240));
+        assert!(cart.mapper.cpu_read(0xe000) == Some(248));
+    }
+    #[test]
+    fn test_vrc4_prg_swap() {
+        let mut cart = cart(23, 1);
+        cart.mapper.cpu_write(0x8000, 5);
+        cart.mapper.cpu_write(0x9002, 2);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(240));
+        assert!(cart.mapper.cpu_read(0xc000) == Some(40));
+    }
+    #[test]
+    fn test_vrc4_chr_nibbles() {
+        let mut cart = cart(23, 1);
+        // bank 7: 0x13
+        cart.mapper.cpu_write(0xe002, 0x03);
+        cart.mapper.cpu_write(0xe003, 0x01);
+        assert!(cart.mapper.ppu_read(0x1c00) == 0x13);
+    }
+    #[test]
+    fn test_vrc4a_wiring() {
+        // register select on A1 and A2
+        let mut cart = cart(21, 1);
+        cart.mapper.cpu_write(0xb000, 0x03);
+        cart.mapper.cpu_write(0xb002, 0x01);
+        assert!(cart.mapper.ppu_read(0x0000) == 0x13);
+        cart.mapper.cpu_write(0xb004, 0x08);
+        assert!(cart.mapper.ppu_read(0x0400) == 0x08);
+    }
+    #[test]
+    fn test_vrc4c_wiring() {
+        // register select on A6 and A7
+        let mut cart = cart(21, 2);
+        cart.mapper.cpu_write(0xb040, 0x01);
+        cart.mapper.cpu_write(0xb080, 0x02);
+        assert!(cart.mapper.ppu_read(0x0000) == 0x10);
+        assert!(cart.mapper.ppu_read(0x0400) == 0x02);
+    }
+    #[test]
+    fn test_vrc4_unknown_submapper() {
+        // both VRC4e (A2, A3) and VRC4f (A0, A1) wirings are decoded
+        let mut cart = cart(23, 0);
+        cart.mapper.cpu_write(0xb001, 0x01);
+        assert!(cart.mapper.ppu_read(0x0000) == 0x10);
+        cart.mapper.cpu_write(0xb004, 0x02);
+        assert!(cart.mapper.ppu_read(0x0000) == 0x20);
+    }
+    #[test]
+    fn test_vrc4_mirroring() {
+        let mut cart = cart(25, 1);
+        assert!(cart.mapper.mirroring() == Mirroring::Vertical);
+        cart.mapper.cpu_write(0x9000, 1);
+        assert!(cart.mapper.mirroring() == Mirroring::Horizontal);
+        cart.mapper.cpu_write(0x9000, 2);
+        assert!(cart.mapper.mirroring() == Mirroring::SingleScreenA);
+        cart.mapper.cpu_write(0x9000, 3);
+        assert!(cart.mapper.mirroring() == Mirroring::SingleScreenB);
+    }
+    #[test]
+    fn test_vrc4_irq_cycle_mode() {
+        let mut cart = cart(23, 1);
+        cart.mapper.cpu_write(0xf000, 0x0e);
+        cart.mapper.cpu_write(0xf001, 0x0f);
+        cart.mapper.cpu_write(0xf002, 0b110);
+        cart.mapper.clock();
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+        // acknowledging disables the counter unless E was set
+        cart.mapper.cpu_write(0xf003, 0);
+        assert!(!cart.mapper.irq());
+        for _ in 0..0x200 { cart.mapper.clock() }
+        assert!(!cart.mapper.irq());
+    }
+    #[test]
+    fn test_vrc4_irq_scanline_mode() {
+        let mut cart = cart(23, 1);
+        cart.mapper.cpu_write(0xf000, 0x0e);
+        cart.mapper.cpu_write(0xf001, 0x0f);
+        cart.mapper.cpu_write(0xf002, 0b011);
+        // two scanlines, 341 ppu dots each
+        for _ in 0..227 { cart.mapper.clock() }
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+        // E was set, so the counter keeps running from the latch,
+        // the prescaler carries over the fractional part
+        cart.mapper.cpu_write(0xf003, 0);
+        for _ in 0..226 { cart.mapper.clock() }
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+    }
+    #[test]
+    fn test_vrc_small_prg() {
+        // 8KB prg as a NES 2.0 exponent size, a single bank for every window
+        for mapper in [23, 24] {
+            let mut cart = Cartridge::from_bytes(&with_submapper(with_small_prg(rom(mapper, 1, 32), 0x2000), 1)).unwrap();
+            assert!(cart.mapper.cpu_read(0x8400) == Some(1));
+            assert!(cart.mapper.cpu_read(0xa800) == Some(2));
+            assert!(cart.mapper.cpu_read(0xc000) == Some(0));
+            assert!(cart.mapper.cpu_read(0xfc00) == Some(7));
+        }
+    }
+    #[test]
+    fn test_vrc2a() {
+        let mut cart = cart(22, 0);
+        // chr banks drop the lowest bit
+        cart.mapper.cpu_write(0xb000, 6);
+        assert!(cart.mapper.ppu_read(0x0000) == 3);
+        // A0 and A1 are swapped
+        cart.mapper.cpu_write(0xb001, 4);
+        assert!(cart.mapper.ppu_read(0x0400) == 2);
+        // only one mirroring bit
+        cart.mapper.cpu_write(0x9000, 2);
+        assert!(cart.mapper.mirroring() == Mirroring::Vertical);
+        // no irq
+        cart.mapper.cpu_write(0xf002, 0b110);
+        for _ in 0..0x200 { cart.mapper.clock() }
+        assert!(!cart.mapper.irq());
+    }
+    #[test]
+    fn test_vrc2_latch() {
+        let mut cart = cart(22, 0);
+        cart.mapper.cpu_write(0x6000, 0xff);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(1));
+        assert!(cart.mapper.cpu_read(0x7000).is_none());
+    }
+    #[test]
+    fn test_vrc6_prg_banks() {
+        let mut cart = cart(24, 0);
+        cart.mapper.cpu_write(0x8000, 3);
+        cart.mapper.cpu_write(0xc000, 7);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(48));
+        assert!(cart.mapper.cpu_read(0xa000) == Some(56));
+        assert!(cart.mapper.cpu_read(0xc000) == Some(56));
+        assert!(cart.mapper.cpu_read(0xe000) == Some(248));
+    }
+    #[test]
+    fn test_vrc6_chr_modes() {
+        let mut cart = cart(24, 0);
+        for i in 0..4 {
+            cart.mapper.cpu_write(0xd000 + i, 10 + i as u8);
+            cart.mapper.cpu_write(0xe000 + i, 20 + i as u8);
+        }
+        assert!(cart.mapper.ppu_read(0x0400) == 11);
+        assert!(cart.mapper.ppu_read(0x1c00) == 23);
+        // 2KB banks with A10 from the ppu
+        cart.mapper.cpu_write(0xb003, 0x21);
+        assert!(cart.mapper.ppu_read(0x0000) == 10);
+        assert!(cart.mapper.ppu_read(0x0400) == 11);
+        assert!(cart.mapper.ppu_read(0x1c00) == 13);
+        // mixed 1KB and 2KB banks
+        cart.mapper.cpu_write(0xb003, 0x22);
+        assert!(cart.mapper.ppu_read(0x0c00) == 13);
+        assert!(cart.mapper.ppu_read(0x1800) == 20);
+        assert!(cart.mapper.ppu_read(0x1c00) == 21);
+    }
+    #[test]
+    fn test_vrc6b_swapped_lines() {
+        let mut cart = cart(26, 0);
+        cart.mapper.cpu_write(0xd001, 7);
+        assert!(cart.mapper.ppu_read(0x0800) == 7);
+    }
+    #[test]
+    fn test_vrc6_mirroring_and_ram() {
+        let mut cart = cart(24, 0);
+        cart.mapper.cpu_write(0xb003, 0x24);
+        assert!(cart.mapper.mirroring() == Mirroring::Horizontal);
+        cart.mapper.cpu_write(0xb003, 0x2c);
+        assert!(cart.mapper.mirroring() == Mirroring::SingleScreenB);
+
+        let mut cart = Cartridge::from_bytes(&rom(24, 16, 32)).unwrap();
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000).is_none());
+        cart.mapper.cpu_write(0xb003, 0x80);
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0x12));
+    }
+    #[test]
+    fn test_vrc6_irq() {
+        let mut cart = cart(24, 0);
+        cart.mapper.cpu_write(0xf000, 0xfd);
+        cart.mapper.cpu_write(0xf001, 0b110);
+        for _ in 0..2 { cart.mapper.clock() }
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+        cart.mapper.cpu_write(0xf002, 0);
+        assert!(!cart.mapper.irq());
+    }
+    #[test]
+    fn test_vrc6_pulse_digitized() {
+        let mut cart = cart(24, 0);
+        cart.mapper.cpu_write(0x9000, 0x8f);
+        cart.mapper.cpu_write(0x9002, 0x80);
+        let level = cart.mapper.audio().unwrap().channel_output(0);
+        assert!(level > 0.2 && level < 0.25);
+    }
+    #[test]
+    fn test_vrc6_pulse_duty() {
+        let mut cart = cart(26, 0);
+        // duty 7 is 8/16 high, on mapper 26 $a001 is the enable register
+        cart.mapper.cpu_write(0xa000, 0x7f);
+        cart.mapper.cpu_write(0xa001, 0x80);
+        let high = (0..160).filter(|_| {
+            cart.mapper.clock();
+            cart.mapper.audio().unwrap().channel_output(1) > 0.0
+        }).count();
+        assert!(high == 80);
+    }
+    #[test]
+    fn test_vrc6_sawtooth() {
+        let mut cart = cart(24, 0);
+        cart.mapper.cpu_write(0xb000, 42);
+        cart.mapper.cpu_write(0xb002, 0x80);
+        let levels = (0..14).map(|_| {
+            cart.mapper.clock();
+            cart.mapper.audio().unwrap().channel_output(2)
+        }).collect::<alloc::vec::Vec<_>>();
+        // 6 accumulations of 42, output is the top 5 bits
+        let max = levels.iter().fold(0.0f32, |a, &b| a.max(b));
+        assert!(max == levels[12]);
+        assert!(levels[12] > 0.46 && levels[12] < 0.47);
+        assert!(levels[13] == 0.0);
+    }
+    #[test]
+    fn test_vrc6_halt() {
+        let mut cart = cart(24, 0);
+        cart.mapper.cpu_write(0xb000, 42);
+        cart.mapper.cpu_write(0xb002, 0x80);
+        cart.mapper.cpu_write(0x9003, 1);
+        for _ in 0..4 { cart.mapper.clock() }
+        assert!(cart.mapper.audio().unwrap().channel_output(2) == 0.0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use crate::{Cartridge, Mirroring};
+    use crate::tests::{rom, with_small_prg, with_submapper};
+
+    fn cart(submapper: u8) -> Cartridge {
+        Cartridge::from_bytes(&with_submapper(rom(85, 16, 32), submapper)).unwrap()
+    }
+    fn write_sound(cart: &mut Cartridge, reg: u8, value: u8) {
+        cart.mapper.cpu_write(0x9010, reg);
+        cart.mapper.cpu_write(0x9030, value);
+    }
+    // one output per fm sample
+    fn samples(cart: &mut Cartridge, channel: usize, count: usize) -> Vec<f32> {
+        (0..count).map(|_| {
+            for _ in 0..36 { cart.mapper.clock() }
+            cart.mapper.audio().unwrap().channel_output(channel)
+        }).collect()
+    }
+    // custom instrument: silent modulator, carrier is a sustained sine with instant attack
+    fn sine(cart: &mut Cartridge, volume: u8) {
+        for (reg, value) in [0x20, 0x21, 0x3f, 0x00, 0x00, 0xf0, 0x00, 0x00].into_iter().enumerate() {
+            write_sound(cart, reg as u8, value);
+        }
+        write_sound(cart, 0x30, volume);
+        write_sound(cart, 0x10, 0x00);
+        // fnum 256, block 4: a 128 sample period
+        write_sound(cart, 0x20, 0x19);
+    }
+
+    #[test]
+    fn test_vrc7_prg_banks() {
+        let mut cart = cart(2);
+        cart.mapper.cpu_write(0x8000, 1);
+        cart.mapper.cpu_write(0x8010, 2);
+        cart.mapper.cpu_write(0x9000, 3);
+        assert!(cart.mapper.cpu_read(0x8000) == Some(8));
+        assert!(cart.mapper.cpu_read(0xa000) == Some(16));
+        assert!(cart.mapper.cpu_read(0xc000) == Some(24));
+        assert!(cart.mapper.cpu_read(0xe000) == Some(248));
+    }
+    #[test]
+    fn test_vrc7b_wiring() {
+        let mut cart = cart(1);
+        cart.mapper.cpu_write(0x8008, 2);
+        assert!(cart.mapper.cpu_read(0xa000) == Some(16));
+        cart.mapper.cpu_write(0xa008, 5);
+        assert!(cart.mapper.ppu_read(0x0400) == 5);
+    }
+    #[test]
+    fn test_vrc7_chr_banks() {
+        let mut cart = cart(0);
+        for i in 0..8u16 {
+            cart.mapper.cpu_write(0xa000 + (i / 2) * 0x1000 + (i & 1) * 0x10, 100 + i as u8);
+        }
+        for i in 0..8u16 {
+            assert!(cart.mapper.ppu_read(i * 0x400) == 100 + i as u8);
+        }
+    }
+    #[test]
+    fn test_vrc7_mirroring_and_ram() {
+        let mut cart = Cartridge::from_bytes(&rom(85, 16, 32)).unwrap();
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000).is_none());
+        cart.mapper.cpu_write(0xe000, 0x81);
+        assert!(cart.mapper.mirroring() == Mirroring::Horizontal);
+        cart.mapper.cpu_write(0x6000, 0x12);
+        assert!(cart.mapper.cpu_read(0x6000) == Some(0x12));
+        cart.mapper.cpu_write(0xe000, 0x03);
+        assert!(cart.mapper.mirroring() == Mirroring::SingleScreenB);
+    }
+    #[test]
+    fn test_vrc7_irq() {
+        let mut cart = cart(0);
+        cart.mapper.cpu_write(0xe010, 0xfe);
+        cart.mapper.cpu_write(0xf000, 0b110);
+        cart.mapper.clock();
+        assert!(!cart.mapper.irq());
+        cart.mapper.clock();
+        assert!(cart.mapper.irq());
+        cart.mapper.cpu_write(0xf010, 0);
+        assert!(!cart.mapper.irq());
+    }
+    #[test]
+    fn test_vrc7_sine_frequency() {
+        let mut cart = cart(0);
+        sine(&mut cart, 0);
+        let levels = samples(&mut cart, 0, 128 * 10 + 1);
+        let rising = levels.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+        assert!(rising == 10);
+    }
+    #[test]
+    fn test_vrc7_volume() {
+        let peak = |volume| {
+            let mut cart = cart(0);
+            sine(&mut cart, volume);
+            samples(&mut cart, 0, 256).into_iter().fold(0.0f32, f32::max)
+        };
+        // 3dB per step
+        let ratio = peak(1) / peak(0);
+        assert!(ratio > 0.68 && ratio < 0.73);
+        assert!(peak(15) < peak(0) / 150.0);
+    }
+    #[test]
+    fn test_vrc7_key_off() {
+        let mut cart = cart(0);
+        // built-in instrument 3, sustain on
+        write_sound(&mut cart, 0x31, 0x30);
+        write_sound(&mut cart, 0x11, 0x80);
+        write_sound(&mut cart, 0x21, 0x38);
+        assert!(samples(&mut cart, 1, 2000).iter().any(|&l| l != 0.0));
+        assert!(samples(&mut cart, 0, 1).iter().all(|&l| l == 0.0));
+        write_sound(&mut cart, 0x21, 0x08);
+        samples(&mut cart, 1, 50_000);
+        assert!(samples(&mut cart, 1, 200).iter().all(|&l| l == 0.0));
+    }
+    #[test]
+    fn test_vrc7_silence() {
+        let mut cart = cart(0);
+        sine(&mut cart, 0);
+        samples(&mut cart, 0, 10);
+        cart.mapper.cpu_write(0xe000, 0x40);
+        assert!(samples(&mut cart, 0, 200).iter().all(|&l| l == 0.0));
+    }
+    #[test]
+    fn test_vrc7_small_prg() {
+        let mut cart = Cartridge::from_bytes(&with_small_prg(rom(85, 1, 32), 0x1000)).unwrap();
+        cart.mapper.cpu_write(0x8000, 3);
+        assert!(cart.mapper.cpu_read(0x8400) == Some(1));
+        assert!(cart.mapper.cpu_read(0xfc00) == Some(3));
+    }
+}
\ No newline at end of file
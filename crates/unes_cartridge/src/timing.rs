@@ -0,0 +1,19 @@
+// the console a dump was made for, NES 2.0 byte 12
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timing {
+    Ntsc,
+    Pal,
+    // runs on either
+    MultiRegion,
+    Dendy
+}
+impl Timing {
+    pub fn from_nes2(value: u8) -> Timing {
+        match value & 3 {
+            0 => Timing::Ntsc,
+            1 => Timing::Pal,
+            2 => Timing::MultiRegion,
+            _ => Timing::Dendy
+        }
+    }
+}
\ No newline at end of file
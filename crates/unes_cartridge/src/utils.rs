@@ -0,0 +1,22 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::header::Header;
+
+// byte offset of a bank within a chip of `len` bytes,
+// out of range banks wrap around like unconnected address lines
+pub fn bank_offset(bank: usize, size: usize, len: usize) -> usize {
+    if len == 0 { return 0 }
+    (bank * size) % len
+}
+// chip offset of `addr` within a bank of `size` (a power of two) bytes,
+// a chip smaller than the bank is mirrored across it
+pub fn bank_addr(bank: usize, size: usize, addr: u16, len: usize) -> usize {
+    if len == 0 { return 0 }
+    (bank_offset(bank, size, len) + (addr as usize & (size - 1))) % len
+}
+// pattern table memory: the chr rom, or ram sized from the header when there is none
+pub fn chr_memory(header: &Header, chr_rom: Vec<u8>) -> Vec<u8> {
+    if !chr_rom.is_empty() { return chr_rom }
+    vec![0; header.chr_ram_len()]
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use crate::error::Error;
+
+// Command line of a subcommand: positional arguments, `--name value` options
+// and `--name` flags. Anything the subcommand does not know about is an error.
+pub struct Args {
+    pub positional: Vec<String>,
+    options: Vec<(String, Option<String>)>
+}
+impl Args {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I, options: &[&str], flags: &[&str]) -> Result<Args, Error> {
+        let mut parsed = Args { positional: Vec::new(), options: Vec::new() };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg);
+                continue
+            };
+            // --name=value works as well
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (name, None)
+            };
+            if options.contains(&name) {
+                let value = match value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(Error::Usage(format!("--{} needs a value", name)))
+                };
+                parsed.options.push((name.to_string(), Some(value)));
+            } else if flags.contains(&name) && value.is_none() {
+                parsed.options.push((name.to_string(), None));
+            } else {
+                return Err(Error::Usage(format!("unknown option --{}", name)))
+            }
+        }
+        Ok(parsed)
+    }
+    // the last one given wins
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options.iter().rev().find(|(option, _)| option == name).and_then(|(_, value)| value.as_deref())
+    }
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+    pub fn number(&self, name: &str) -> Result<Option<u64>, Error> {
+        match self.value(name) {
+            Some(value) => match parse_number(value) {
+                Some(number) => Ok(Some(number)),
+                None => Err(Error::Usage(format!("--{} expects a number, got {}", name, value)))
+            },
+            None => Ok(None)
+        }
+    }
+    // the only positional argument, a file
+    pub fn file(&self, what: &str) -> Result<&str, Error> {
+        match &self.positional[..] {
+            [file] => Ok(file),
+            [] => Err(Error::Usage(format!("missing {}", what))),
+            [_, extra, ..] => Err(Error::Usage(format!("unexpected argument {}", extra)))
+        }
+    }
+}
+
+// decimal, or hex with a 0x or $ prefix
+pub fn parse_number(value: &str) -> Option<u64> {
+    if let Some(hex) = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")).or_else(|| value.strip_prefix('$')) {
+        u64::from_str_radix(hex, 16).ok()
+    } else {
+        value.parse().ok()
+    }
+}
\ No newline at end of file
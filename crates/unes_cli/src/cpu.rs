@@ -0,0 +1,72 @@
+use unes_cpu::{CPU, instruction_size};
+
+use crate::args::Args;
+use crate::error::Error;
+use crate::read;
+
+// the easy6502 default
+const DEFAULT_ORG: u64 = 0x0600;
+const DEFAULT_STEPS: u64 = 1_000_000;
+
+pub const USAGE: &str = "unes cpu <prog.bin> [--org 0x0600] [--steps N] [--trace]";
+
+// runs a bare 6502 program on 64KB of ram until BRK
+pub fn cpu(args: Vec<String>) -> Result<(), Error> {
+    let args = Args::parse(args, &["org", "steps"], &["trace"])?;
+    let path = args.file("program")?;
+    let program = read(path)?;
+    let org = args.number("org")?.unwrap_or(DEFAULT_ORG);
+    if org > 0xffff || org + program.len() as u64 > 0x10000 {
+        return Err(Error::Usage(format!("{} does not fit at ${:04X}", path, org)))
+    }
+    let steps = args.number("steps")?.unwrap_or(DEFAULT_STEPS);
+    let trace = args.flag("trace");
+
+    let mut cpu = load(&program, org as u16);
+    if !run(&mut cpu, steps, trace) {
+        eprintln!("stopped after {} instructions without reaching BRK", steps);
+    }
+    println!("{}", registers(&cpu));
+    Ok(())
+}
+
+// 64KB of ram holding `program` at `org`, which has to fit below $10000
+pub fn load(program: &[u8], org: u16) -> CPU {
+    let mut cpu = CPU::new();
+    for (i, &byte) in program.iter().enumerate() {
+        cpu.memory.write(org + i as u16, byte);
+    }
+    cpu.pc = org;
+    cpu.running = true;
+    cpu.halt_on_brk = true;
+    cpu
+}
+
+// runs up to `steps` instructions, false when BRK was not reached
+pub fn run(cpu: &mut CPU, steps: u64, trace: bool) -> bool {
+    let mut step = 0;
+    while cpu.running && step < steps {
+        if trace { println!("{}", trace_line(cpu)) }
+        cpu.step();
+        step += 1;
+    }
+    !cpu.running
+}
+
+// the instruction about to run and the registers before it:
+// 0600  A9 01     A:00 X:00 Y:00 P:30 SP:FF CYC:0
+pub fn trace_line(cpu: &CPU) -> String {
+    let code = cpu.memory.read(cpu.pc);
+    let bytes = (0..instruction_size(code))
+        .map(|i| format!("{:02X}", cpu.memory.read(cpu.pc.wrapping_add(i))))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{:04X}  {:<8}  {}", cpu.pc, bytes, registers(cpu))
+}
+
+fn registers(cpu: &CPU) -> String {
+    format!(
+        "A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PC:{:04X} CYC:{}",
+        cpu.reg_a, cpu.reg_x, cpu.reg_y, cpu.status, cpu.sp, cpu.pc, cpu.cycles
+    )
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use std::fmt;
+use std::io;
+
+use unes_cartridge::CartridgeError;
+
+use crate::fm2::MovieError;
+
+pub enum Error {
+    // bad command line, the usage is printed after it
+    Usage(String),
+    Io(String, io::Error),
+    Cartridge(CartridgeError),
+    Movie(String, MovieError)
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(message) => write!(f, "{}", message),
+            Self::Io(path, error) => write!(f, "{}: {}", path, error),
+            Self::Cartridge(error) => write!(f, "{}", error),
+            Self::Movie(path, error) => write!(f, "{}: {}", path, error)
+        }
+    }
+}
+impl From<CartridgeError> for Error {
+    fn from(error: CartridgeError) -> Self {
+        Error::Cartridge(error)
+    }
+}
\ No newline at end of file
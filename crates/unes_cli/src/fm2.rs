@@ -0,0 +1,99 @@
+use std::fmt;
+
+// input of a frame, the commands are a bit set of these
+pub const COMMAND_RESET: u8 = 1;
+pub const COMMAND_POWER: u8 = 2;
+
+// fm2 port types
+const PORT_NONE: u8 = 0;
+const PORT_GAMEPAD: u8 = 1;
+const PORT_ZAPPER: u8 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovieError {
+    // the input is stored after the header as binary records
+    Binary,
+    Zapper,
+    UnknownPort(u8),
+    // line number
+    InvalidLine(usize)
+}
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Binary => write!(f, "binary movies are not supported"),
+            Self::Zapper => write!(f, "zapper input is not supported"),
+            Self::UnknownPort(port) => write!(f, "unknown port device {}", port),
+            Self::InvalidLine(line) => write!(f, "invalid input on line {}", line)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Frame {
+    pub commands: u8,
+    // player 1 to 4, in the `unes::input::joypad` bit order
+    pub buttons: [u8; 4]
+}
+
+// An FCEUX text movie: `key value` header lines, then one `|commands|port0|port1|port2|`
+// line per frame. A gamepad is 8 characters, RLDUTSBA, a '.' or ' ' for
+// a button that is up. With the Four Score there are four gamepads before port 2.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Movie {
+    pub pal: bool,
+    pub fourscore: bool,
+    pub ports: [bool; 2],
+    pub frames: Vec<Frame>
+}
+impl Movie {
+    pub fn parse(text: &str) -> Result<Movie, MovieError> {
+        let mut movie = Movie { ports: [true; 2], ..Movie::default() };
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if let Some(fields) = line.strip_prefix('|') {
+                let frame = movie.parse_frame(fields).ok_or(MovieError::InvalidLine(i + 1))?;
+                movie.frames.push(frame);
+                continue
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            match key {
+                "palFlag" => movie.pal = value == "1",
+                "fourscore" => movie.fourscore = value == "1",
+                "binary" if value == "1" => return Err(MovieError::Binary),
+                "port0" | "port1" => {
+                    let port = (key == "port1") as usize;
+                    movie.ports[port] = match value.parse().map_err(|_| MovieError::InvalidLine(i + 1))? {
+                        PORT_NONE => false,
+                        PORT_GAMEPAD => true,
+                        PORT_ZAPPER => return Err(MovieError::Zapper),
+                        other => return Err(MovieError::UnknownPort(other))
+                    };
+                },
+                _ => ()
+            }
+        }
+        Ok(movie)
+    }
+    // the commands and gamepads, port 2 (the expansion port) is ignored
+    fn parse_frame(&self, fields: &str) -> Option<Frame> {
+        let mut fields = fields.split('|');
+        let mut frame = Frame { commands: fields.next()?.trim().parse().ok()?, buttons: [0; 4] };
+        let players = if self.fourscore { 4 } else { 2 };
+        for player in 0..players {
+            let field = fields.next()?;
+            if !self.fourscore && !self.ports[player] { continue }
+            frame.buttons[player] = parse_gamepad(field)?;
+        }
+        Some(frame)
+    }
+}
+
+fn parse_gamepad(field: &str) -> Option<u8> {
+    if field.len() != 8 { return None }
+    Some(field.bytes().enumerate().fold(0, |buttons, (i, c)| match c {
+        b'.' | b' ' => buttons,
+        _ => buttons | 0x80 >> i
+    }))
+}
\ No newline at end of file
@@ -0,0 +1,41 @@
+use unes_cartridge::{Cartridge, CartridgeError, Header};
+
+use crate::args::Args;
+use crate::error::Error;
+use crate::read;
+
+pub const USAGE: &str = "unes info <rom>";
+
+// prints what the header says about a rom and whether its mapper is supported
+pub fn info(args: Vec<String>) -> Result<(), Error> {
+    let args = Args::parse(args, &[], &[])?;
+    let rom = read(args.file("rom")?)?;
+    let header = Header::parse(&rom)?;
+    let supported = match Cartridge::from_bytes(&rom) {
+        Ok(_) => "",
+        Err(CartridgeError::UnsupportedMapper(_)) => " (not supported)",
+        Err(error) => return Err(error.into())
+    };
+    println!("format:    {}", if header.nes2 { "NES 2.0" } else { "iNES" });
+    println!("mapper:    {}.{}{}", header.mapper, header.submapper, supported);
+    println!("prg rom:   {}", size(header.prg_rom_size));
+    println!("chr rom:   {}", size(header.chr_rom_size));
+    if header.chr_rom_size == 0 {
+        println!("chr ram:   {}", size(header.chr_ram_len()));
+    }
+    println!("prg ram:   {}", size(header.prg_ram_size));
+    println!("prg nvram: {}", size(header.prg_nvram_size));
+    println!("mirroring: {:?}", header.mirroring);
+    println!("battery:   {}", if header.battery { "yes" } else { "no" });
+    println!("trainer:   {}", if header.trainer { "yes" } else { "no" });
+    println!("timing:    {:?}", header.timing);
+    Ok(())
+}
+
+fn size(bytes: usize) -> String {
+    match bytes {
+        0 => "none".to_string(),
+        bytes if bytes % 1024 == 0 => format!("{}KB", bytes / 1024),
+        bytes => format!("{} bytes", bytes)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+mod args;
+mod cpu;
+mod error;
+mod fm2;
+mod info;
+mod output;
+mod run;
+mod tests;
+
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use crate::error::Error;
+
+// headless frontend, for scripts and ci
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+    let result = match args.next().as_deref() {
+        Some("run") => run::run(args.collect()),
+        Some("info") => info::info(args.collect()),
+        Some("cpu") => cpu::cpu(args.collect()),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", usage());
+            return ExitCode::SUCCESS
+        },
+        Some(command) => Err(Error::Usage(format!("unknown command {}", command))),
+        None => Err(Error::Usage("missing command".to_string()))
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error @ Error::Usage(_)) => {
+            eprintln!("unes: {}\n\n{}", error, usage());
+            ExitCode::from(2)
+        },
+        Err(error) => {
+            eprintln!("unes: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage() -> String {
+    format!("usage:\n  {}\n  {}\n  {}", run::USAGE, info::USAGE, cpu::USAGE)
+}
+
+pub fn read(path: &str) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|error| Error::Io(path.to_string(), error))
+}
+pub fn write(path: &str, bytes: &[u8]) -> Result<(), Error> {
+    fs::write(path, bytes).map_err(|error| Error::Io(path.to_string(), error))
+}
\ No newline at end of file
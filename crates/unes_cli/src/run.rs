@@ -0,0 +1,90 @@
+use unes::audio::SAMPLE_RATE;
+use unes::input::{Device, FourScore, Joypad};
+use unes::{Console, Region};
+use unes_ppu::{HEIGHT, WIDTH};
+
+use crate::args::Args;
+use crate::error::Error;
+use crate::fm2::{COMMAND_POWER, COMMAND_RESET, Movie};
+use crate::output::{ppm, wav};
+use crate::{read, write};
+
+// frames run without a movie or --frames
+const DEFAULT_FRAMES: u64 = 60;
+
+pub const USAGE: &str = "unes run <rom> [--frames N] [--input movie.fm2] [--region ntsc|pal|dendy] [--screenshot out.ppm] [--wav out.wav]";
+
+// runs a game headless, optionally driven by a movie, and dumps the last frame and the audio
+pub fn run(args: Vec<String>) -> Result<(), Error> {
+    let args = Args::parse(args, &["frames", "input", "region", "screenshot", "wav"], &[])?;
+    let rom = read(args.file("rom")?)?;
+    let movie = match args.value("input") {
+        Some(path) => {
+            let text = String::from_utf8_lossy(&read(path)?).into_owned();
+            Some(Movie::parse(&text).map_err(|error| Error::Movie(path.to_string(), error))?)
+        },
+        None => None
+    };
+    let region = match args.value("region") {
+        Some(name) => Some(parse_region(name)?),
+        None => movie.as_ref().filter(|movie| movie.pal).map(|_| Region::Pal)
+    };
+    let frames = match args.number("frames")? {
+        Some(frames) => frames,
+        None => movie.as_ref().map_or(DEFAULT_FRAMES, |movie| movie.frames.len() as u64)
+    };
+
+    let mut console = power_on(&rom, region, movie.as_ref())?;
+    let mut samples = Vec::new();
+    for frame in 0..frames {
+        if let Some(input) = movie.as_ref().and_then(|movie| movie.frames.get(frame as usize)) {
+            if input.commands & COMMAND_POWER != 0 {
+                console = power_on(&rom, region, movie.as_ref())?;
+            } else if input.commands & COMMAND_RESET != 0 {
+                console.reset();
+            }
+            for (player, &buttons) in input.buttons.iter().enumerate() {
+                console.set_input(player, buttons);
+            }
+        }
+        console.run_frame();
+        // taken every frame so a long run does not keep it all twice
+        samples.extend(console.cpu.memory.audio.take_i16());
+    }
+
+    if let Some(path) = args.value("screenshot") {
+        write(path, &ppm(WIDTH, HEIGHT, &console.framebuffer_rgb()))?;
+    }
+    if let Some(path) = args.value("wav") {
+        write(path, &wav(&samples, SAMPLE_RATE))?;
+    }
+    println!("{} frames, {} cpu cycles", frames, console.cpu.cycles);
+    Ok(())
+}
+
+// a fresh console, with the controllers the movie was recorded with
+fn power_on(rom: &[u8], region: Option<Region>, movie: Option<&Movie>) -> Result<Console, Error> {
+    let mut console = Console::from_rom(rom)?;
+    if let Some(region) = region { console.set_region(region) }
+    if let Some(movie) = movie {
+        let input = &mut console.cpu.memory.input;
+        if movie.fourscore {
+            input.ports = [Device::None; 2];
+            input.expansion = Device::FourScore(FourScore::new());
+        } else {
+            for (port, &plugged) in input.ports.iter_mut().zip(movie.ports.iter()) {
+                *port = if plugged { Device::Joypad(Joypad::new()) } else { Device::None };
+            }
+        }
+    }
+    Ok(console)
+}
+
+pub fn parse_region(name: &str) -> Result<Region, Error> {
+    match name.to_ascii_lowercase().as_str() {
+        "ntsc" => Ok(Region::Ntsc),
+        "pal" => Ok(Region::Pal),
+        "dendy" => Ok(Region::Dendy),
+        _ => Err(Error::Usage(format!("unknown region {}", name)))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use crate::args::{Args, parse_number};
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_options() {
+        let parsed = Args::parse(args("game.nes --frames 600 --wav=out.wav --trace"), &["frames", "wav"], &["trace"]).ok().unwrap();
+        assert!(parsed.positional == ["game.nes"]);
+        assert!(parsed.number("frames").ok().unwrap() == Some(600));
+        assert!(parsed.value("wav") == Some("out.wav"));
+        assert!(parsed.flag("trace"));
+        assert!(parsed.value("screenshot").is_none());
+        assert!(parsed.file("rom").ok() == Some("game.nes"));
+    }
+    #[test]
+    fn test_errors() {
+        assert!(Args::parse(args("--frames"), &["frames"], &[]).is_err());
+        assert!(Args::parse(args("--bogus 1"), &["frames"], &[]).is_err());
+        // flags take no value
+        assert!(Args::parse(args("--trace=1"), &[], &["trace"]).is_err());
+        let parsed = Args::parse(args("a b --frames x"), &["frames"], &[]).ok().unwrap();
+        assert!(parsed.file("rom").is_err());
+        assert!(parsed.number("frames").is_err());
+    }
+    #[test]
+    fn test_numbers() {
+        assert!(parse_number("1536") == Some(0x600));
+        assert!(parse_number("0x0600") == Some(0x600));
+        assert!(parse_number("$c000") == Some(0xc000));
+        assert!(parse_number("0x").is_none());
+        assert!(parse_number("-1").is_none());
+    }
+}
\ No newline at end of file
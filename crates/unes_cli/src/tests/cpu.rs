@@ -0,0 +1,24 @@
+#[cfg(test)]
+mod tests {
+    use unes_cpu::CPU;
+
+    use crate::cpu::{load, run, trace_line};
+
+    #[test]
+    fn test_trace_line() {
+        let mut cpu = CPU::new();
+        cpu.load_executable::<6>(0x0600, &[0xa9, 0x01, 0x8d, 0x00, 0x02, 0xe8]);
+        assert!(trace_line(&cpu) == "0600  A9 01     A:00 X:00 Y:00 P:30 SP:FF PC:0600 CYC:0");
+        cpu.step();
+        assert!(trace_line(&cpu) == "0602  8D 00 02  A:01 X:00 Y:00 P:30 SP:FF PC:0602 CYC:2");
+        cpu.step();
+        assert!(trace_line(&cpu).starts_with("0605  E8        A:01"));
+    }
+    #[test]
+    fn test_top_of_memory() {
+        // four nops up to $ffff, then the brk of the empty ram at $0000
+        let mut cpu = load(&[0xea; 4], 0xfffc);
+        assert!(run(&mut cpu, 10, false));
+        assert!(cpu.pc == 0x0001 && cpu.cycles == 8 + 7);
+    }
+}
\ No newline at end of file
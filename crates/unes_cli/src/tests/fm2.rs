@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tests {
+    use unes::input::joypad::*;
+
+    use crate::fm2::{COMMAND_POWER, COMMAND_RESET, Frame, Movie, MovieError};
+
+    const HEADER: &str = "version 3\nemuVersion 22020\npalFlag 0\nromFilename game\nport0 1\nport1 1\nport2 0\n";
+
+    #[test]
+    fn test_gamepads() {
+        let text = format!("{}|2|........|........||\r\n|0|R..U...A|.L..T.B.||\n|1|........|........||\n", HEADER);
+        let movie = Movie::parse(&text).unwrap();
+        assert!(!movie.pal && !movie.fourscore);
+        assert!(movie.frames.len() == 3);
+        assert!(movie.frames[0] == Frame { commands: COMMAND_POWER, buttons: [0; 4] });
+        assert!(movie.frames[1].buttons[0] == BUTTON_RIGHT | BUTTON_UP | BUTTON_A);
+        assert!(movie.frames[1].buttons[1] == BUTTON_LEFT | BUTTON_START | BUTTON_B);
+        assert!(movie.frames[2].commands == COMMAND_RESET);
+    }
+    #[test]
+    fn test_fourscore() {
+        let text = "palFlag 1\nfourscore 1\nport2 0\n|0|........|........|..D.....|.......A||\n";
+        let movie = Movie::parse(text).unwrap();
+        assert!(movie.pal && movie.fourscore);
+        assert!(movie.frames[0].buttons == [0, 0, BUTTON_DOWN, BUTTON_A]);
+    }
+    #[test]
+    fn test_empty_port() {
+        let text = "port0 1\nport1 0\n|0|.......A|||\n";
+        let movie = Movie::parse(text).unwrap();
+        assert!(movie.ports == [true, false]);
+        assert!(movie.frames[0].buttons[0] == BUTTON_A);
+    }
+    #[test]
+    fn test_errors() {
+        assert!(Movie::parse("binary 1\n") == Err(MovieError::Binary));
+        assert!(Movie::parse("port1 2\n") == Err(MovieError::Zapper));
+        assert!(Movie::parse("port1 0\n|0|.......A|||\n|0|ABC|||\n") == Err(MovieError::InvalidLine(3)));
+        assert!(Movie::parse("|x|........|........||\n") == Err(MovieError::InvalidLine(1)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,4 @@
+mod args;
+mod cpu;
+mod fm2;
+mod output;
\ No newline at end of file
@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod tests {
+    use crate::output::{ppm, wav};
+
+    #[test]
+    fn test_ppm() {
+        let image = ppm(2, 1, &[1, 2, 3, 4, 5, 6]);
+        assert!(image == b"P6\n2 1\n255\n\x01\x02\x03\x04\x05\x06");
+    }
+    #[test]
+    fn test_wav() {
+        let sound = wav(&[1, -1], 48_000);
+        assert!(sound.len() == 48);
+        assert!(&sound[0..4] == b"RIFF" && &sound[8..12] == b"WAVE");
+        assert!(sound[4..8] == 40u32.to_le_bytes());
+        assert!(sound[24..28] == 48_000u32.to_le_bytes());
+        assert!(sound[28..32] == 96_000u32.to_le_bytes());
+        assert!(sound[40..44] == 4u32.to_le_bytes());
+        assert!(sound[44..] == [0x01, 0x00, 0xff, 0xff]);
+    }
+}
\ No newline at end of file
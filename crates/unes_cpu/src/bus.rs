@@ -0,0 +1,20 @@
+// everything the cpu reaches through its address and data pins
+pub trait Bus {
+    fn read(&mut self, addr: u16) -> u8;
+    fn write(&mut self, addr: u16, value: u8);
+    fn read_u16(&mut self, addr: u16) -> u16 {
+        u16::from_le_bytes([self.read(addr), self.read(addr.wrapping_add(1))])
+    }
+    // called before every cpu cycle, with the address of a read (None for a
+    // write or an internal cycle), the rest of the machine runs along with
+    // the cpu. A dma halts the cpu on a read for the returned number of cycles
+    // first (the alignment depends on the cycle parity).
+    fn tick(&mut self, _read: Option<u16>, _odd_cycle: bool) -> u16 {
+        0
+    }
+    // the nmi edge and irq level as sampled before the last cycle of the
+    // instruction that just ran, None leaves them to `CPU::set_nmi/set_irq`
+    fn poll_interrupts(&mut self) -> Option<(bool, bool)> {
+        None
+    }
+}
\ No newline at end of file
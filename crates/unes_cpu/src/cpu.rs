@@ -1,9 +1,10 @@
+use crate::bus::Bus;
 use crate::flags::*;
 use crate::opcodes::match_opcode;
 use crate::utils::is_page_crossed;
 
 pub struct Memory {
-    state: [u8; 0xFFFF]
+    pub(crate) state: [u8; 0x10000]
 }
 impl Memory {
     pub fn read(&self, addr: u16) -> u8 {
@@ -27,9 +28,17 @@ impl Memory {
         self.state[addr as usize..addr as usize + S].copy_from_slice(code);
     }
 }
+impl Bus for Memory {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.state[addr as usize]
+    }
+    fn write(&mut self, addr: u16, value: u8) {
+        self.state[addr as usize] = value;
+    }
+}
 impl Default for Memory {
     fn default() -> Self {
-        Memory { state: [0; 0xFFFF] }
+        Memory { state: [0; 0x10000] }
     }
 }
 
@@ -66,13 +75,20 @@ impl AddrMode {
     }
 }
 
+const STACK_BASE: u16 = 0x0100;
+pub const NMI_VECTOR: u16 = 0xFFFA;
+pub const RESET_VECTOR: u16 = 0xFFFC;
+pub const IRQ_VECTOR: u16 = 0xFFFE;
+
 // a number of extra cycles should be returned
-pub type Instruction = fn(&mut CPU, Option<u16>) -> u8;
+pub type Instruction<M> = fn(&mut CPU<M>, Option<u16>) -> u8;
 
 #[derive(Default)]
-pub struct CPU {
-    // emulator only flag set by BRK ins
+pub struct CPU<M: Bus = Memory> {
+    // emulator only flag cleared by BRK (with halt_on_brk) and jam opcodes
     pub running: bool,
+    // stop at BRK instead of taking the interrupt, for bare programs
+    pub halt_on_brk: bool,
     // emulator only flag set when a page is crossed during addressing
     // that might result in an extra cpu cycle
     pub addr_page_crossed: bool,
@@ -84,11 +100,21 @@ pub struct CPU {
     pub pc: u16,
     pub sp: u8,
     pub status: u8,
-    pub memory: Memory
+    pub memory: M,
+    // cycles run since power on, dma stalls included
+    pub cycles: u64,
+
+    // level of the nmi input and the edge latched from it
+    pub nmi_line: bool,
+    pub nmi_pending: bool,
+    // irq is level triggered, taken while the line is high and I is clear
+    pub irq_line: bool,
+    // bus cycles run by the current instruction
+    bus_cycles: u8
 }
 impl CPU {
     pub fn new() -> CPU {
-        CPU { status: 0b0011_0000, sp: 0xff, ..Default::default() }
+        CPU::with_bus(Memory::default())
     }
     pub fn load<const S: usize>(&mut self, addr: u16, code: &[u8; S]) {
         self.memory.load::<S>(addr, code);
@@ -97,13 +123,51 @@ impl CPU {
         self.load::<S>(addr, code);
         self.pc = addr;
         self.running = true;
+        self.halt_on_brk = true;
+    }
+}
+impl<M: Bus> CPU<M> {
+    pub fn with_bus(memory: M) -> CPU<M> {
+        CPU {
+            running: false,
+            halt_on_brk: false,
+            addr_page_crossed: false,
+            reg_a: 0,
+            reg_x: 0,
+            reg_y: 0,
+            pc: 0,
+            sp: 0xff,
+            status: 0b0011_0000,
+            memory,
+            cycles: 0,
+            nmi_line: false,
+            nmi_pending: false,
+            irq_line: false,
+            bus_cycles: 0
+        }
     }
-    pub fn step(&mut self) -> u8 {
-        // return cycles taken
-        let code = self.memory.read(self.pc);
-        let (ins, mode, cycles) = match_opcode(code);
-        let extra_cycles = self.op_execute(ins, mode);
-        cycles + extra_cycles
+    pub fn step(&mut self) -> u16 {
+        // return cycles taken, including the dma halting it
+        let start = self.cycles;
+        self.bus_cycles = 0;
+        let cycles = if self.nmi_pending {
+            self.nmi_pending = false;
+            self.interrupt(NMI_VECTOR)
+        } else if self.irq_line && !self.check_flag(INTERRUPT_FLAG) {
+            self.interrupt(IRQ_VECTOR)
+        } else {
+            let code = self.read(self.pc);
+            let (ins, mode, cycles) = match_opcode(code);
+            cycles + self.op_execute(ins, mode, cycles)
+        };
+        // the internal cycles left
+        while self.bus_cycles < cycles { self.tick(None) }
+        // the lines were sampled before the last cycle
+        if let Some((nmi, irq)) = self.memory.poll_interrupts() {
+            self.nmi_pending |= nmi;
+            self.irq_line = irq;
+        }
+        (self.cycles - start) as u16
     }
     pub fn run(&mut self) {
         self.running = true;
@@ -111,50 +175,134 @@ impl CPU {
             self.step();
         }
     }
-    fn get_op_addr(&mut self, mode: &AddrMode) -> u16 {
+    // nmi is edge triggered, it is taken before the next instruction
+    // after the line goes from low to high
+    pub fn set_nmi(&mut self, level: bool) {
+        if level && !self.nmi_line { self.nmi_pending = true }
+        self.nmi_line = level;
+    }
+    pub fn set_irq(&mut self, level: bool) {
+        self.irq_line = level;
+    }
+    // the reset sequence goes through the stack without writing
+    pub fn reset(&mut self) {
+        self.sp = self.sp.wrapping_sub(3);
+        self.set_flag(INTERRUPT_FLAG, true);
+        self.pc = self.memory.read_u16(RESET_VECTOR);
+        self.running = true;
+        self.cycles += 7;
+    }
+    // one bus cycle, the bus is clocked before the access completes
+    fn tick(&mut self, read: Option<u16>) {
+        let stall = self.memory.tick(read, self.cycles & 1 == 1);
+        self.cycles += stall as u64 + 1;
+        self.bus_cycles = self.bus_cycles.wrapping_add(1);
+    }
+    pub(crate) fn read(&mut self, addr: u16) -> u8 {
+        self.tick(Some(addr));
+        self.memory.read(addr)
+    }
+    pub(crate) fn read_u16(&mut self, addr: u16) -> u16 {
+        u16::from_le_bytes([self.read(addr), self.read(addr.wrapping_add(1))])
+    }
+    pub(crate) fn write(&mut self, addr: u16, value: u8) {
+        self.tick(None);
+        self.memory.write(addr, value);
+    }
+    pub(crate) fn interrupt(&mut self, vector: u16) -> u8 {
+        // the opcode fetch is thrown away, then the next byte is read again
+        self.read(self.pc);
+        self.read(self.pc);
+        self.stack_push_u16(self.pc);
+        self.stack_push((self.status & !BREAK_FLAG) | UNUSED_FLAG);
+        self.set_flag(INTERRUPT_FLAG, true);
+        self.pc = self.read_u16(vector);
+        7
+    }
+    pub fn stack_push(&mut self, value: u8) {
+        self.write(STACK_BASE + self.sp as u16, value);
+        self.sp = self.sp.wrapping_sub(1);
+    }
+    pub fn stack_push_u16(&mut self, value: u16) {
+        let bytes = value.to_le_bytes();
+        self.stack_push(bytes[1]);
+        self.stack_push(bytes[0]);
+    }
+    pub fn stack_pop(&mut self) -> u8 {
+        self.sp = self.sp.wrapping_add(1);
+        self.read(STACK_BASE + self.sp as u16)
+    }
+    pub fn stack_pop_u16(&mut self) -> u16 {
+        let low = self.stack_pop();
+        u16::from_le_bytes([low, self.stack_pop()])
+    }
+    // `cycles` from the opcode table tells stores and read-modify-writes, which
+    // always read the address before the carry into the high byte is fixed
+    fn get_op_addr(&mut self, mode: &AddrMode, cycles: u8) -> u16 {
         self.addr_page_crossed = false;
         match mode {
-            AddrMode::Absolute => self.memory.read_u16(self.pc),
+            AddrMode::Absolute => self.read_u16(self.pc),
             AddrMode::AbsoluteX => {
-                let base = self.memory.read_u16(self.pc);
-                let addr = base.wrapping_add(self.reg_x as u16);
-                if is_page_crossed(base, addr) { self.addr_page_crossed = true }
-                addr
+                let base = self.read_u16(self.pc);
+                self.index(base, self.reg_x, cycles > 4)
             },
             AddrMode::AbsoluteY => {
-                let base = self.memory.read_u16(self.pc);
-                let addr = base.wrapping_add(self.reg_y as u16);
-                if is_page_crossed(base, addr) { self.addr_page_crossed = true }
-                addr
+                let base = self.read_u16(self.pc);
+                self.index(base, self.reg_y, cycles > 4)
             },
             AddrMode::Implied => panic!("Invalid addr mode!"),
             AddrMode::Immediate => self.pc,
-            AddrMode::Indirect => self.memory.read_u16(self.pc),
+            AddrMode::Indirect => {
+                // the pointer high byte is read without carrying into the page
+                let pointer = self.read_u16(self.pc);
+                let high = pointer & 0xff00 | (pointer as u8).wrapping_add(1) as u16;
+                u16::from_le_bytes([self.read(pointer), self.read(high)])
+            },
             AddrMode::IndirectX => {
-                let base = self.memory.read(self.pc);
-                self.memory.read_u16(base.wrapping_add(self.reg_x) as u16)
+                let base = self.read(self.pc);
+                // the base is read while x is added
+                self.read(base as u16);
+                self.read_zero_page_u16(base.wrapping_add(self.reg_x))
             },
             AddrMode::IndirectY => {
-                let zero_addr = self.memory.read(self.pc);
-                let base = self.memory.read_u16(zero_addr as u16);
-                let addr = base.wrapping_add(self.reg_y as u16);
-                if is_page_crossed(base, addr) { self.addr_page_crossed = true }
-                addr
+                let zero_addr = self.read(self.pc);
+                let base = self.read_zero_page_u16(zero_addr);
+                self.index(base, self.reg_y, cycles > 5)
             }
             AddrMode::Relative => self.pc,
-            AddrMode::ZeroPage => self.memory.read(self.pc) as u16,
-            AddrMode::ZeroPageX => self.memory.read(self.pc).wrapping_add(self.reg_x) as u16,
-            AddrMode::ZeroPageY => self.memory.read(self.pc).wrapping_add(self.reg_y) as u16,
+            AddrMode::ZeroPage => self.read(self.pc) as u16,
+            AddrMode::ZeroPageX => {
+                let base = self.read(self.pc);
+                self.read(base as u16);
+                base.wrapping_add(self.reg_x) as u16
+            },
+            AddrMode::ZeroPageY => {
+                let base = self.read(self.pc);
+                self.read(base as u16);
+                base.wrapping_add(self.reg_y) as u16
+            },
         }
     }
-    fn op_execute(&mut self, ins: Instruction, mode: AddrMode) -> u8 {
+    // indexing reads the address with the low byte added first, it is only
+    // repeated with the high byte fixed when a page was crossed
+    fn index(&mut self, base: u16, index: u8, always_read: bool) -> u16 {
+        let addr = base.wrapping_add(index as u16);
+        if is_page_crossed(base, addr) { self.addr_page_crossed = true }
+        if self.addr_page_crossed || always_read { self.read(base & 0xff00 | addr & 0xff); }
+        addr
+    }
+    // pointers in the zero page wrap around it
+    fn read_zero_page_u16(&mut self, addr: u8) -> u16 {
+        u16::from_le_bytes([self.read(addr as u16), self.read(addr.wrapping_add(1) as u16)])
+    }
+    fn op_execute(&mut self, ins: Instruction<M>, mode: AddrMode, cycles: u8) -> u8 {
         // returns a number of extra cycles
-        self.pc += 1;
+        self.pc = self.pc.wrapping_add(1);
         let addr = match mode {
             AddrMode::Implied => None,
-            _ => Some(self.get_op_addr(&mode))
+            _ => Some(self.get_op_addr(&mode, cycles))
         };
-        self.pc += mode.get_size();
+        self.pc = self.pc.wrapping_add(mode.get_size());
         ins(self, addr)
     }
     pub fn check_flag(&self, flag: u8) -> bool {
@@ -1,4 +1,8 @@
 pub const CARRY_FLAG: u8 = 0b0000_0001;
 pub const ZERO_FLAG: u8 = 0b0000_0010;
+pub const INTERRUPT_FLAG: u8 = 0b0000_0100;
+pub const DECIMAL_FLAG: u8 = 0b0000_1000;
+pub const BREAK_FLAG: u8 = 0b0001_0000;
+pub const UNUSED_FLAG: u8 = 0b0010_0000;
 pub const OVERFLOW_FLAG: u8 = 0b0100_0000;
 pub const NEGATIVE_FLAG: u8 = 0b1000_0000;
\ No newline at end of file
@@ -1,8 +1,14 @@
 #![no_std]
+extern crate alloc;
+
+mod bus;
 mod cpu;
 pub mod flags;
 mod opcodes;
+mod state;
 mod tests;
 mod utils;
 
-pub use cpu::CPU;
\ No newline at end of file
+pub use bus::Bus;
+pub use cpu::{CPU, Memory};
+pub use opcodes::instruction_size;
\ No newline at end of file
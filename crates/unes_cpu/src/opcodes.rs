@@ -1,26 +1,110 @@
-use crate::cpu::{AddrMode, CPU, Instruction};
+use crate::bus::Bus;
+use crate::cpu::{AddrMode, CPU, IRQ_VECTOR, Instruction, Memory};
 use crate::flags::*;
 use crate::utils::is_page_crossed;
 
-pub fn match_opcode(code: u8) -> (Instruction, AddrMode, u8) {
+// bytes taken by the instruction, opcode included
+pub fn instruction_size(code: u8) -> u16 {
+    match_opcode::<Memory>(code).1.get_size() + 1
+}
+pub fn match_opcode<M: Bus>(code: u8) -> (Instruction<M>, AddrMode, u8) {
     // ins, mode, base cycles
     match code {
         // adc
         0x69 => (adc, AddrMode::Immediate, 2),
+        0x65 => (adc, AddrMode::ZeroPage, 3),
+        0x75 => (adc, AddrMode::ZeroPageX, 4),
+        0x6D => (adc, AddrMode::Absolute, 4),
+        0x7D => (adc, AddrMode::AbsoluteX, 4),
+        0x79 => (adc, AddrMode::AbsoluteY, 4),
+        0x61 => (adc, AddrMode::IndirectX, 6),
+        0x71 => (adc, AddrMode::IndirectY, 5),
+        // and
+        0x29 => (and, AddrMode::Immediate, 2),
+        0x25 => (and, AddrMode::ZeroPage, 3),
+        0x35 => (and, AddrMode::ZeroPageX, 4),
+        0x2D => (and, AddrMode::Absolute, 4),
+        0x3D => (and, AddrMode::AbsoluteX, 4),
+        0x39 => (and, AddrMode::AbsoluteY, 4),
+        0x21 => (and, AddrMode::IndirectX, 6),
+        0x31 => (and, AddrMode::IndirectY, 5),
+        // asl
+        0x0A => (asl_acc, AddrMode::Implied, 2),
+        0x06 => (asl, AddrMode::ZeroPage, 5),
+        0x16 => (asl, AddrMode::ZeroPageX, 6),
+        0x0E => (asl, AddrMode::Absolute, 6),
+        0x1E => (asl, AddrMode::AbsoluteX, 7),
+        // branches
+        0x90 => (bcc, AddrMode::Relative, 2),
+        0xB0 => (bcs, AddrMode::Relative, 2),
+        0xF0 => (beq, AddrMode::Relative, 2),
+        0x30 => (bmi, AddrMode::Relative, 2),
+        0xD0 => (bne, AddrMode::Relative, 2),
+        0x10 => (bpl, AddrMode::Relative, 2),
+        0x50 => (bvc, AddrMode::Relative, 2),
+        0x70 => (bvs, AddrMode::Relative, 2),
+        // bit
+        0x24 => (bit, AddrMode::ZeroPage, 3),
+        0x2C => (bit, AddrMode::Absolute, 4),
         // brk
         0x00 => (brk, AddrMode::Implied, 7),
-        // bne
-        0xD0 => (bne, AddrMode::Relative, 2),
+        // flags
+        0x18 => (clc, AddrMode::Implied, 2),
+        0xD8 => (cld, AddrMode::Implied, 2),
+        0x58 => (cli, AddrMode::Implied, 2),
+        0xB8 => (clv, AddrMode::Implied, 2),
+        0x38 => (sec, AddrMode::Implied, 2),
+        0xF8 => (sed, AddrMode::Implied, 2),
+        0x78 => (sei, AddrMode::Implied, 2),
+        // cmp
+        0xC9 => (cmp, AddrMode::Immediate, 2),
+        0xC5 => (cmp, AddrMode::ZeroPage, 3),
+        0xD5 => (cmp, AddrMode::ZeroPageX, 4),
+        0xCD => (cmp, AddrMode::Absolute, 4),
+        0xDD => (cmp, AddrMode::AbsoluteX, 4),
+        0xD9 => (cmp, AddrMode::AbsoluteY, 4),
+        0xC1 => (cmp, AddrMode::IndirectX, 6),
+        0xD1 => (cmp, AddrMode::IndirectY, 5),
         // cpx
         0xEC => (cpx, AddrMode::Absolute, 4),
         0xE0 => (cpx, AddrMode::Immediate, 2),
         0xE4 => (cpx, AddrMode::ZeroPage, 3),
+        // cpy
+        0xCC => (cpy, AddrMode::Absolute, 4),
+        0xC0 => (cpy, AddrMode::Immediate, 2),
+        0xC4 => (cpy, AddrMode::ZeroPage, 3),
+        // dec
+        0xC6 => (dec, AddrMode::ZeroPage, 5),
+        0xD6 => (dec, AddrMode::ZeroPageX, 6),
+        0xCE => (dec, AddrMode::Absolute, 6),
+        0xDE => (dec, AddrMode::AbsoluteX, 7),
         // dex
         0xCA => (dex, AddrMode::Implied, 2),
+        // dey
+        0x88 => (dey, AddrMode::Implied, 2),
+        // eor
+        0x49 => (eor, AddrMode::Immediate, 2),
+        0x45 => (eor, AddrMode::ZeroPage, 3),
+        0x55 => (eor, AddrMode::ZeroPageX, 4),
+        0x4D => (eor, AddrMode::Absolute, 4),
+        0x5D => (eor, AddrMode::AbsoluteX, 4),
+        0x59 => (eor, AddrMode::AbsoluteY, 4),
+        0x41 => (eor, AddrMode::IndirectX, 6),
+        0x51 => (eor, AddrMode::IndirectY, 5),
+        // inc
+        0xE6 => (inc, AddrMode::ZeroPage, 5),
+        0xF6 => (inc, AddrMode::ZeroPageX, 6),
+        0xEE => (inc, AddrMode::Absolute, 6),
+        0xFE => (inc, AddrMode::AbsoluteX, 7),
         // inx
         0xE8 => (inx, AddrMode::Implied, 2),
+        // iny
+        0xC8 => (iny, AddrMode::Implied, 2),
         // jmp
+        0x4C => (jmp, AddrMode::Absolute, 3),
         0x6C => (jmp, AddrMode::Indirect, 5),
+        // jsr
+        0x20 => (jsr, AddrMode::Absolute, 6),
         // lda
         0xAD => (lda, AddrMode::Absolute, 4),
         0xBD => (lda, AddrMode::AbsoluteX, 4),
@@ -36,113 +120,628 @@ pub fn match_opcode(code: u8) -> (Instruction, AddrMode, u8) {
         0xA2 => (ldx, AddrMode::Immediate, 2),
         0xA6 => (ldx, AddrMode::ZeroPage, 3),
         0xB6 => (ldx, AddrMode::ZeroPageY, 4),
+        // ldy
+        0xAC => (ldy, AddrMode::Absolute, 4),
+        0xBC => (ldy, AddrMode::AbsoluteX, 4),
+        0xA0 => (ldy, AddrMode::Immediate, 2),
+        0xA4 => (ldy, AddrMode::ZeroPage, 3),
+        0xB4 => (ldy, AddrMode::ZeroPageX, 4),
+        // lsr
+        0x4A => (lsr_acc, AddrMode::Implied, 2),
+        0x46 => (lsr, AddrMode::ZeroPage, 5),
+        0x56 => (lsr, AddrMode::ZeroPageX, 6),
+        0x4E => (lsr, AddrMode::Absolute, 6),
+        0x5E => (lsr, AddrMode::AbsoluteX, 7),
+        // nop
+        0xEA => (nop, AddrMode::Implied, 2),
+        // ora
+        0x09 => (ora, AddrMode::Immediate, 2),
+        0x05 => (ora, AddrMode::ZeroPage, 3),
+        0x15 => (ora, AddrMode::ZeroPageX, 4),
+        0x0D => (ora, AddrMode::Absolute, 4),
+        0x1D => (ora, AddrMode::AbsoluteX, 4),
+        0x19 => (ora, AddrMode::AbsoluteY, 4),
+        0x01 => (ora, AddrMode::IndirectX, 6),
+        0x11 => (ora, AddrMode::IndirectY, 5),
+        // stack
+        0x48 => (pha, AddrMode::Implied, 3),
+        0x08 => (php, AddrMode::Implied, 3),
+        0x68 => (pla, AddrMode::Implied, 4),
+        0x28 => (plp, AddrMode::Implied, 4),
+        // rol
+        0x2A => (rol_acc, AddrMode::Implied, 2),
+        0x26 => (rol, AddrMode::ZeroPage, 5),
+        0x36 => (rol, AddrMode::ZeroPageX, 6),
+        0x2E => (rol, AddrMode::Absolute, 6),
+        0x3E => (rol, AddrMode::AbsoluteX, 7),
+        // ror
+        0x6A => (ror_acc, AddrMode::Implied, 2),
+        0x66 => (ror, AddrMode::ZeroPage, 5),
+        0x76 => (ror, AddrMode::ZeroPageX, 6),
+        0x6E => (ror, AddrMode::Absolute, 6),
+        0x7E => (ror, AddrMode::AbsoluteX, 7),
+        // rti, rts
+        0x40 => (rti, AddrMode::Implied, 6),
+        0x60 => (rts, AddrMode::Implied, 6),
+        // sbc
+        0xE9 => (sbc, AddrMode::Immediate, 2),
+        0xE5 => (sbc, AddrMode::ZeroPage, 3),
+        0xF5 => (sbc, AddrMode::ZeroPageX, 4),
+        0xED => (sbc, AddrMode::Absolute, 4),
+        0xFD => (sbc, AddrMode::AbsoluteX, 4),
+        0xF9 => (sbc, AddrMode::AbsoluteY, 4),
+        0xE1 => (sbc, AddrMode::IndirectX, 6),
+        0xF1 => (sbc, AddrMode::IndirectY, 5),
         // sta
         0x8D => (sta, AddrMode::Absolute, 4),
         0x9D => (sta, AddrMode::AbsoluteX, 5),
         0x99 => (sta, AddrMode::AbsoluteY, 5),
         0x85 => (sta, AddrMode::ZeroPage, 3),
         0x95 => (sta, AddrMode::ZeroPageX, 4),
+        0x81 => (sta, AddrMode::IndirectX, 6),
+        0x91 => (sta, AddrMode::IndirectY, 6),
         //stx
         0x8E => (stx, AddrMode::Absolute, 4),
         0x86 => (stx, AddrMode::ZeroPage, 3),
         0x96 => (stx, AddrMode::ZeroPageY, 4),
-        // tax
+        // sty
+        0x8C => (sty, AddrMode::Absolute, 4),
+        0x84 => (sty, AddrMode::ZeroPage, 3),
+        0x94 => (sty, AddrMode::ZeroPageX, 4),
+        // transfers
         0xAA => (tax, AddrMode::Implied, 2),
-        _ => panic!("{:?} opcode is not supported!", code)
+        0xA8 => (tay, AddrMode::Implied, 2),
+        0xBA => (tsx, AddrMode::Implied, 2),
+        0x8A => (txa, AddrMode::Implied, 2),
+        0x9A => (txs, AddrMode::Implied, 2),
+        0x98 => (tya, AddrMode::Implied, 2),
+
+        // unofficial opcodes
+        // nop with an operand
+        0x1A | 0x3A | 0x5A | 0x7A | 0xDA | 0xFA => (nop, AddrMode::Implied, 2),
+        0x80 | 0x82 | 0x89 | 0xC2 | 0xE2 => (nop, AddrMode::Immediate, 2),
+        0x04 | 0x44 | 0x64 => (nop, AddrMode::ZeroPage, 3),
+        0x14 | 0x34 | 0x54 | 0x74 | 0xD4 | 0xF4 => (nop, AddrMode::ZeroPageX, 4),
+        0x0C => (nop, AddrMode::Absolute, 4),
+        0x1C | 0x3C | 0x5C | 0x7C | 0xDC | 0xFC => (nop_read, AddrMode::AbsoluteX, 4),
+        // lax
+        0xAB => (lax, AddrMode::Immediate, 2),
+        0xA7 => (lax, AddrMode::ZeroPage, 3),
+        0xB7 => (lax, AddrMode::ZeroPageY, 4),
+        0xAF => (lax, AddrMode::Absolute, 4),
+        0xBF => (lax, AddrMode::AbsoluteY, 4),
+        0xA3 => (lax, AddrMode::IndirectX, 6),
+        0xB3 => (lax, AddrMode::IndirectY, 5),
+        // sax
+        0x87 => (sax, AddrMode::ZeroPage, 3),
+        0x97 => (sax, AddrMode::ZeroPageY, 4),
+        0x8F => (sax, AddrMode::Absolute, 4),
+        0x83 => (sax, AddrMode::IndirectX, 6),
+        // sbc
+        0xEB => (sbc, AddrMode::Immediate, 2),
+        // dcp
+        0xC7 => (dcp, AddrMode::ZeroPage, 5),
+        0xD7 => (dcp, AddrMode::ZeroPageX, 6),
+        0xCF => (dcp, AddrMode::Absolute, 6),
+        0xDF => (dcp, AddrMode::AbsoluteX, 7),
+        0xDB => (dcp, AddrMode::AbsoluteY, 7),
+        0xC3 => (dcp, AddrMode::IndirectX, 8),
+        0xD3 => (dcp, AddrMode::IndirectY, 8),
+        // isb
+        0xE7 => (isb, AddrMode::ZeroPage, 5),
+        0xF7 => (isb, AddrMode::ZeroPageX, 6),
+        0xEF => (isb, AddrMode::Absolute, 6),
+        0xFF => (isb, AddrMode::AbsoluteX, 7),
+        0xFB => (isb, AddrMode::AbsoluteY, 7),
+        0xE3 => (isb, AddrMode::IndirectX, 8),
+        0xF3 => (isb, AddrMode::IndirectY, 8),
+        // slo
+        0x07 => (slo, AddrMode::ZeroPage, 5),
+        0x17 => (slo, AddrMode::ZeroPageX, 6),
+        0x0F => (slo, AddrMode::Absolute, 6),
+        0x1F => (slo, AddrMode::AbsoluteX, 7),
+        0x1B => (slo, AddrMode::AbsoluteY, 7),
+        0x03 => (slo, AddrMode::IndirectX, 8),
+        0x13 => (slo, AddrMode::IndirectY, 8),
+        // rla
+        0x27 => (rla, AddrMode::ZeroPage, 5),
+        0x37 => (rla, AddrMode::ZeroPageX, 6),
+        0x2F => (rla, AddrMode::Absolute, 6),
+        0x3F => (rla, AddrMode::AbsoluteX, 7),
+        0x3B => (rla, AddrMode::AbsoluteY, 7),
+        0x23 => (rla, AddrMode::IndirectX, 8),
+        0x33 => (rla, AddrMode::IndirectY, 8),
+        // sre
+        0x47 => (sre, AddrMode::ZeroPage, 5),
+        0x57 => (sre, AddrMode::ZeroPageX, 6),
+        0x4F => (sre, AddrMode::Absolute, 6),
+        0x5F => (sre, AddrMode::AbsoluteX, 7),
+        0x5B => (sre, AddrMode::AbsoluteY, 7),
+        0x43 => (sre, AddrMode::IndirectX, 8),
+        0x53 => (sre, AddrMode::IndirectY, 8),
+        // rra
+        0x67 => (rra, AddrMode::ZeroPage, 5),
+        0x77 => (rra, AddrMode::ZeroPageX, 6),
+        0x6F => (rra, AddrMode::Absolute, 6),
+        0x7F => (rra, AddrMode::AbsoluteX, 7),
+        0x7B => (rra, AddrMode::AbsoluteY, 7),
+        0x63 => (rra, AddrMode::IndirectX, 8),
+        0x73 => (rra, AddrMode::IndirectY, 8),
+        // immediate combinations
+        0x0B | 0x2B => (anc, AddrMode::Immediate, 2),
+        0x4B => (alr, AddrMode::Immediate, 2),
+        0x6B => (arr, AddrMode::Immediate, 2),
+        0xCB => (axs, AddrMode::Immediate, 2),
+        0x8B => (xaa, AddrMode::Immediate, 2),
+        // stores anding with the high byte of the address + 1
+        0x93 => (sha, AddrMode::IndirectY, 6),
+        0x9F => (sha, AddrMode::AbsoluteY, 5),
+        0x9E => (shx, AddrMode::AbsoluteY, 5),
+        0x9C => (shy, AddrMode::AbsoluteX, 5),
+        0x9B => (tas, AddrMode::AbsoluteY, 5),
+        0xBB => (las, AddrMode::AbsoluteY, 4),
+        // jam
+        0x02 | 0x12 | 0x22 | 0x32 | 0x42 | 0x52 | 0x62 | 0x72 | 0x92 | 0xB2 | 0xD2 | 0xF2 => (jam, AddrMode::Implied, 2)
     }
 }
 
-fn adc(cpu: &mut CPU, addr: Option<u16>) -> u8 {
-    let operand = cpu.memory.read(
-        addr.expect("Invalid ADC operand!")
-    );
-    let (mut res, carry) = cpu.reg_a.overflowing_add(operand);
-    // if prev carry add 1
-    if cpu.check_flag(CARRY_FLAG) { res += 1 };
-    // set new carry
-    cpu.set_flag(CARRY_FLAG, carry);
-    // set overflow
+fn operand<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.read(
+        addr.expect("Invalid operand!")
+    )
+}
+// an extra cycle when indexing crossed a page, for instructions that only read
+fn page_penalty<M: Bus>(cpu: &CPU<M>) -> u8 {
+    if cpu.addr_page_crossed { 1 } else { 0 }
+}
+// read-modify-write instructions write the unmodified value back first
+fn modify<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>, op: fn(&mut CPU<M>, u8) -> u8) -> u8 {
+    let addr = addr.expect("Invalid operand!");
+    let value = cpu.read(addr);
+    cpu.write(addr, value);
+    let res = op(cpu, value);
+    cpu.write(addr, res);
+    res
+}
+fn add<M: Bus>(cpu: &mut CPU<M>, operand: u8) {
+    let sum = cpu.reg_a as u16 + operand as u16 + cpu.check_flag(CARRY_FLAG) as u16;
+    let res = sum as u8;
+    cpu.set_flag(CARRY_FLAG, sum > 0xff);
     cpu.set_flag(
         OVERFLOW_FLAG,
         (operand ^ res) & (cpu.reg_a ^ res) & 0x80 != 0
     );
-    // assign reg value
     cpu.reg_a = res;
-
     cpu.update_zero_negative_flags(cpu.reg_a);
-    if cpu.addr_page_crossed { 1 } else { 0 }
+}
+fn compare<M: Bus>(cpu: &mut CPU<M>, reg: u8, value: u8) {
+    cpu.set_flag(CARRY_FLAG, reg >= value);
+    cpu.update_zero_negative_flags(reg.wrapping_sub(value));
+}
+fn branch<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>, condition: bool) -> u8 {
+    if !condition { return 0 }
+    let offset = operand(cpu, addr) as i8;
+    let before = cpu.pc;
+    cpu.pc = cpu.pc.wrapping_add(offset as u16);
+    if is_page_crossed(before, cpu.pc) { 2 } else { 1 }
+}
+fn shift_left<M: Bus>(cpu: &mut CPU<M>, value: u8, carry_in: bool) -> u8 {
+    let res = value << 1 | carry_in as u8;
+    cpu.set_flag(CARRY_FLAG, value & 0x80 != 0);
+    cpu.update_zero_negative_flags(res);
+    res
+}
+fn shift_right<M: Bus>(cpu: &mut CPU<M>, value: u8, carry_in: bool) -> u8 {
+    let res = value >> 1 | (carry_in as u8) << 7;
+    cpu.set_flag(CARRY_FLAG, value & 1 != 0);
+    cpu.update_zero_negative_flags(res);
+    res
+}
+// B and the unused bit only exist on the stack
+fn pull_status<M: Bus>(cpu: &mut CPU<M>) {
+    let value = cpu.stack_pop();
+    let kept = BREAK_FLAG | UNUSED_FLAG;
+    cpu.status = value & !kept | cpu.status & kept;
 }
 
-fn brk(cpu: &mut CPU, _addr: Option<u16>) -> u8 {
-    cpu.running = false;
+fn adc<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = operand(cpu, addr);
+    add(cpu, value);
+    page_penalty(cpu)
+}
+fn and<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.reg_a &= operand(cpu, addr);
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    page_penalty(cpu)
+}
+fn asl<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    modify(cpu, addr, |cpu, value| shift_left(cpu, value, false));
+    0
+}
+fn asl_acc<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_a = shift_left(cpu, cpu.reg_a, false);
     0
 }
-fn bne(cpu: &mut CPU, addr: Option<u16>) -> u8 {
-    if !cpu.check_flag(ZERO_FLAG) {
-        let offset = cpu.memory.read(
-            addr.expect("Invalid BNE operand!")
-        ) as i8;
-        let before = cpu.pc;
-        cpu.pc = cpu.pc.wrapping_add(offset as u16);
-        if is_page_crossed(before, cpu.pc) { return 2 } else { return 1 }
+fn bcc<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    branch(cpu, addr, !cpu.check_flag(CARRY_FLAG))
+}
+fn bcs<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    branch(cpu, addr, cpu.check_flag(CARRY_FLAG))
+}
+fn beq<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    branch(cpu, addr, cpu.check_flag(ZERO_FLAG))
+}
+fn bit<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = operand(cpu, addr);
+    cpu.set_flag(ZERO_FLAG, cpu.reg_a & value == 0);
+    cpu.set_flag(OVERFLOW_FLAG, value & 0x40 != 0);
+    cpu.set_flag(NEGATIVE_FLAG, value & 0x80 != 0);
+    0
+}
+fn bmi<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    branch(cpu, addr, cpu.check_flag(NEGATIVE_FLAG))
+}
+fn bne<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    branch(cpu, addr, !cpu.check_flag(ZERO_FLAG))
+}
+fn bpl<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    branch(cpu, addr, !cpu.check_flag(NEGATIVE_FLAG))
+}
+fn brk<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    if cpu.halt_on_brk {
+        cpu.running = false;
+        return 0
     }
+    // skips a padding byte and pushes the status with B set
+    cpu.stack_push_u16(cpu.pc.wrapping_add(1));
+    cpu.stack_push(cpu.status | BREAK_FLAG | UNUSED_FLAG);
+    cpu.set_flag(INTERRUPT_FLAG, true);
+    cpu.pc = cpu.read_u16(IRQ_VECTOR);
     0
 }
-fn cpx(cpu: &mut CPU, addr: Option<u16>) -> u8 {
-    let val = cpu.memory.read(
-        addr.expect("Invalid CPX operand!")
-    );
-    let res = cpu.reg_x.wrapping_sub(val);
-    cpu.set_flag(CARRY_FLAG, cpu.reg_x >= val);
+fn bvc<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    branch(cpu, addr, !cpu.check_flag(OVERFLOW_FLAG))
+}
+fn bvs<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    branch(cpu, addr, cpu.check_flag(OVERFLOW_FLAG))
+}
+fn clc<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.set_flag(CARRY_FLAG, false);
+    0
+}
+fn cld<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.set_flag(DECIMAL_FLAG, false);
+    0
+}
+fn cli<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.set_flag(INTERRUPT_FLAG, false);
+    0
+}
+fn clv<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.set_flag(OVERFLOW_FLAG, false);
+    0
+}
+fn cmp<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = operand(cpu, addr);
+    compare(cpu, cpu.reg_a, value);
+    page_penalty(cpu)
+}
+fn cpx<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = operand(cpu, addr);
+    compare(cpu, cpu.reg_x, value);
+    0
+}
+fn cpy<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = operand(cpu, addr);
+    compare(cpu, cpu.reg_y, value);
+    0
+}
+fn dec<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let res = modify(cpu, addr, |_, value| value.wrapping_sub(1));
     cpu.update_zero_negative_flags(res);
     0
 }
-fn dex(cpu: &mut CPU, _addr: Option<u16>) -> u8 {
+fn dex<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
     cpu.reg_x = cpu.reg_x.wrapping_sub(1);
     cpu.update_zero_negative_flags(cpu.reg_x);
     0
 }
-fn inx(cpu: &mut CPU, _addr: Option<u16>) -> u8 {
+fn dey<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_y = cpu.reg_y.wrapping_sub(1);
+    cpu.update_zero_negative_flags(cpu.reg_y);
+    0
+}
+fn eor<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.reg_a ^= operand(cpu, addr);
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    page_penalty(cpu)
+}
+fn inc<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let res = modify(cpu, addr, |_, value| value.wrapping_add(1));
+    cpu.update_zero_negative_flags(res);
+    0
+}
+fn inx<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
     cpu.reg_x = cpu.reg_x.wrapping_add(1);
     cpu.update_zero_negative_flags(cpu.reg_x);
     0
 }
-fn jmp(cpu: &mut CPU, addr: Option<u16>) -> u8 {
-    cpu.pc = cpu.memory.read_u16(
-        addr.expect("Invalid JMP operand!")
-    );
+fn iny<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_y = cpu.reg_y.wrapping_add(1);
+    cpu.update_zero_negative_flags(cpu.reg_y);
     0
 }
-fn lda(cpu: &mut CPU, addr: Option<u16>) -> u8 {
-    cpu.reg_a = cpu.memory.read(
-        addr.expect("Invalid LDA operand!")
-    );
+fn jmp<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.pc = addr.expect("Invalid JMP operand!");
+    0
+}
+fn jsr<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    // the return address is the last byte of the instruction
+    cpu.stack_push_u16(cpu.pc.wrapping_sub(1));
+    cpu.pc = addr.expect("Invalid JSR operand!");
+    0
+}
+fn lda<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.reg_a = operand(cpu, addr);
     cpu.update_zero_negative_flags(cpu.reg_a);
-    if cpu.addr_page_crossed { 1 } else { 0 }
+    page_penalty(cpu)
 }
-fn ldx(cpu: &mut CPU, addr: Option<u16>) -> u8 {
-    cpu.reg_x = cpu.memory.read(
-        addr.expect("Invalid LDX operand!")
-    );
+fn ldx<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.reg_x = operand(cpu, addr);
     cpu.update_zero_negative_flags(cpu.reg_x);
-    if cpu.addr_page_crossed { 1 } else { 0 }
+    page_penalty(cpu)
+}
+fn ldy<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.reg_y = operand(cpu, addr);
+    cpu.update_zero_negative_flags(cpu.reg_y);
+    page_penalty(cpu)
+}
+fn lsr<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    modify(cpu, addr, |cpu, value| shift_right(cpu, value, false));
+    0
+}
+fn lsr_acc<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_a = shift_right(cpu, cpu.reg_a, false);
+    0
+}
+fn nop<M: Bus>(_cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    0
+}
+fn ora<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.reg_a |= operand(cpu, addr);
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    page_penalty(cpu)
+}
+fn pha<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.stack_push(cpu.reg_a);
+    0
+}
+fn php<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.stack_push(cpu.status | BREAK_FLAG | UNUSED_FLAG);
+    0
+}
+fn pla<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_a = cpu.stack_pop();
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    0
+}
+fn plp<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    pull_status(cpu);
+    0
+}
+fn rol<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    modify(cpu, addr, |cpu, value| shift_left(cpu, value, cpu.check_flag(CARRY_FLAG)));
+    0
+}
+fn rol_acc<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_a = shift_left(cpu, cpu.reg_a, cpu.check_flag(CARRY_FLAG));
+    0
+}
+fn ror<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    modify(cpu, addr, |cpu, value| shift_right(cpu, value, cpu.check_flag(CARRY_FLAG)));
+    0
+}
+fn ror_acc<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_a = shift_right(cpu, cpu.reg_a, cpu.check_flag(CARRY_FLAG));
+    0
+}
+fn rti<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    pull_status(cpu);
+    cpu.pc = cpu.stack_pop_u16();
+    0
+}
+fn rts<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.pc = cpu.stack_pop_u16().wrapping_add(1);
+    0
 }
-fn sta(cpu: &mut CPU, addr: Option<u16>) -> u8 {
-    cpu.memory.write(
-        addr.expect("Invalid LDA operand!"),
+fn sbc<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = operand(cpu, addr);
+    add(cpu, !value);
+    page_penalty(cpu)
+}
+fn sec<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.set_flag(CARRY_FLAG, true);
+    0
+}
+fn sed<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.set_flag(DECIMAL_FLAG, true);
+    0
+}
+fn sei<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.set_flag(INTERRUPT_FLAG, true);
+    0
+}
+fn sta<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.write(
+        addr.expect("Invalid STA operand!"),
         cpu.reg_a
     );
     0
 }
-fn stx(cpu: &mut CPU, addr: Option<u16>) -> u8 {
-    cpu.memory.write(
+fn stx<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.write(
         addr.expect("Invalid STX operand!"),
         cpu.reg_x
     );
     0
 }
-fn tax(cpu: &mut CPU, _addr: Option<u16>) -> u8 {
+fn sty<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.write(
+        addr.expect("Invalid STY operand!"),
+        cpu.reg_y
+    );
+    0
+}
+fn tax<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
     cpu.reg_x = cpu.reg_a;
     cpu.update_zero_negative_flags(cpu.reg_x);
     0
+}
+fn tay<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_y = cpu.reg_a;
+    cpu.update_zero_negative_flags(cpu.reg_y);
+    0
+}
+fn tsx<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_x = cpu.sp;
+    cpu.update_zero_negative_flags(cpu.reg_x);
+    0
+}
+fn txa<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_a = cpu.reg_x;
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    0
+}
+fn txs<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.sp = cpu.reg_x;
+    0
+}
+fn tya<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    cpu.reg_a = cpu.reg_y;
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    0
+}
+
+// unofficial
+fn alr<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = cpu.reg_a & operand(cpu, addr);
+    cpu.reg_a = shift_right(cpu, value, false);
+    0
+}
+fn anc<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.reg_a &= operand(cpu, addr);
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    cpu.set_flag(CARRY_FLAG, cpu.reg_a & 0x80 != 0);
+    0
+}
+fn arr<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = cpu.reg_a & operand(cpu, addr);
+    cpu.reg_a = value >> 1 | (cpu.check_flag(CARRY_FLAG) as u8) << 7;
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    cpu.set_flag(CARRY_FLAG, cpu.reg_a & 0x40 != 0);
+    cpu.set_flag(OVERFLOW_FLAG, (cpu.reg_a >> 6 ^ cpu.reg_a >> 5) & 1 != 0);
+    0
+}
+fn axs<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = operand(cpu, addr);
+    let reg = cpu.reg_a & cpu.reg_x;
+    compare(cpu, reg, value);
+    cpu.reg_x = reg.wrapping_sub(value);
+    0
+}
+fn dcp<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let res = modify(cpu, addr, |_, value| value.wrapping_sub(1));
+    compare(cpu, cpu.reg_a, res);
+    0
+}
+fn isb<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let res = modify(cpu, addr, |_, value| value.wrapping_add(1));
+    add(cpu, !res);
+    0
+}
+fn jam<M: Bus>(cpu: &mut CPU<M>, _addr: Option<u16>) -> u8 {
+    // the cpu locks up until reset
+    cpu.running = false;
+    cpu.pc = cpu.pc.wrapping_sub(1);
+    0
+}
+fn las<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let value = operand(cpu, addr) & cpu.sp;
+    cpu.reg_a = value;
+    cpu.reg_x = value;
+    cpu.sp = value;
+    cpu.update_zero_negative_flags(value);
+    page_penalty(cpu)
+}
+fn lax<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.reg_a = operand(cpu, addr);
+    cpu.reg_x = cpu.reg_a;
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    page_penalty(cpu)
+}
+fn nop_read<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    operand(cpu, addr);
+    page_penalty(cpu)
+}
+fn rla<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let res = modify(cpu, addr, |cpu, value| shift_left(cpu, value, cpu.check_flag(CARRY_FLAG)));
+    cpu.reg_a &= res;
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    0
+}
+fn rra<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let res = modify(cpu, addr, |cpu, value| shift_right(cpu, value, cpu.check_flag(CARRY_FLAG)));
+    add(cpu, res);
+    0
+}
+fn sax<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.write(
+        addr.expect("Invalid SAX operand!"),
+        cpu.reg_a & cpu.reg_x
+    );
+    0
+}
+// the value is anded with the high byte of the target + 1, which also
+// replaces the high byte when the indexing crossed a page
+fn store_high<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>, value: u8, index: u8) {
+    let addr = addr.expect("Invalid operand!");
+    let base = addr.wrapping_sub(index as u16);
+    let value = value & ((base >> 8) as u8).wrapping_add(1);
+    let addr = if is_page_crossed(base, addr) { (value as u16) << 8 | addr & 0xff } else { addr };
+    cpu.write(addr, value);
+}
+fn sha<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    store_high(cpu, addr, cpu.reg_a & cpu.reg_x, cpu.reg_y);
+    0
+}
+fn shx<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    store_high(cpu, addr, cpu.reg_x, cpu.reg_y);
+    0
+}
+fn shy<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    store_high(cpu, addr, cpu.reg_y, cpu.reg_x);
+    0
+}
+fn slo<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let res = modify(cpu, addr, |cpu, value| shift_left(cpu, value, false));
+    cpu.reg_a |= res;
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    0
+}
+fn sre<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    let res = modify(cpu, addr, |cpu, value| shift_right(cpu, value, false));
+    cpu.reg_a ^= res;
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    0
+}
+fn tas<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    cpu.sp = cpu.reg_a & cpu.reg_x;
+    store_high(cpu, addr, cpu.sp, cpu.reg_y);
+    0
+}
+fn xaa<M: Bus>(cpu: &mut CPU<M>, addr: Option<u16>) -> u8 {
+    // unstable, the magic constant varies between chips
+    cpu.reg_a = (cpu.reg_a | 0xee) & cpu.reg_x & operand(cpu, addr);
+    cpu.update_zero_negative_flags(cpu.reg_a);
+    0
 }
\ No newline at end of file
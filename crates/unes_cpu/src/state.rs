@@ -0,0 +1,64 @@
+use alloc::vec::Vec;
+
+use unes_state::{Chunk, Snapshot, State, StateError, StateReader, StateWriter};
+
+use crate::bus::Bus;
+use crate::cpu::{CPU, Memory};
+
+impl Snapshot for Memory {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_bytes(&self.state);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        r.read_bytes(&mut self.state)
+    }
+}
+// the registers and interrupt lines, the bus is up to its owner
+impl<M: Bus> Snapshot for CPU<M> {
+    fn save(&self, w: &mut StateWriter) {
+        self.running.save(w);
+        self.halt_on_brk.save(w);
+        self.reg_a.save(w);
+        self.reg_x.save(w);
+        self.reg_y.save(w);
+        self.pc.save(w);
+        self.sp.save(w);
+        self.status.save(w);
+        self.cycles.save(w);
+        self.nmi_line.save(w);
+        self.nmi_pending.save(w);
+        self.irq_line.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.running.load(r)?;
+        self.halt_on_brk.load(r)?;
+        self.reg_a.load(r)?;
+        self.reg_x.load(r)?;
+        self.reg_y.load(r)?;
+        self.pc.load(r)?;
+        self.sp.load(r)?;
+        self.status.load(r)?;
+        self.cycles.load(r)?;
+        self.nmi_line.load(r)?;
+        self.nmi_pending.load(r)?;
+        self.irq_line.load(r)
+    }
+}
+impl CPU {
+    // registers and the whole 64KB of memory
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut w = StateWriter::new();
+        w.chunk(Chunk::CPU, |w| self.save(w));
+        w.chunk(Chunk::MEMORY, |w| self.memory.save(w));
+        w.finish()
+    }
+    // nothing changes when the state is refused
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let state = State::parse(data)?;
+        let (mut cpu, mut memory) = (state.chunk(Chunk::CPU)?, state.chunk(Chunk::MEMORY)?);
+        let backup = self.save_state();
+        let result = Snapshot::load(self, &mut cpu).and_then(|_| self.memory.load(&mut memory));
+        if result.is_err() { self.load_state(&backup)? }
+        result
+    }
+}
\ No newline at end of file
@@ -130,4 +130,38 @@ mod tests {
         cpu.step();
         assert!(cpu.reg_a == 0xac);
     }
+    #[test]
+    fn test_jmp_indirect_page_wrap() {
+        let mut cpu = CPU::new();
+        cpu.load_executable::<3>(0x8000, &[0x6c, 0xff, 0x10]);
+        // the high byte comes from $1000, not $1100
+        cpu.load::<1>(0x10ff, &[0x34]);
+        cpu.load::<1>(0x1000, &[0x12]);
+        cpu.load::<1>(0x1100, &[0x56]);
+        cpu.step();
+        assert!(cpu.pc == 0x1234);
+    }
+    #[test]
+    fn test_indirect_zero_page_wrap() {
+        let mut cpu = CPU::new();
+        // lda ($ff),y with the pointer split between $ff and $00
+        cpu.load_executable::<2>(0x8000, &[0xb1, 0xff]);
+        cpu.load::<1>(0x00ff, &[0x00]);
+        cpu.load::<1>(0x0000, &[0x20]);
+        cpu.load::<1>(0x2001, &[0x77]);
+        cpu.reg_y = 1;
+        cpu.step();
+        assert!(cpu.reg_a == 0x77);
+    }
+    #[test]
+    fn test_pc_wrap() {
+        let mut cpu = CPU::new();
+        // lda #$05 with the operand at $0000, then nop
+        cpu.load_executable::<1>(0xffff, &[0xa9]);
+        cpu.load::<2>(0x0000, &[0x05, 0xea]);
+        cpu.step();
+        assert!(cpu.reg_a == 0x05 && cpu.pc == 0x0001);
+        cpu.step();
+        assert!(cpu.pc == 0x0002);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{Bus, CPU};
+
+    // flat ram that halts the cpu on the read after a write to $4014, like the oam dma
+    struct DmaBus {
+        ram: [u8; 0x10000],
+        dma: bool,
+        reads: Vec<u16>
+    }
+    impl Bus for DmaBus {
+        fn read(&mut self, addr: u16) -> u8 {
+            self.reads.push(addr);
+            self.ram[addr as usize]
+        }
+        fn write(&mut self, addr: u16, value: u8) {
+            if addr == 0x4014 { self.dma = true }
+            self.ram[addr as usize] = value;
+        }
+        fn tick(&mut self, read: Option<u16>, odd_cycle: bool) -> u16 {
+            if read.is_none() || !self.dma { return 0 }
+            self.dma = false;
+            513 + odd_cycle as u16
+        }
+    }
+    fn cpu(code: &[u8]) -> CPU<DmaBus> {
+        let mut bus = DmaBus { ram: [0; 0x10000], dma: false, reads: Vec::new() };
+        bus.ram[0x8000..0x8000 + code.len()].copy_from_slice(code);
+        let mut cpu = CPU::with_bus(bus);
+        cpu.pc = 0x8000;
+        cpu
+    }
+
+    #[test]
+    fn test_cycle_count() {
+        let mut cpu = cpu(&[0xa9, 0x01, 0xa5, 0x10, 0xe8]);
+        cpu.step();
+        cpu.step();
+        cpu.step();
+        assert!(cpu.cycles == 7);
+    }
+    #[test]
+    fn test_dummy_reads() {
+        // lda $10ff,x, sta $1000,x with x = 1
+        let mut cpu = cpu(&[0xbd, 0xff, 0x10, 0x9d, 0x00, 0x10]);
+        cpu.reg_x = 1;
+        // the address before the carry, then the right one
+        assert!(cpu.step() == 5);
+        assert!(cpu.memory.reads == [0x8000, 0x8001, 0x8002, 0x1000, 0x1100]);
+        // stores read it even without a carry
+        cpu.memory.reads.clear();
+        assert!(cpu.step() == 5);
+        assert!(cpu.memory.reads == [0x8003, 0x8004, 0x8005, 0x1001]);
+    }
+    #[test]
+    fn test_dma_stall() {
+        // lda #2, sta $4014, nop
+        let mut cpu = cpu(&[0xa9, 0x02, 0x8d, 0x14, 0x40, 0xea]);
+        cpu.step();
+        assert!(cpu.step() == 4);
+        // halted on the opcode fetch
+        assert!(cpu.step() == 2 + 513);
+        assert!(cpu.cycles == 8 + 513);
+    }
+    #[test]
+    fn test_dma_stall_odd_cycle() {
+        // lda $00, sta $4014, nop
+        let mut cpu = cpu(&[0xa5, 0x00, 0x8d, 0x14, 0x40, 0xea]);
+        cpu.step();
+        cpu.step();
+        assert!(cpu.step() == 2 + 514);
+        assert!(cpu.cycles == 9 + 514);
+    }
+}
\ No newline at end of file
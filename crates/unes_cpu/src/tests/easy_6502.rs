@@ -3,7 +3,6 @@
 #[cfg(test)]
 mod tests {
     use crate::CPU;
-    use crate::flags::*;
 
     #[test]
     fn test_first() {
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use crate::CPU;
+    use crate::flags::*;
+
+    #[test]
+    fn test_nmi() {
+        let mut cpu = CPU::new();
+        cpu.load::<2>(0xfffa, &[0x00, 0x90]);
+        cpu.load_executable::<2>(0x8000, &[0xe8, 0xe8]);
+        cpu.set_flag(CARRY_FLAG, true);
+        cpu.set_nmi(true);
+        assert!(cpu.step() == 7);
+        assert!(cpu.pc == 0x9000);
+        assert!(cpu.sp == 0xfc);
+        assert!(cpu.check_flag(INTERRUPT_FLAG));
+        // return address and status with B clear
+        assert!(cpu.memory.read_u16(0x01fe) == 0x8000);
+        assert!(cpu.memory.read(0x01fd) == 0b0010_0001);
+    }
+    #[test]
+    fn test_nmi_edge_triggered() {
+        let mut cpu = CPU::new();
+        cpu.load::<2>(0xfffa, &[0x00, 0x90]);
+        cpu.load::<1>(0x9000, &[0xe8]);
+        cpu.load_executable::<1>(0x8000, &[0xe8]);
+        cpu.set_nmi(true);
+        cpu.step();
+        // a line held high does not trigger again
+        cpu.set_nmi(true);
+        cpu.step();
+        assert!(cpu.pc == 0x9001);
+        cpu.set_nmi(false);
+        cpu.set_nmi(true);
+        cpu.step();
+        assert!(cpu.pc == 0x9000);
+    }
+    #[test]
+    fn test_stack() {
+        let mut cpu = CPU::new();
+        cpu.stack_push_u16(0x1234);
+        cpu.stack_push(0x56);
+        assert!(cpu.sp == 0xfc);
+        assert!(cpu.stack_pop() == 0x56);
+        assert!(cpu.stack_pop_u16() == 0x1234);
+        assert!(cpu.sp == 0xff);
+    }
+    #[test]
+    fn test_irq() {
+        let mut cpu = CPU::new();
+        cpu.load::<2>(0xfffe, &[0x00, 0xa0]);
+        // cli, inx
+        cpu.load_executable::<2>(0x8000, &[0x58, 0xe8]);
+        cpu.set_flag(INTERRUPT_FLAG, true);
+        cpu.set_irq(true);
+        // masked
+        cpu.step();
+        assert!(cpu.pc == 0x8001);
+        assert!(cpu.step() == 7);
+        assert!(cpu.pc == 0xa000 && cpu.check_flag(INTERRUPT_FLAG));
+        assert!(cpu.memory.read(0x01fd) & BREAK_FLAG == 0);
+    }
+    #[test]
+    fn test_brk_rti() {
+        let mut cpu = CPU::new();
+        cpu.load::<2>(0xfffe, &[0x00, 0xa0]);
+        cpu.load::<1>(0xa000, &[0x40]);
+        cpu.load::<1>(0x8000, &[0x00]);
+        cpu.pc = 0x8000;
+        cpu.set_flag(CARRY_FLAG, true);
+        assert!(cpu.step() == 7);
+        assert!(cpu.pc == 0xa000 && cpu.check_flag(INTERRUPT_FLAG));
+        // the padding byte is skipped, B is set in the pushed status
+        assert!(cpu.memory.read_u16(0x01fe) == 0x8002);
+        assert!(cpu.memory.read(0x01fd) == 0b0011_0001);
+        assert!(cpu.step() == 6);
+        assert!(cpu.pc == 0x8002 && cpu.sp == 0xff);
+        assert!(cpu.check_flag(CARRY_FLAG) && !cpu.check_flag(INTERRUPT_FLAG));
+    }
+    #[test]
+    fn test_reset() {
+        let mut cpu = CPU::new();
+        cpu.load::<2>(0xfffc, &[0x34, 0x12]);
+        cpu.sp = 0;
+        cpu.reset();
+        assert!(cpu.pc == 0x1234 && cpu.sp == 0xfd);
+        assert!(cpu.check_flag(INTERRUPT_FLAG) && cpu.running);
+        assert!(cpu.cycles == 7);
+    }
+}
\ No newline at end of file
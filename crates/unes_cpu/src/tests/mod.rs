@@ -1,4 +1,7 @@
 mod addressing;
+mod bus;
 mod combined;
 mod easy_6502;
+mod interrupts;
 mod opcodes;
+mod state;
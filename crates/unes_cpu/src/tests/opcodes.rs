@@ -259,4 +259,189 @@ mod tests {
         assert!(!cpu.check_flag(ZERO_FLAG));
         assert!(!cpu.check_flag(NEGATIVE_FLAG)); 
     }
+    #[test]
+    fn test_adc_carry_in_overflow() {
+        // 0xff + 0x00 + carry wraps to 0 and carries out
+        let mut cpu = CPU::new();
+        cpu.reg_a = 0xff;
+        cpu.set_flag(CARRY_FLAG, true);
+        cpu.load_executable::<2>(0x8000, &[0x69, 0x00]);
+        cpu.step();
+        assert!(cpu.reg_a == 0x00);
+        assert!(cpu.check_flag(CARRY_FLAG) && cpu.check_flag(ZERO_FLAG));
+    }
+    #[test]
+    fn test_sbc() {
+        let mut cpu = CPU::new();
+        // sec, lda #$50, sbc #$f0, sbc #$b0
+        cpu.load_executable::<7>(0x8000, &[0x38, 0xa9, 0x50, 0xe9, 0xf0, 0xe9, 0xb0]);
+        cpu.step();
+        cpu.step();
+        cpu.step();
+        // borrow, no overflow
+        assert!(cpu.reg_a == 0x60);
+        assert!(!cpu.check_flag(CARRY_FLAG) && !cpu.check_flag(OVERFLOW_FLAG));
+        cpu.step();
+        // 0x60 - 0xb0 - 1, signed overflow
+        assert!(cpu.reg_a == 0xaf);
+        assert!(!cpu.check_flag(CARRY_FLAG) && cpu.check_flag(OVERFLOW_FLAG) && cpu.check_flag(NEGATIVE_FLAG));
+    }
+    #[test]
+    fn test_logic() {
+        let mut cpu = CPU::new();
+        // lda #$f0, and #$3c, ora #$01, eor #$ff
+        cpu.load_executable::<8>(0x8000, &[0xa9, 0xf0, 0x29, 0x3c, 0x09, 0x01, 0x49, 0xff]);
+        cpu.step();
+        cpu.step();
+        assert!(cpu.reg_a == 0x30);
+        cpu.step();
+        assert!(cpu.reg_a == 0x31);
+        cpu.step();
+        assert!(cpu.reg_a == 0xce && cpu.check_flag(NEGATIVE_FLAG));
+    }
+    #[test]
+    fn test_shifts() {
+        let mut cpu = CPU::new();
+        // lda #$81, asl a, rol a, lsr a, ror a
+        cpu.load_executable::<6>(0x8000, &[0xa9, 0x81, 0x0a, 0x2a, 0x4a, 0x6a]);
+        cpu.step();
+        cpu.step();
+        assert!(cpu.reg_a == 0x02 && cpu.check_flag(CARRY_FLAG));
+        cpu.step();
+        assert!(cpu.reg_a == 0x05 && !cpu.check_flag(CARRY_FLAG));
+        cpu.step();
+        assert!(cpu.reg_a == 0x02 && cpu.check_flag(CARRY_FLAG));
+        cpu.step();
+        assert!(cpu.reg_a == 0x81 && !cpu.check_flag(CARRY_FLAG));
+    }
+    #[test]
+    fn test_read_modify_write() {
+        let mut cpu = CPU::new();
+        // inc $10, dec $11,x, asl $0200
+        cpu.load_executable::<7>(0x8000, &[0xe6, 0x10, 0xd6, 0x10, 0x0e, 0x00, 0x02]);
+        cpu.load::<2>(0x10, &[0xff, 0x01]);
+        cpu.load::<1>(0x0200, &[0xc0]);
+        cpu.reg_x = 1;
+        assert!(cpu.step() == 5);
+        assert!(cpu.memory.read(0x10) == 0 && cpu.check_flag(ZERO_FLAG));
+        assert!(cpu.step() == 6);
+        assert!(cpu.memory.read(0x11) == 0);
+        assert!(cpu.step() == 6);
+        assert!(cpu.memory.read(0x0200) == 0x80 && cpu.check_flag(CARRY_FLAG));
+    }
+    #[test]
+    fn test_compare_and_bit() {
+        let mut cpu = CPU::new();
+        // ldy #$40, cpy #$41, cmp #$00, bit $10
+        cpu.load_executable::<8>(0x8000, &[0xa0, 0x40, 0xc0, 0x41, 0xc9, 0x00, 0x24, 0x10]);
+        cpu.load::<1>(0x10, &[0xc0]);
+        cpu.step();
+        cpu.step();
+        assert!(!cpu.check_flag(CARRY_FLAG) && cpu.check_flag(NEGATIVE_FLAG));
+        cpu.step();
+        assert!(cpu.check_flag(CARRY_FLAG) && cpu.check_flag(ZERO_FLAG));
+        cpu.step();
+        // a is 0, the top bits come from memory
+        assert!(cpu.check_flag(ZERO_FLAG) && cpu.check_flag(OVERFLOW_FLAG) && cpu.check_flag(NEGATIVE_FLAG));
+    }
+    #[test]
+    fn test_branches() {
+        let mut cpu = CPU::new();
+        // bcs (not taken), bpl +2, ..., bmi (not taken), beq -8
+        cpu.load_executable::<8>(0x8000, &[0xb0, 0x7f, 0x10, 0x02, 0xea, 0xea, 0x30, 0x7f]);
+        assert!(cpu.step() == 2);
+        assert!(cpu.step() == 3);
+        assert!(cpu.pc == 0x8006);
+        assert!(cpu.step() == 2);
+        cpu.set_flag(OVERFLOW_FLAG, true);
+        // bvs back across the page
+        cpu.load::<2>(0x8100, &[0x70, 0xfb]);
+        cpu.pc = 0x8100;
+        assert!(cpu.step() == 4);
+        assert!(cpu.pc == 0x80fd);
+    }
+    #[test]
+    fn test_jsr_rts() {
+        let mut cpu = CPU::new();
+        cpu.load_executable::<3>(0x8000, &[0x20, 0x00, 0x90]);
+        cpu.load::<1>(0x9000, &[0x60]);
+        assert!(cpu.step() == 6);
+        assert!(cpu.pc == 0x9000 && cpu.sp == 0xfd);
+        // the pushed address points at the last byte of jsr
+        assert!(cpu.memory.read_u16(0x01fe) == 0x8002);
+        assert!(cpu.step() == 6);
+        assert!(cpu.pc == 0x8003 && cpu.sp == 0xff);
+    }
+    #[test]
+    fn test_stack_ops() {
+        let mut cpu = CPU::new();
+        // lda #$80, pha, php, lda #$00, plp, pla
+        cpu.load_executable::<8>(0x8000, &[0xa9, 0x80, 0x48, 0x08, 0xa9, 0x00, 0x28, 0x68]);
+        cpu.step();
+        cpu.step();
+        cpu.step();
+        // php pushes B and the unused bit
+        assert!(cpu.memory.read(0x01fe) == 0b1011_0000);
+        cpu.step();
+        assert!(cpu.check_flag(ZERO_FLAG) && !cpu.check_flag(NEGATIVE_FLAG));
+        cpu.step();
+        assert!(!cpu.check_flag(ZERO_FLAG) && cpu.check_flag(NEGATIVE_FLAG));
+        cpu.step();
+        assert!(cpu.reg_a == 0x80 && cpu.sp == 0xff);
+    }
+    #[test]
+    fn test_transfers() {
+        let mut cpu = CPU::new();
+        // lda #$80, tay, tsx, txs, dey, iny, tya
+        cpu.load_executable::<8>(0x8000, &[0xa9, 0x80, 0xa8, 0xba, 0x9a, 0x88, 0xc8, 0x98]);
+        cpu.step();
+        cpu.step();
+        assert!(cpu.reg_y == 0x80);
+        cpu.step();
+        assert!(cpu.reg_x == 0xff && cpu.check_flag(NEGATIVE_FLAG));
+        cpu.step();
+        cpu.step();
+        assert!(cpu.reg_y == 0x7f && !cpu.check_flag(NEGATIVE_FLAG));
+        cpu.step();
+        cpu.step();
+        assert!(cpu.reg_a == 0x80 && cpu.sp == 0xff);
+    }
+    #[test]
+    fn test_unofficial() {
+        let mut cpu = CPU::new();
+        // lax $10, sax $11, dcp $12, isb $13, slo $14
+        cpu.load_executable::<10>(0x8000, &[0xa7, 0x10, 0x87, 0x11, 0xc7, 0x12, 0xe7, 0x13, 0x07, 0x14]);
+        cpu.load::<5>(0x10, &[0x0f, 0x00, 0x10, 0x01, 0x40]);
+        cpu.step();
+        assert!(cpu.reg_a == 0x0f && cpu.reg_x == 0x0f);
+        cpu.step();
+        assert!(cpu.memory.read(0x11) == 0x0f);
+        cpu.step();
+        // decremented to 0x0f, then compared with a
+        assert!(cpu.memory.read(0x12) == 0x0f && cpu.check_flag(ZERO_FLAG) && cpu.check_flag(CARRY_FLAG));
+        cpu.step();
+        // 0x0f - 0x02
+        assert!(cpu.reg_a == 0x0d);
+        assert!(cpu.step() == 5);
+        assert!(cpu.memory.read(0x14) == 0x80 && cpu.reg_a == 0x8d);
+    }
+    #[test]
+    fn test_unofficial_nops() {
+        let mut cpu = CPU::new();
+        cpu.load_executable::<6>(0x8000, &[0x1a, 0x80, 0x01, 0xfc, 0xff, 0x80]);
+        cpu.reg_x = 1;
+        assert!(cpu.step() == 2);
+        assert!(cpu.step() == 2);
+        // the absolute,x one reads and pays for the page cross
+        assert!(cpu.step() == 5);
+        assert!(cpu.pc == 0x8006 && cpu.reg_a == 0);
+    }
+    #[test]
+    fn test_jam() {
+        let mut cpu = CPU::new();
+        cpu.load_executable::<1>(0x8000, &[0x02]);
+        cpu.step();
+        cpu.step();
+        assert!(!cpu.running && cpu.pc == 0x8000);
+    }
 }
\ No newline at end of file
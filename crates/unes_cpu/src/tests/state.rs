@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests {
+    use unes_state::StateError;
+
+    use crate::CPU;
+
+    // inx, inc $10, jmp $8000
+    const COUNT: [u8; 6] = [0xe8, 0xe6, 0x10, 0x4c, 0x00, 0x80];
+
+    #[test]
+    fn test_save_load() {
+        let mut cpu = CPU::new();
+        cpu.load_executable::<6>(0x8000, &COUNT);
+        for _ in 0..10 { cpu.step(); }
+        let state = cpu.save_state();
+        let (x, pc, cycles) = (cpu.reg_x, cpu.pc, cpu.cycles);
+        for _ in 0..7 { cpu.step(); }
+        cpu.set_nmi(true);
+        cpu.load_state(&state).unwrap();
+        assert!(cpu.reg_x == x && cpu.pc == pc && cpu.cycles == cycles && !cpu.nmi_pending);
+        assert!(cpu.memory.read(0x10) == 3);
+        // runs on the same way
+        for _ in 0..3 { cpu.step(); }
+        assert!(cpu.reg_x == 5 && cpu.memory.read(0x10) == 4);
+    }
+    #[test]
+    fn test_refused_state() {
+        let mut cpu = CPU::new();
+        cpu.load_executable::<6>(0x8000, &COUNT);
+        let mut state = cpu.save_state();
+        cpu.step();
+        // the running flag, first in the cpu chunk
+        state[16] = 2;
+        assert!(cpu.load_state(&state) == Err(StateError::InvalidData));
+        assert!(cpu.reg_x == 1 && cpu.pc == 0x8001);
+        assert!(cpu.load_state(&state[..100]) == Err(StateError::Truncated));
+    }
+}
\ No newline at end of file
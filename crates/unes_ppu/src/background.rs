@@ -0,0 +1,61 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// latches filled by the tile fetches and the shift registers feeding the pixel output
+// The pattern shifters hold two tiles, the high byte is the one being drawn.
+// The attribute shifters are expanded from the 2 bit palette number so they
+// can be shifted in lockstep with the pattern.
+#[derive(Default)]
+pub struct Background {
+    pub tile: u8,
+    pub attribute: u8,
+    pub pattern_lo: u8,
+    pub pattern_hi: u8,
+    pub shift_lo: u16,
+    pub shift_hi: u16,
+    pub shift_attr_lo: u16,
+    pub shift_attr_hi: u16
+}
+impl Background {
+    pub fn shift(&mut self) {
+        self.shift_lo <<= 1;
+        self.shift_hi <<= 1;
+        self.shift_attr_lo <<= 1;
+        self.shift_attr_hi <<= 1;
+    }
+    // moves the fetched tile into the low byte of the shifters
+    pub fn reload(&mut self) {
+        let expand = |bit: u8| if self.attribute & bit != 0 { 0xff } else { 0x00 };
+        self.shift_lo = self.shift_lo & 0xff00 | self.pattern_lo as u16;
+        self.shift_hi = self.shift_hi & 0xff00 | self.pattern_hi as u16;
+        self.shift_attr_lo = self.shift_attr_lo & 0xff00 | expand(1);
+        self.shift_attr_hi = self.shift_attr_hi & 0xff00 | expand(2);
+    }
+    // color (0-3) and palette (0-3) of the pixel selected by fine x
+    pub fn pixel(&self, fine_x: u8) -> (u8, u8) {
+        let bit = 0x8000 >> fine_x;
+        let pick = |shifter: u16| (shifter & bit != 0) as u8;
+        (pick(self.shift_hi) << 1 | pick(self.shift_lo), pick(self.shift_attr_hi) << 1 | pick(self.shift_attr_lo))
+    }
+}
+impl Snapshot for Background {
+    fn save(&self, w: &mut StateWriter) {
+        self.tile.save(w);
+        self.attribute.save(w);
+        self.pattern_lo.save(w);
+        self.pattern_hi.save(w);
+        self.shift_lo.save(w);
+        self.shift_hi.save(w);
+        self.shift_attr_lo.save(w);
+        self.shift_attr_hi.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.tile.load(r)?;
+        self.attribute.load(r)?;
+        self.pattern_lo.load(r)?;
+        self.pattern_hi.load(r)?;
+        self.shift_lo.load(r)?;
+        self.shift_hi.load(r)?;
+        self.shift_attr_lo.load(r)?;
+        self.shift_attr_hi.load(r)
+    }
+}
\ No newline at end of file
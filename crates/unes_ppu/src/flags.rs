@@ -0,0 +1,20 @@
+// PPUCTRL ($2000)
+pub const CTRL_NAMETABLE: u8 = 0b0000_0011;
+pub const CTRL_INCREMENT: u8 = 0b0000_0100;
+pub const CTRL_SPRITE_TABLE: u8 = 0b0000_1000;
+pub const CTRL_BACKGROUND_TABLE: u8 = 0b0001_0000;
+pub const CTRL_SPRITE_SIZE: u8 = 0b0010_0000;
+pub const CTRL_NMI: u8 = 0b1000_0000;
+
+// PPUMASK ($2001)
+pub const MASK_GREYSCALE: u8 = 0b0000_0001;
+pub const MASK_BACKGROUND_LEFT: u8 = 0b0000_0010;
+pub const MASK_SPRITES_LEFT: u8 = 0b0000_0100;
+pub const MASK_BACKGROUND: u8 = 0b0000_1000;
+pub const MASK_SPRITES: u8 = 0b0001_0000;
+pub const MASK_EMPHASIS: u8 = 0b1110_0000;
+
+// PPUSTATUS ($2002)
+pub const STATUS_OVERFLOW: u8 = 0b0010_0000;
+pub const STATUS_SPRITE_ZERO: u8 = 0b0100_0000;
+pub const STATUS_VBLANK: u8 = 0b1000_0000;
\ No newline at end of file
@@ -0,0 +1,11 @@
+#![no_std]
+extern crate alloc;
+
+mod background;
+pub mod flags;
+mod ppu;
+mod sprites;
+pub mod vram;
+mod tests;
+
+pub use ppu::{Layout, PPU, WIDTH, HEIGHT};
\ No newline at end of file
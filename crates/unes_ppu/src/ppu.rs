@@ -0,0 +1,449 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use unes_cartridge::Mapper;
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+use crate::background::Background;
+use crate::flags::*;
+use crate::sprites::Sprites;
+use crate::vram::{self, Target, palette_index};
+
+pub const WIDTH: usize = 256;
+pub const HEIGHT: usize = 240;
+
+pub const DOTS_PER_SCANLINE: u16 = 341;
+const VISIBLE_SCANLINES: u16 = 240;
+// the bits of the io latch fade out after about 600ms without a refresh
+const IO_DECAY_FRAMES: u64 = 36;
+
+// frame timing of the different consoles, the last scanline is the pre-render one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub scanlines: u16,
+    pub vblank_scanline: u16,
+    // odd frames drop a dot of the pre-render line while rendering
+    pub odd_frame_skip: bool
+}
+impl Layout {
+    pub const NTSC: Layout = Layout { scanlines: 262, vblank_scanline: 241, odd_frame_skip: true };
+    pub const PAL: Layout = Layout { scanlines: 312, vblank_scanline: 241, odd_frame_skip: false };
+    // the Dendy idles 50 lines after the picture before raising vblank
+    pub const DENDY: Layout = Layout { scanlines: 312, vblank_scanline: 291, odd_frame_skip: false };
+}
+
+pub struct PPU {
+    // cpu facing registers
+    pub ctrl: u8,
+    pub mask: u8,
+    pub status: u8,
+    pub oam_addr: u8,
+    pub oam: [u8; 256],
+
+    // internal "loopy" registers: current and temporary vram address,
+    // fine x scroll and the write toggle shared by $2005 and $2006
+    pub v: u16,
+    pub t: u16,
+    pub x: u8,
+    pub w: bool,
+    // PPUDATA reads go through this buffer, except for the palette
+    pub read_buffer: u8,
+    // last value seen on the cpu <-> ppu data bus, with the frame each bit was last driven
+    pub io_latch: u8,
+    pub io_refreshed: [u64; 8],
+    // $2002 was read just before vblank, the flag (and nmi) is skipped this frame
+    pub suppress_vblank: bool,
+
+    // 2KB of nametable ram inside the console, routed through the mapper
+    pub ciram: [u8; 0x800],
+    pub palette: [u8; 32],
+
+    pub background: Background,
+    pub sprites: Sprites,
+    // 6 bit color index and the 3 emphasis bits above it, one entry per pixel
+    pub framebuffer: Vec<u16>,
+
+    pub layout: Layout,
+    pub scanline: u16,
+    pub dot: u16,
+    pub frame: u64
+}
+impl Default for PPU {
+    fn default() -> Self {
+        PPU {
+            ctrl: 0,
+            mask: 0,
+            status: 0,
+            oam_addr: 0,
+            oam: [0; 256],
+            v: 0,
+            t: 0,
+            x: 0,
+            w: false,
+            read_buffer: 0,
+            io_latch: 0,
+            io_refreshed: [0; 8],
+            suppress_vblank: false,
+            ciram: [0; 0x800],
+            palette: [0; 32],
+            background: Background::default(),
+            sprites: Sprites::default(),
+            framebuffer: vec![0; WIDTH * HEIGHT],
+            layout: Layout::NTSC,
+            scanline: 0,
+            dot: 0,
+            frame: 0
+        }
+    }
+}
+impl PPU {
+    pub fn new() -> PPU {
+        PPU::default()
+    }
+    // level of the /NMI output, the cpu triggers on its rising edge
+    pub fn nmi_line(&self) -> bool {
+        self.status & STATUS_VBLANK != 0 && self.ctrl & CTRL_NMI != 0
+    }
+    pub fn pre_render_scanline(&self) -> u16 {
+        self.layout.scanlines - 1
+    }
+    pub fn rendering_enabled(&self) -> bool {
+        self.mask & (MASK_BACKGROUND | MASK_SPRITES) != 0
+    }
+    // cpu access to $2000-$3fff (mirrored every 8 bytes)
+    pub fn read_register(&mut self, addr: u16, mapper: &mut dyn Mapper) -> u8 {
+        // bits the register drives, the others return what is left on the bus
+        let (value, driven) = match addr & 7 {
+            2 => {
+                // a read on the very dot the flag goes up sees it clear and cancels it
+                if (self.scanline, self.dot) == (self.layout.vblank_scanline, 1) { self.suppress_vblank = true }
+                let value = self.status & 0xe0;
+                self.status &= !STATUS_VBLANK;
+                self.w = false;
+                (value, 0xe0)
+            },
+            4 => (self.oam[self.oam_addr as usize], 0xff),
+            7 => self.read_data(mapper),
+            _ => (0, 0)
+        };
+        self.refresh_latch(value, driven);
+        self.io_latch
+    }
+    pub fn write_register(&mut self, addr: u16, value: u8, mapper: &mut dyn Mapper) {
+        self.refresh_latch(value, 0xff);
+        match addr & 7 {
+            0 => {
+                self.ctrl = value;
+                self.t = self.t & !0x0c00 | ((value & CTRL_NAMETABLE) as u16) << 10;
+            },
+            1 => self.mask = value,
+            3 => self.oam_addr = value,
+            4 => {
+                // bits 2-4 of the sprite attributes do not exist
+                let value = if self.oam_addr & 3 == 2 { value & 0xe3 } else { value };
+                self.oam[self.oam_addr as usize] = value;
+                self.oam_addr = self.oam_addr.wrapping_add(1);
+            },
+            5 => {
+                if !self.w {
+                    self.t = self.t & !0x001f | (value >> 3) as u16;
+                    self.x = value & 7;
+                } else {
+                    self.t = self.t & !0x73e0 | ((value & 7) as u16) << 12 | ((value & 0xf8) as u16) << 2;
+                }
+                self.w = !self.w;
+            },
+            6 => {
+                if !self.w {
+                    self.t = self.t & 0x00ff | ((value & 0x3f) as u16) << 8;
+                } else {
+                    self.t = self.t & 0xff00 | value as u16;
+                    self.v = self.t;
+                }
+                self.w = !self.w;
+            },
+            7 => {
+                self.write_vram(self.v, value, mapper);
+                self.increment_v();
+            },
+            _ => ()
+        }
+    }
+    fn read_data(&mut self, mapper: &mut dyn Mapper) -> (u8, u8) {
+        let addr = self.v & 0x3fff;
+        let read = if addr >= 0x3f00 {
+            // palette reads are immediate (6 bits, greyscale applied),
+            // the buffer gets the nametable byte "under" it
+            self.read_buffer = self.read_vram(addr - 0x1000, mapper);
+            let mut value = self.read_vram(addr, mapper);
+            if self.mask & MASK_GREYSCALE != 0 { value &= 0x30 }
+            (value, 0x3f)
+        } else {
+            let value = self.read_buffer;
+            self.read_buffer = self.read_vram(addr, mapper);
+            (value, 0xff)
+        };
+        self.increment_v();
+        read
+    }
+    fn refresh_latch(&mut self, value: u8, bits: u8) {
+        self.io_latch = self.io_latch & !bits | value & bits;
+        for (bit, refreshed) in self.io_refreshed.iter_mut().enumerate() {
+            if bits & (1 << bit) != 0 { *refreshed = self.frame }
+        }
+    }
+    fn decay_latch(&mut self) {
+        for (bit, &refreshed) in self.io_refreshed.iter().enumerate() {
+            if self.frame - refreshed >= IO_DECAY_FRAMES { self.io_latch &= !(1 << bit) }
+        }
+    }
+    fn increment_v(&mut self) {
+        let step = if self.ctrl & CTRL_INCREMENT != 0 { 32 } else { 1 };
+        self.v = self.v.wrapping_add(step) & 0x7fff;
+    }
+    // ppu address space: pattern tables, nametables and their mirror, palette
+    pub fn read_vram(&mut self, addr: u16, mapper: &mut dyn Mapper) -> u8 {
+        match vram::decode(addr) {
+            Target::Pattern(addr) => mapper.ppu_read(addr),
+            Target::Nametable(addr) => mapper.nametable_read(addr, &self.ciram),
+            Target::Palette(index) => self.palette[index]
+        }
+    }
+    pub fn write_vram(&mut self, addr: u16, value: u8, mapper: &mut dyn Mapper) {
+        match vram::decode(addr) {
+            Target::Pattern(addr) => mapper.ppu_write(addr, value),
+            Target::Nametable(addr) => mapper.nametable_write(addr, value, &mut self.ciram),
+            Target::Palette(index) => self.palette[index] = value & 0x3f
+        }
+    }
+    // advances a single dot
+    pub fn tick(&mut self, mapper: &mut dyn Mapper) {
+        let visible = self.scanline < VISIBLE_SCANLINES;
+        let pre_render = self.pre_render_scanline();
+        if (visible || self.scanline == pre_render) && self.rendering_enabled() {
+            self.fetch(mapper);
+        }
+        if visible && (1..=256).contains(&self.dot) {
+            self.output_pixel();
+        }
+        if self.dot == 1 {
+            if self.scanline == self.layout.vblank_scanline {
+                if !self.suppress_vblank { self.status |= STATUS_VBLANK }
+                self.suppress_vblank = false;
+            } else if self.scanline == pre_render {
+                self.status &= !(STATUS_VBLANK | STATUS_SPRITE_ZERO | STATUS_OVERFLOW);
+            }
+        }
+        self.dot += 1;
+        // with rendering on, the pre-render line of odd frames is one dot shorter
+        let skip = self.layout.odd_frame_skip && self.scanline == pre_render && self.dot == DOTS_PER_SCANLINE - 1
+            && self.frame & 1 == 1 && self.rendering_enabled();
+        if self.dot == DOTS_PER_SCANLINE || skip {
+            self.dot = 0;
+            self.scanline += 1;
+            if self.scanline >= self.layout.scanlines {
+                self.scanline = 0;
+                self.frame += 1;
+                self.decay_latch();
+            }
+        }
+    }
+    // memory accesses and scroll updates of a rendering scanline
+    // Each tile takes 8 dots: nametable, attribute, pattern low and high byte.
+    // Dots 1-256 fetch the tiles 2-33 of the line, 257-320 are the sprite
+    // fetches (with two unused nametable reads each), 321-336 fetch the first
+    // two tiles of the next line and 337/339 are two more unused nametable reads.
+    fn fetch(&mut self, mapper: &mut dyn Mapper) {
+        let dot = self.dot;
+        if matches!(dot, 2..=257 | 322..=337) {
+            self.background.shift();
+            if dot & 7 == 1 { self.background.reload() }
+        }
+        match dot {
+            1..=256 | 321..=336 => match (dot - 1) & 7 {
+                0 => self.background.tile = self.fetch_nametable(mapper),
+                2 => self.background.attribute = self.fetch_attribute(mapper),
+                4 => self.background.pattern_lo = self.fetch_pattern(0, mapper),
+                6 => self.background.pattern_hi = self.fetch_pattern(8, mapper),
+                7 => self.increment_x(),
+                _ => ()
+            },
+            257..=320 => {
+                // oam is busy with the sprite fetches
+                self.oam_addr = 0;
+                let slot = (dot as usize - 257) / 8;
+                match (dot - 1) & 7 {
+                    0 | 2 => { self.fetch_nametable(mapper); },
+                    4 => self.sprites.slots[slot].pattern_lo = self.fetch_sprite_pattern(slot, 0, mapper),
+                    6 => self.sprites.slots[slot].pattern_hi = self.fetch_sprite_pattern(slot, 8, mapper),
+                    _ => ()
+                }
+            },
+            337 | 339 => { self.fetch_nametable(mapper); },
+            _ => ()
+        }
+        match dot {
+            256 => {
+                self.increment_y();
+                // sprites are evaluated over dots 65-256, there are none on the line after the pre-render one
+                if self.scanline == self.pre_render_scanline() {
+                    self.sprites.clear();
+                } else if self.sprites.evaluate(&self.oam, self.scanline, self.sprite_height()) {
+                    self.status |= STATUS_OVERFLOW;
+                }
+            },
+            257 => {
+                self.v = self.v & !0x041f | self.t & 0x041f;
+                self.sprites.count = self.sprites.found;
+                self.sprites.zero_loaded = self.sprites.zero_found;
+                for (i, slot) in self.sprites.slots.iter_mut().enumerate() {
+                    slot.attributes = self.sprites.secondary[i * 4 + 2];
+                    slot.x = self.sprites.secondary[i * 4 + 3];
+                }
+            },
+            280..=304 if self.scanline == self.pre_render_scanline() => {
+                self.v = self.v & !0x7be0 | self.t & 0x7be0;
+            },
+            _ => ()
+        }
+    }
+    fn fetch_nametable(&mut self, mapper: &mut dyn Mapper) -> u8 {
+        self.read_vram(0x2000 | self.v & 0x0fff, mapper)
+    }
+    // the 2 bit palette of the 16x16 area the tile is in
+    fn fetch_attribute(&mut self, mapper: &mut dyn Mapper) -> u8 {
+        let v = self.v;
+        let addr = 0x23c0 | v & 0x0c00 | (v >> 4) & 0x38 | (v >> 2) & 0x07;
+        let shift = (v >> 4) & 4 | v & 2;
+        (self.read_vram(addr, mapper) >> shift) & 3
+    }
+    fn fetch_pattern(&mut self, plane: u16, mapper: &mut dyn Mapper) -> u8 {
+        let table = if self.ctrl & CTRL_BACKGROUND_TABLE != 0 { 0x1000 } else { 0 };
+        let fine_y = (self.v >> 12) & 7;
+        self.read_vram(table + (self.background.tile as u16) * 16 + plane + fine_y, mapper)
+    }
+    fn sprite_height(&self) -> u16 {
+        if self.ctrl & CTRL_SPRITE_SIZE != 0 { 16 } else { 8 }
+    }
+    // unused slots still fetch tile $ff, the data is thrown away
+    fn fetch_sprite_pattern(&mut self, slot: usize, plane: u16, mapper: &mut dyn Mapper) -> u8 {
+        let sprite = &self.sprites.secondary[slot * 4..slot * 4 + 3];
+        let (y, tile, attributes) = (sprite[0] as u16, sprite[1] as u16, sprite[2]);
+        let height = self.sprite_height();
+        let mut row = self.scanline.wrapping_sub(y) & (height - 1);
+        if attributes & 0x80 != 0 { row = height - 1 - row }
+        let addr = if height == 16 {
+            // 8x16 sprites take the pattern table from bit 0 of the tile
+            (tile & 1) * 0x1000 + (tile & 0xfe) * 16 + (row & 8) * 2 + (row & 7)
+        } else {
+            let table = if self.ctrl & CTRL_SPRITE_TABLE != 0 { 0x1000 } else { 0 };
+            table + tile * 16 + row
+        };
+        let data = self.read_vram(addr + plane, mapper);
+        if slot >= self.sprites.found {
+            0
+        } else if attributes & 0x40 != 0 {
+            data.reverse_bits()
+        } else {
+            data
+        }
+    }
+    // coarse x, wrapping into the horizontally adjacent nametable
+    fn increment_x(&mut self) {
+        if self.v & 0x001f == 31 {
+            self.v = (self.v & !0x001f) ^ 0x0400;
+        } else {
+            self.v += 1;
+        }
+    }
+    // fine y, then coarse y, wrapping into the vertically adjacent nametable after row 29
+    fn increment_y(&mut self) {
+        if self.v & 0x7000 != 0x7000 {
+            self.v += 0x1000;
+            return
+        }
+        self.v &= !0x7000;
+        let coarse_y = match (self.v >> 5) & 0x1f {
+            29 => {
+                self.v ^= 0x0800;
+                0
+            },
+            // rows 30 and 31 are the attribute table, they wrap without switching
+            31 => 0,
+            y => y + 1
+        };
+        self.v = self.v & !0x03e0 | coarse_y << 5;
+    }
+    fn output_pixel(&mut self) {
+        let x = self.dot as usize - 1;
+        let left = x < 8;
+        let show_background = self.mask & MASK_BACKGROUND != 0 && (!left || self.mask & MASK_BACKGROUND_LEFT != 0);
+        let show_sprites = self.mask & MASK_SPRITES != 0 && (!left || self.mask & MASK_SPRITES_LEFT != 0);
+        let (color, palette) = if show_background { self.background.pixel(self.x) } else { (0, 0) };
+        let background = if color == 0 { 0 } else { palette * 4 + color };
+        let sprite = if show_sprites { self.sprites.pixel(x as u8) } else { None };
+        let index = match sprite {
+            Some(sprite) => {
+                // a hit regardless of priority, but never on the last column
+                if sprite.sprite_zero && color != 0 && x != 255 { self.status |= STATUS_SPRITE_ZERO }
+                if color == 0 || !sprite.behind_background {
+                    0x10 + sprite.palette * 4 + sprite.color
+                } else {
+                    background
+                }
+            },
+            None => background
+        };
+        let mut color = self.palette[palette_index(index as usize)];
+        if self.mask & MASK_GREYSCALE != 0 { color &= 0x30 }
+        let emphasis = ((self.mask & MASK_EMPHASIS) as u16) << 1;
+        self.framebuffer[self.scanline as usize * WIDTH + x] = emphasis | color as u16;
+    }
+}
+// neither the framebuffer nor the layout (which follows the region) are saved
+impl Snapshot for PPU {
+    fn save(&self, w: &mut StateWriter) {
+        self.ctrl.save(w);
+        self.mask.save(w);
+        self.status.save(w);
+        self.oam_addr.save(w);
+        self.oam.save(w);
+        self.v.save(w);
+        self.t.save(w);
+        self.x.save(w);
+        self.w.save(w);
+        self.read_buffer.save(w);
+        self.io_latch.save(w);
+        self.io_refreshed.save(w);
+        self.suppress_vblank.save(w);
+        self.ciram.save(w);
+        self.palette.save(w);
+        self.background.save(w);
+        self.sprites.save(w);
+        self.scanline.save(w);
+        self.dot.save(w);
+        self.frame.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ctrl.load(r)?;
+        self.mask.load(r)?;
+        self.status.load(r)?;
+        self.oam_addr.load(r)?;
+        self.oam.load(r)?;
+        self.v.load(r)?;
+        self.t.load(r)?;
+        self.x.load(r)?;
+        self.w.load(r)?;
+        self.read_buffer.load(r)?;
+        self.io_latch.load(r)?;
+        self.io_refreshed.load(r)?;
+        self.suppress_vblank.load(r)?;
+        self.ciram.load(r)?;
+        self.palette.load(r)?;
+        self.background.load(r)?;
+        self.sprites.load(r)?;
+        self.scanline.load(r)?;
+        self.dot.load(r)?;
+        self.frame.load(r)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,110 @@
+use unes_state::{Snapshot, StateError, StateReader, StateWriter};
+
+// a sprite fetched for the current scanline
+#[derive(Clone, Copy, Default)]
+pub struct Slot {
+    pub x: u8,
+    pub attributes: u8,
+    pub pattern_lo: u8,
+    pub pattern_hi: u8
+}
+impl Snapshot for Slot {
+    fn save(&self, w: &mut StateWriter) {
+        self.x.save(w);
+        self.attributes.save(w);
+        self.pattern_lo.save(w);
+        self.pattern_hi.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.x.load(r)?;
+        self.attributes.load(r)?;
+        self.pattern_lo.load(r)?;
+        self.pattern_hi.load(r)
+    }
+}
+
+// what the sprite side outputs for a single pixel
+pub struct SpritePixel {
+    pub color: u8,
+    pub palette: u8,
+    pub behind_background: bool,
+    pub sprite_zero: bool
+}
+
+// secondary oam filled by the evaluation of one line and the slots fetched from it
+// for the next one
+#[derive(Default)]
+pub struct Sprites {
+    pub secondary: [u8; 32],
+    pub found: usize,
+    pub zero_found: bool,
+    pub slots: [Slot; 8],
+    pub count: usize,
+    pub zero_loaded: bool
+}
+impl Sprites {
+    // picks the (up to 8) sprites of the next line, returns whether it overflowed
+    // After 8 sprites the hardware keeps scanning but increments the byte
+    // index together with the sprite index, so it compares tiles, attributes
+    // and x positions as y coordinates: both false positives and misses.
+    pub fn evaluate(&mut self, oam: &[u8; 256], scanline: u16, height: u16) -> bool {
+        let in_range = |y: u8| scanline.wrapping_sub(y as u16) < height;
+        self.clear();
+        let mut n = 0;
+        while n < 64 && self.found < 8 {
+            let sprite = &oam[n * 4..n * 4 + 4];
+            self.secondary[self.found * 4] = sprite[0];
+            if in_range(sprite[0]) {
+                self.secondary[self.found * 4..self.found * 4 + 4].copy_from_slice(sprite);
+                if n == 0 { self.zero_found = true }
+                self.found += 1;
+            }
+            n += 1;
+        }
+        let mut m = 0;
+        while n < 64 {
+            if in_range(oam[n * 4 + m]) { return true }
+            n += 1;
+            m = (m + 1) & 3;
+        }
+        false
+    }
+    pub fn clear(&mut self) {
+        self.secondary = [0xff; 32];
+        self.found = 0;
+        self.zero_found = false;
+    }
+    // the frontmost opaque sprite pixel at x
+    pub fn pixel(&self, x: u8) -> Option<SpritePixel> {
+        self.slots[..self.count].iter().enumerate().find_map(|(i, slot)| {
+            let offset = x.checked_sub(slot.x).filter(|&offset| offset < 8)?;
+            let bit = 7 - offset;
+            let color = ((slot.pattern_hi >> bit) & 1) << 1 | (slot.pattern_lo >> bit) & 1;
+            if color == 0 { return None }
+            Some(SpritePixel {
+                color,
+                palette: slot.attributes & 3,
+                behind_background: slot.attributes & 0x20 != 0,
+                sprite_zero: i == 0 && self.zero_loaded
+            })
+        })
+    }
+}
+impl Snapshot for Sprites {
+    fn save(&self, w: &mut StateWriter) {
+        self.secondary.save(w);
+        self.found.save(w);
+        self.zero_found.save(w);
+        self.slots.save(w);
+        self.count.save(w);
+        self.zero_loaded.save(w);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.secondary.load(r)?;
+        self.found.load(r)?;
+        self.zero_found.load(r)?;
+        self.slots.load(r)?;
+        self.count.load(r)?;
+        self.zero_loaded.load(r)
+    }
+}
\ No newline at end of file
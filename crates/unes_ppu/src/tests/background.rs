@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use unes_cartridge::Mapper;
+
+    use crate::{PPU, WIDTH};
+    use crate::flags::*;
+    use crate::tests::{cartridge, fill, run_frame};
+
+    // tile 1 is solid color 1, tile 2 solid color 2, nametable 0 is all tile 1
+    fn setup(ppu: &mut PPU, mapper: &mut dyn Mapper) {
+        fill(ppu, mapper, 0x0010, &[0xff; 8]);
+        fill(ppu, mapper, 0x0028, &[0xff; 8]);
+        fill(ppu, mapper, 0x2000, &[1; 0x3c0]);
+        fill(ppu, mapper, 0x3f00, &[0x0f, 0x11, 0x22, 0x00, 0x00, 0x15]);
+    }
+    fn pixel(ppu: &PPU, x: usize, y: usize) -> u16 {
+        ppu.framebuffer[y * WIDTH + x]
+    }
+
+    #[test]
+    fn test_solid_background() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut());
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_BACKGROUND_LEFT, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        assert!(ppu.framebuffer.iter().all(|&p| p == 0x11));
+    }
+    #[test]
+    fn test_disabled_shows_backdrop() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        assert!(ppu.framebuffer.iter().all(|&p| p == 0x0f));
+    }
+    #[test]
+    fn test_left_column_mask() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut());
+        ppu.write_register(0x2001, MASK_BACKGROUND, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 7, 100) == 0x0f && pixel(&ppu, 8, 100) == 0x11);
+    }
+    #[test]
+    fn test_attributes() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut());
+        // palette 1 for the top left 16x16 area, palette 0 for the rest
+        fill(&mut ppu, cart.mapper.as_mut(), 0x23c0, &[0b01]);
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_BACKGROUND_LEFT, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 15, 15) == 0x15);
+        assert!(pixel(&ppu, 16, 0) == 0x11 && pixel(&ppu, 0, 16) == 0x11);
+    }
+    #[test]
+    fn test_fine_x_scroll() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut());
+        // tile 2 in the first column, the nametable to the right stays blank
+        for row in 0..30 { fill(&mut ppu, cart.mapper.as_mut(), 0x2000 + row * 32, &[2]) }
+        ppu.write_register(0x2005, 3, cart.mapper.as_mut());
+        ppu.write_register(0x2005, 0, cart.mapper.as_mut());
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_BACKGROUND_LEFT, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 4, 50) == 0x22 && pixel(&ppu, 5, 50) == 0x11);
+        assert!(pixel(&ppu, 252, 50) == 0x11 && pixel(&ppu, 253, 50) == 0x0f);
+    }
+    #[test]
+    fn test_vertical_scroll() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut());
+        // row 2 uses tile 2
+        fill(&mut ppu, cart.mapper.as_mut(), 0x2040, &[2; 32]);
+        ppu.write_register(0x2005, 0, cart.mapper.as_mut());
+        ppu.write_register(0x2005, 13, cart.mapper.as_mut());
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_BACKGROUND_LEFT, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 0, 2) == 0x11 && pixel(&ppu, 0, 3) == 0x22);
+        assert!(pixel(&ppu, 0, 10) == 0x22 && pixel(&ppu, 0, 11) == 0x11);
+    }
+    #[test]
+    fn test_mid_frame_split() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut());
+        for row in 0..30 { fill(&mut ppu, cart.mapper.as_mut(), 0x2000 + row * 32, &[2]) }
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_BACKGROUND_LEFT, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        // change the horizontal scroll late on line 100, it is picked up at dot 257
+        while (ppu.scanline, ppu.dot) != (100, 200) { ppu.tick(cart.mapper.as_mut()) }
+        ppu.write_register(0x2005, 8, cart.mapper.as_mut());
+        ppu.write_register(0x2005, 0, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 0, 100) == 0x22 && pixel(&ppu, 0, 101) == 0x11);
+        // the vertical scroll is not touched
+        assert!(pixel(&ppu, 0, 239) == 0x11 && pixel(&ppu, 248, 239) == 0x0f);
+    }
+    #[test]
+    fn test_greyscale_and_emphasis() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut());
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_BACKGROUND_LEFT | MASK_GREYSCALE | 0xa0, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        // red and blue emphasis on top of color $10
+        assert!(pixel(&ppu, 0, 0) == 0x0150);
+    }
+}
\ No newline at end of file
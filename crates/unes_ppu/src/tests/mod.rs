@@ -0,0 +1,45 @@
+mod background;
+mod nametables;
+mod quirks;
+mod registers;
+mod sprites;
+mod timing;
+
+#[cfg(test)]
+use alloc::vec::Vec;
+#[cfg(test)]
+use unes_cartridge::{Cartridge, Mapper};
+#[cfg(test)]
+use crate::PPU;
+
+// NROM with 8KB of chr ram and vertical mirroring
+#[cfg(test)]
+pub fn cartridge() -> Cartridge {
+    cartridge_with(0, 0x01)
+}
+// 16KB of blank prg rom and chr ram
+#[cfg(test)]
+pub fn cartridge_with(mapper: u8, flags_6: u8) -> Cartridge {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&[b'N', b'E', b'S', 0x1a, 1, 0, mapper << 4 | flags_6, mapper & 0xf0]);
+    bytes.extend_from_slice(&[0; 8]);
+    bytes.extend((0..0x4000).map(|_| 0));
+    Cartridge::from_bytes(&bytes).unwrap()
+}
+
+// writes through $2006/$2007, leaving the address at zero
+#[cfg(test)]
+pub fn fill(ppu: &mut PPU, mapper: &mut dyn Mapper, addr: u16, bytes: &[u8]) {
+    ppu.write_register(0x2006, (addr >> 8) as u8, mapper);
+    ppu.write_register(0x2006, addr as u8, mapper);
+    for &byte in bytes { ppu.write_register(0x2007, byte, mapper) }
+    ppu.write_register(0x2006, 0, mapper);
+    ppu.write_register(0x2006, 0, mapper);
+}
+
+// runs up to the start of the next frame
+#[cfg(test)]
+pub fn run_frame(ppu: &mut PPU, mapper: &mut dyn Mapper) {
+    let frame = ppu.frame;
+    while ppu.frame == frame { ppu.tick(mapper) }
+}
\ No newline at end of file
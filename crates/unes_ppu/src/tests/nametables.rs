@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use unes_cartridge::Mapper;
+
+    use crate::PPU;
+    use crate::tests::{cartridge_with, fill};
+    use crate::vram::{decode, Target};
+
+    // one distinct byte at the start of each logical nametable
+    fn tag(ppu: &mut PPU, mapper: &mut dyn Mapper) -> [u8; 4] {
+        for table in 0..4 { fill(ppu, mapper, 0x2000 + table * 0x400, &[table as u8 + 1]) }
+        let mut tables = [0; 4];
+        for (table, value) in tables.iter_mut().enumerate() {
+            *value = ppu.read_vram(0x2000 + table as u16 * 0x400, mapper);
+        }
+        tables
+    }
+
+    #[test]
+    fn test_decode() {
+        assert!(decode(0x1fff) == Target::Pattern(0x1fff));
+        assert!(decode(0x2c05) == Target::Nametable(0x2c05));
+        assert!(decode(0x3c05) == Target::Nametable(0x2c05));
+        assert!(decode(0x3f14) == Target::Palette(0x04));
+        assert!(decode(0x3f35) == Target::Palette(0x15));
+        // 14 bit addresses
+        assert!(decode(0x6123) == Target::Nametable(0x2123));
+    }
+    #[test]
+    fn test_header_mirroring() {
+        let mut cart = cartridge_with(0, 0x00);
+        let mut ppu = PPU::new();
+        assert!(tag(&mut ppu, cart.mapper.as_mut()) == [2, 2, 4, 4]);
+        let mut cart = cartridge_with(0, 0x01);
+        let mut ppu = PPU::new();
+        assert!(tag(&mut ppu, cart.mapper.as_mut()) == [3, 4, 3, 4]);
+    }
+    #[test]
+    fn test_four_screen() {
+        let mut cart = cartridge_with(0, 0x09);
+        let mut ppu = PPU::new();
+        assert!(tag(&mut ppu, cart.mapper.as_mut()) == [1, 2, 3, 4]);
+        // the upper two are not in the console
+        assert!(ppu.ciram[0] == 1 && ppu.ciram[0x400] == 2);
+        assert!(ppu.read_vram(0x3800, cart.mapper.as_mut()) == 3);
+    }
+    #[test]
+    fn test_runtime_mirroring() {
+        // FME7, command $c selects the mirroring
+        let mut cart = cartridge_with(69, 0x00);
+        let mut ppu = PPU::new();
+        let mapper = cart.mapper.as_mut();
+        fill(&mut ppu, mapper, 0x2000, &[0xaa]);
+        fill(&mut ppu, mapper, 0x2400, &[0xbb]);
+        mapper.cpu_write(0x8000, 0x0c);
+        mapper.cpu_write(0xa000, 1);
+        assert!(ppu.read_vram(0x2400, mapper) == 0xaa && ppu.read_vram(0x2800, mapper) == 0xbb);
+        mapper.cpu_write(0xa000, 3);
+        assert!(ppu.read_vram(0x2000, mapper) == 0xbb && ppu.read_vram(0x2c00, mapper) == 0xbb);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use crate::PPU;
+    use crate::flags::*;
+    use crate::tests::{cartridge, fill, run_frame};
+
+    const FRAME_DOTS: usize = 341 * 262;
+
+    #[test]
+    fn test_odd_frame_skip() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2001, MASK_BACKGROUND, cart.mapper.as_mut());
+        for _ in 0..FRAME_DOTS { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.frame == 1 && (ppu.scanline, ppu.dot) == (0, 0));
+        // frame 1 is a dot short
+        for _ in 0..FRAME_DOTS - 1 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.frame == 2 && (ppu.scanline, ppu.dot) == (0, 0));
+        // not without rendering
+        ppu.write_register(0x2001, 0, cart.mapper.as_mut());
+        for _ in 0..FRAME_DOTS * 2 - 1 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.frame == 3 && (ppu.scanline, ppu.dot) == (261, 340));
+    }
+    #[test]
+    fn test_vblank_read_race() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2000, CTRL_NMI, cart.mapper.as_mut());
+        while (ppu.scanline, ppu.dot) != (241, 1) { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.read_register(0x2002, cart.mapper.as_mut()) & STATUS_VBLANK == 0);
+        // neither the flag nor the nmi for this frame
+        for _ in 0..341 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.status & STATUS_VBLANK == 0 && !ppu.nmi_line());
+        // a dot later the flag is read and cleared
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        while (ppu.scanline, ppu.dot) != (241, 2) { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.nmi_line());
+        assert!(ppu.read_register(0x2002, cart.mapper.as_mut()) & STATUS_VBLANK != 0);
+        assert!(!ppu.nmi_line());
+    }
+    #[test]
+    fn test_nmi_enable_during_vblank() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        while ppu.scanline != 245 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(!ppu.nmi_line());
+        // every enable while the flag is up is a new rising edge
+        ppu.write_register(0x2000, CTRL_NMI, cart.mapper.as_mut());
+        assert!(ppu.nmi_line());
+        ppu.write_register(0x2000, 0, cart.mapper.as_mut());
+        assert!(!ppu.nmi_line());
+        ppu.write_register(0x2000, CTRL_NMI, cart.mapper.as_mut());
+        assert!(ppu.nmi_line());
+    }
+    #[test]
+    fn test_open_bus_decay() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2000, 0xff, cart.mapper.as_mut());
+        // the low bits of the status are open bus and refresh nothing
+        assert!(ppu.read_register(0x2002, cart.mapper.as_mut()) == 0x1f);
+        for _ in 0..30 { run_frame(&mut ppu, cart.mapper.as_mut()) }
+        ppu.status = STATUS_VBLANK | STATUS_SPRITE_ZERO;
+        assert!(ppu.read_register(0x2002, cart.mapper.as_mut()) == 0xdf);
+        for _ in 0..10 { run_frame(&mut ppu, cart.mapper.as_mut()) }
+        // the status bits were driven later
+        assert!(ppu.read_register(0x2005, cart.mapper.as_mut()) == 0xc0);
+    }
+    #[test]
+    fn test_palette_read_greyscale() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        fill(&mut ppu, cart.mapper.as_mut(), 0x3f01, &[0x2c]);
+        ppu.write_register(0x2001, MASK_GREYSCALE, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x3f, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x01, cart.mapper.as_mut());
+        assert!(ppu.read_register(0x2007, cart.mapper.as_mut()) == 0x20);
+    }
+    #[test]
+    fn test_oam_attribute_bits() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2003, 0x02, cart.mapper.as_mut());
+        ppu.write_register(0x2004, 0xff, cart.mapper.as_mut());
+        ppu.write_register(0x2004, 0xff, cart.mapper.as_mut());
+        assert!(ppu.oam[2] == 0xe3 && ppu.oam[3] == 0xff);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use crate::PPU;
+    use crate::flags::*;
+    use crate::tests::cartridge;
+
+    #[test]
+    fn test_scroll_writes() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2000, 0b11, cart.mapper.as_mut());
+        ppu.write_register(0x2005, 0x7d, cart.mapper.as_mut());
+        assert!(ppu.x == 5 && ppu.w);
+        ppu.write_register(0x2005, 0x5e, cart.mapper.as_mut());
+        // fine y 6, nametable 3, coarse y 11, coarse x 15
+        assert!(ppu.t == 0x6d6f && !ppu.w);
+    }
+    #[test]
+    fn test_addr_writes() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // the top bits of the high byte are dropped
+        ppu.write_register(0x2006, 0xff, cart.mapper.as_mut());
+        assert!(ppu.t == 0x3f00 && ppu.v == 0);
+        ppu.write_register(0x2006, 0x12, cart.mapper.as_mut());
+        assert!(ppu.v == 0x3f12);
+    }
+    #[test]
+    fn test_shared_toggle() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2005, 0x00, cart.mapper.as_mut());
+        // the second write goes to the low byte of t
+        ppu.write_register(0x2006, 0x21, cart.mapper.as_mut());
+        assert!(ppu.v == 0x21);
+        // reading the status resets the toggle
+        ppu.write_register(0x2006, 0x23, cart.mapper.as_mut());
+        ppu.read_register(0x2002, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x24, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x56, cart.mapper.as_mut());
+        assert!(ppu.v == 0x2456);
+    }
+    #[test]
+    fn test_buffered_reads() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2006, 0x20, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x00, cart.mapper.as_mut());
+        for value in [0x11, 0x22, 0x33] {
+            ppu.write_register(0x2007, value, cart.mapper.as_mut());
+        }
+        ppu.write_register(0x2006, 0x20, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x00, cart.mapper.as_mut());
+        ppu.read_register(0x2007, cart.mapper.as_mut());
+        assert!(ppu.read_register(0x2007, cart.mapper.as_mut()) == 0x11);
+        assert!(ppu.read_register(0x2007, cart.mapper.as_mut()) == 0x22);
+        // vertical mirroring, $2800 is $2000
+        ppu.write_register(0x2006, 0x28, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x02, cart.mapper.as_mut());
+        ppu.read_register(0x2007, cart.mapper.as_mut());
+        assert!(ppu.read_register(0x2007, cart.mapper.as_mut()) == 0x33);
+    }
+    #[test]
+    fn test_increment_32() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2000, CTRL_INCREMENT, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x20, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x00, cart.mapper.as_mut());
+        ppu.write_register(0x2007, 1, cart.mapper.as_mut());
+        ppu.write_register(0x2007, 2, cart.mapper.as_mut());
+        assert!(ppu.v == 0x2040);
+        assert!(ppu.ciram[0x20] == 2);
+    }
+    #[test]
+    fn test_pattern_table_access() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2006, 0x10, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x00, cart.mapper.as_mut());
+        ppu.write_register(0x2007, 0xab, cart.mapper.as_mut());
+        assert!(cart.mapper.ppu_read(0x1000) == 0xab);
+    }
+    #[test]
+    fn test_palette() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2006, 0x3f, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x10, cart.mapper.as_mut());
+        ppu.write_register(0x2007, 0xff, cart.mapper.as_mut());
+        // $3f10 mirrors $3f00, values are 6 bits
+        assert!(ppu.palette[0] == 0x3f);
+        // whole palette mirrored up to $3fff, reads are not buffered
+        // and the top two bits are open bus (the $e0 written last)
+        ppu.write_register(0x2006, 0x3f, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0xe0, cart.mapper.as_mut());
+        assert!(ppu.read_register(0x2007, cart.mapper.as_mut()) == 0xff);
+        ppu.write_register(0x2006, 0x3f, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x04, cart.mapper.as_mut());
+        ppu.write_register(0x2007, 0x05, cart.mapper.as_mut());
+        assert!(ppu.palette[0x14] == 0 && ppu.palette[4] == 5);
+    }
+    #[test]
+    fn test_palette_read_fills_buffer() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // $2f00 is in the second ciram page
+        ppu.ciram[0x700] = 0x77;
+        ppu.write_register(0x2006, 0x3f, cart.mapper.as_mut());
+        ppu.write_register(0x2006, 0x00, cart.mapper.as_mut());
+        ppu.read_register(0x2007, cart.mapper.as_mut());
+        assert!(ppu.read_buffer == 0x77);
+    }
+    #[test]
+    fn test_oam() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2003, 0xff, cart.mapper.as_mut());
+        ppu.write_register(0x2004, 1, cart.mapper.as_mut());
+        ppu.write_register(0x2004, 2, cart.mapper.as_mut());
+        assert!(ppu.oam[0xff] == 1 && ppu.oam[0] == 2);
+        // reads do not increment the address
+        ppu.write_register(0x2003, 0xff, cart.mapper.as_mut());
+        assert!(ppu.read_register(0x2004, cart.mapper.as_mut()) == 1);
+        assert!(ppu.read_register(0x2004, cart.mapper.as_mut()) == 1);
+    }
+    #[test]
+    fn test_status_read() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.status = STATUS_VBLANK | STATUS_SPRITE_ZERO;
+        // the low bits come from the data bus
+        ppu.write_register(0x2000, 0x1f, cart.mapper.as_mut());
+        assert!(ppu.read_register(0x3ffa, cart.mapper.as_mut()) == 0xdf);
+        assert!(ppu.status == STATUS_SPRITE_ZERO);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use unes_cartridge::Mapper;
+
+    use crate::{PPU, WIDTH};
+    use crate::flags::*;
+    use crate::tests::{cartridge, fill, run_frame};
+
+    // tile 1 solid color 1, tile 2 solid color 2, tile 3 a single top left dot
+    fn setup(ppu: &mut PPU, mapper: &mut dyn Mapper, oam: &[u8]) {
+        fill(ppu, mapper, 0x0010, &[0xff; 8]);
+        fill(ppu, mapper, 0x0028, &[0xff; 8]);
+        fill(ppu, mapper, 0x0030, &[0x80]);
+        fill(ppu, mapper, 0x3f00, &[0x0f, 0x11]);
+        fill(ppu, mapper, 0x3f11, &[0x30, 0x31]);
+        ppu.oam = [0xff; 256];
+        ppu.oam[..oam.len()].copy_from_slice(oam);
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_SPRITES | MASK_BACKGROUND_LEFT | MASK_SPRITES_LEFT, mapper);
+    }
+    fn pixel(ppu: &PPU, x: usize, y: usize) -> u16 {
+        ppu.framebuffer[y * WIDTH + x]
+    }
+    // runs the visible part of the next frame
+    fn render(ppu: &mut PPU, mapper: &mut dyn Mapper) {
+        run_frame(ppu, mapper);
+        while ppu.scanline != 240 { ppu.tick(mapper) }
+    }
+
+    #[test]
+    fn test_sprite_position() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // drawn one line below its y coordinate
+        setup(&mut ppu, cart.mapper.as_mut(), &[49, 1, 0, 100]);
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 100, 50) == 0x30 && pixel(&ppu, 107, 57) == 0x30);
+        assert!(pixel(&ppu, 99, 50) == 0x0f && pixel(&ppu, 108, 50) == 0x0f);
+        assert!(pixel(&ppu, 100, 49) == 0x0f && pixel(&ppu, 100, 58) == 0x0f);
+    }
+    #[test]
+    fn test_right_edge() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // does not wrap around to the left side
+        setup(&mut ppu, cart.mapper.as_mut(), &[49, 1, 0, 252]);
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 255, 50) == 0x30 && pixel(&ppu, 0, 50) == 0x0f);
+    }
+    #[test]
+    fn test_flip() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut(), &[9, 3, 0, 10, 29, 3, 0x40, 10, 49, 3, 0x80, 10, 69, 3, 0xc1, 10]);
+        fill(&mut ppu, cart.mapper.as_mut(), 0x3f15, &[0x25]);
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 10, 10) == 0x30);
+        assert!(pixel(&ppu, 17, 30) == 0x30 && pixel(&ppu, 10, 30) == 0x0f);
+        assert!(pixel(&ppu, 10, 57) == 0x30 && pixel(&ppu, 10, 50) == 0x0f);
+        // both, with palette 1
+        assert!(pixel(&ppu, 17, 77) == 0x25);
+    }
+    #[test]
+    fn test_sprite_priority() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // the first sprite in oam wins, even when it is behind the background
+        setup(&mut ppu, cart.mapper.as_mut(), &[49, 1, 0x20, 100, 49, 2, 0, 100, 97, 1, 0x20, 100, 97, 2, 0, 100]);
+        // background row 12 covers lines 96-103
+        fill(&mut ppu, cart.mapper.as_mut(), 0x2180, &[1; 32]);
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 100, 50) == 0x30);
+        assert!(pixel(&ppu, 100, 100) == 0x11);
+        // in front of it
+        ppu.oam[10] = 0;
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 100, 100) == 0x30);
+    }
+    #[test]
+    fn test_sprite_zero_hit() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        setup(&mut ppu, cart.mapper.as_mut(), &[49, 1, 0x20, 100]);
+        render(&mut ppu, cart.mapper.as_mut());
+        // transparent background
+        assert!(ppu.status & STATUS_SPRITE_ZERO == 0);
+        fill(&mut ppu, cart.mapper.as_mut(), 0x20c0, &[1; 32]);
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        while ppu.status & STATUS_SPRITE_ZERO == 0 && ppu.scanline < 240 { ppu.tick(cart.mapper.as_mut()) }
+        // set at the first overlapping pixel, behind the background or not
+        assert!((ppu.scanline, ppu.dot) == (50, 102));
+        // cleared on the pre-render line
+        run_frame(&mut ppu, cart.mapper.as_mut());
+        assert!(ppu.status & STATUS_SPRITE_ZERO == 0);
+    }
+    #[test]
+    fn test_sprite_zero_edge_cases() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // only the last column overlaps
+        setup(&mut ppu, cart.mapper.as_mut(), &[49, 3, 0x40, 248]);
+        fill(&mut ppu, cart.mapper.as_mut(), 0x20c0, &[1; 32]);
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 255, 50) == 0x30 && ppu.status & STATUS_SPRITE_ZERO == 0);
+        // clipped in the left column
+        ppu.oam[3] = 0;
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_SPRITES, cart.mapper.as_mut());
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(ppu.status & STATUS_SPRITE_ZERO == 0);
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_SPRITES | MASK_SPRITES_LEFT, cart.mapper.as_mut());
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(ppu.status & STATUS_SPRITE_ZERO == 0);
+        ppu.write_register(0x2001, MASK_BACKGROUND | MASK_SPRITES | MASK_SPRITES_LEFT | MASK_BACKGROUND_LEFT, cart.mapper.as_mut());
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(ppu.status & STATUS_SPRITE_ZERO != 0);
+    }
+    #[test]
+    fn test_sprite_limit() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        let oam: Vec<u8> = (0..9).flat_map(|i| [49, 1, 0, i * 8]).collect();
+        setup(&mut ppu, cart.mapper.as_mut(), &oam);
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 56, 50) == 0x30 && pixel(&ppu, 64, 50) == 0x0f);
+        assert!(ppu.status & STATUS_OVERFLOW != 0);
+        // 8 sprites are fine
+        ppu.oam[32] = 0xff;
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(ppu.status & STATUS_OVERFLOW == 0);
+    }
+    #[test]
+    fn test_overflow_bug() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // after 8 sprites the tile number of sprite 9 is read as its y coordinate
+        let mut oam: Vec<u8> = (0..8).flat_map(|i| [40, 1, 0, i * 8]).collect();
+        oam.extend_from_slice(&[200, 0, 0, 0, 200, 45, 0, 0]);
+        setup(&mut ppu, cart.mapper.as_mut(), &oam);
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(ppu.status & STATUS_OVERFLOW != 0);
+    }
+    #[test]
+    fn test_tall_sprites() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // tile 3 selects tiles 2 and 3 of the second pattern table
+        fill(&mut ppu, cart.mapper.as_mut(), 0x1020, &[0xff; 8]);
+        fill(&mut ppu, cart.mapper.as_mut(), 0x1038, &[0xff; 8]);
+        setup(&mut ppu, cart.mapper.as_mut(), &[49, 3, 0, 100, 99, 3, 0x80, 100]);
+        ppu.write_register(0x2000, CTRL_SPRITE_SIZE, cart.mapper.as_mut());
+        render(&mut ppu, cart.mapper.as_mut());
+        assert!(pixel(&ppu, 100, 50) == 0x30 && pixel(&ppu, 100, 58) == 0x31 && pixel(&ppu, 100, 66) == 0x0f);
+        // flipped vertically as a whole
+        assert!(pixel(&ppu, 100, 100) == 0x31 && pixel(&ppu, 100, 108) == 0x30);
+    }
+}
\ No newline at end of file
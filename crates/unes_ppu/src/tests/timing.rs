@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use crate::{Layout, PPU};
+    use crate::flags::*;
+    use crate::tests::cartridge;
+
+    const FRAME_DOTS: usize = 341 * 262;
+
+    #[test]
+    fn test_vblank() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        // up to dot 1 of scanline 241
+        for _ in 0..241 * 341 + 1 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.status & STATUS_VBLANK == 0);
+        ppu.tick(cart.mapper.as_mut());
+        assert!(ppu.status & STATUS_VBLANK != 0);
+        // cleared on dot 1 of the pre-render line
+        for _ in 0..20 * 341 - 1 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.status & STATUS_VBLANK != 0);
+        ppu.status |= STATUS_SPRITE_ZERO | STATUS_OVERFLOW;
+        ppu.tick(cart.mapper.as_mut());
+        assert!(ppu.status == 0);
+    }
+    #[test]
+    fn test_frame_length() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        for _ in 0..FRAME_DOTS - 1 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.frame == 0 && ppu.scanline == 261 && ppu.dot == 340);
+        ppu.tick(cart.mapper.as_mut());
+        assert!(ppu.frame == 1 && ppu.scanline == 0 && ppu.dot == 0);
+    }
+    #[test]
+    fn test_nmi_line() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.write_register(0x2000, CTRL_NMI, cart.mapper.as_mut());
+        for _ in 0..241 * 341 + 2 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.nmi_line());
+        // disabling nmi or reading the status drops the line
+        ppu.write_register(0x2000, 0, cart.mapper.as_mut());
+        assert!(!ppu.nmi_line());
+        ppu.write_register(0x2000, CTRL_NMI, cart.mapper.as_mut());
+        assert!(ppu.nmi_line());
+        ppu.read_register(0x2002, cart.mapper.as_mut());
+        assert!(!ppu.nmi_line());
+    }
+    #[test]
+    fn test_pal_frame() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.layout = Layout::PAL;
+        ppu.mask = MASK_BACKGROUND;
+        // 312 full lines, odd frames included
+        for _ in 0..2 * 341 * 312 - 1 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.frame == 1 && ppu.scanline == 311 && ppu.dot == 340);
+        ppu.tick(cart.mapper.as_mut());
+        assert!(ppu.frame == 2);
+        // vblank still starts right after the picture
+        for _ in 0..241 * 341 + 2 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.status & STATUS_VBLANK != 0);
+    }
+    #[test]
+    fn test_dendy_vblank() {
+        let mut cart = cartridge();
+        let mut ppu = PPU::new();
+        ppu.layout = Layout::DENDY;
+        for _ in 0..291 * 341 + 1 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.status & STATUS_VBLANK == 0);
+        ppu.tick(cart.mapper.as_mut());
+        assert!(ppu.status & STATUS_VBLANK != 0);
+        // and cleared on the pre-render line, 20 lines later
+        for _ in 0..20 * 341 { ppu.tick(cart.mapper.as_mut()) }
+        assert!(ppu.status & STATUS_VBLANK == 0 && ppu.scanline == 311);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,26 @@
+// what a ppu address selects, the 14 bit address space is mirrored above $4000
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    // pattern tables, on the cartridge
+    Pattern(u16),
+    // $2000-$2fff (also seen at $3000-$3eff), the mapper routes it to
+    // the ciram with its current mirroring or to memory of its own
+    Nametable(u16),
+    // index into the 32 bytes of palette ram
+    Palette(usize)
+}
+
+pub fn decode(addr: u16) -> Target {
+    let addr = addr & 0x3fff;
+    match addr {
+        0x0000..=0x1fff => Target::Pattern(addr),
+        0x2000..=0x3eff => Target::Nametable(0x2000 | addr & 0x0fff),
+        _ => Target::Palette(palette_index(addr as usize))
+    }
+}
+
+// $3f10/$3f14/$3f18/$3f1c mirror the backdrop entries of the background palettes
+pub fn palette_index(addr: usize) -> usize {
+    let index = addr & 0x1f;
+    if index & 0x13 == 0x10 { index & 0x0f } else { index }
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+// a chunk tag and the version its current layout is written at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    pub tag: [u8; 4],
+    pub version: u16
+}
+impl Chunk {
+    // cpu registers, flags and interrupt lines
+    pub const CPU: Chunk = Chunk { tag: *b"CPU ", version: 1 };
+    // the flat 64KB memory of a bare cpu
+    pub const MEMORY: Chunk = Chunk { tag: *b"MEM ", version: 1 };
+    // console ram, open bus, pending dma and the nmi edge
+    pub const BUS: Chunk = Chunk { tag: *b"BUS ", version: 3 };
+    // registers, oam, nametables, palette, shifters and the beam position
+    pub const PPU: Chunk = Chunk { tag: *b"PPU ", version: 1 };
+    // every channel and the frame counter
+    pub const APU: Chunk = Chunk { tag: *b"APU ", version: 1 };
+    // mapper registers, cartridge ram, irq counters and expansion audio
+    pub const CARTRIDGE: Chunk = Chunk { tag: *b"CART", version: 1 };
+    // the devices plugged into the controller and expansion ports
+    pub const INPUT: Chunk = Chunk { tag: *b"INPT", version: 1 };
+    // region, master clock and what the state was taken from
+    pub const CONSOLE: Chunk = Chunk { tag: *b"CONS", version: 1 };
+}
\ No newline at end of file
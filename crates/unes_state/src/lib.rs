@@ -0,0 +1,32 @@
+#![no_std]
+extern crate alloc;
+
+// Save state format, every value little endian:
+//
+//   header  "UNES", u16 format version
+//   chunk   4 byte tag, u16 chunk version, u32 payload length, payload
+//
+// Each component (cpu, ppu, apu, cartridge...) writes its own chunk, made of
+// its fields in declaration order: integers at their width, bools as a byte,
+// arrays element by element and variable sized memory (cartridge ram) as a
+// u32 length followed by the bytes. The chunks below are versioned on their
+// own: a component changing its layout bumps the version of its chunk and
+// keeps reading the older ones through `StateReader::version`. Readers skip
+// the chunks they do not know, states from newer builds are refused.
+//
+// The framebuffer and the audio buffers are output, not state: the next
+// frame redraws them.
+
+mod chunk;
+mod reader;
+mod snapshot;
+mod tests;
+mod writer;
+
+pub use chunk::Chunk;
+pub use reader::{State, StateError, StateReader};
+pub use snapshot::Snapshot;
+pub use writer::StateWriter;
+
+pub const MAGIC: [u8; 4] = *b"UNES";
+pub const FORMAT_VERSION: u16 = 1;
\ No newline at end of file
@@ -0,0 +1,116 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::chunk::Chunk;
+use crate::{FORMAT_VERSION, MAGIC};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateError {
+    InvalidHeader,
+    // written by a newer build, the tag is that of the chunk (or the magic)
+    UnsupportedVersion([u8; 4], u16),
+    MissingChunk([u8; 4]),
+    Truncated,
+    // a value out of range or memory of the wrong size
+    InvalidData,
+    // taken with another game
+    CartridgeMismatch
+}
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "not a save state"),
+            Self::UnsupportedVersion(chunk, version) => write!(f, "{} version {} is not supported", name(chunk), version),
+            Self::MissingChunk(chunk) => write!(f, "the state has no {} chunk", name(chunk)),
+            Self::Truncated => write!(f, "save state is truncated"),
+            Self::InvalidData => write!(f, "save state is corrupted"),
+            Self::CartridgeMismatch => write!(f, "save state is for another game")
+        }
+    }
+}
+
+fn name(tag: &[u8; 4]) -> &str {
+    core::str::from_utf8(tag).unwrap_or("????").trim_end()
+}
+
+// the chunks of a save state
+pub struct State<'a> {
+    pub version: u16,
+    chunks: Vec<([u8; 4], u16, &'a [u8])>
+}
+impl<'a> State<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<State<'a>, StateError> {
+        if data.len() < 6 || data[..4] != MAGIC { return Err(StateError::InvalidHeader) }
+        let mut reader = StateReader::new(&data[4..], 0);
+        let version = reader.read_u16()?;
+        if version > FORMAT_VERSION { return Err(StateError::UnsupportedVersion(MAGIC, version)) }
+        let mut chunks = Vec::new();
+        while !reader.is_empty() {
+            let mut tag = [0; 4];
+            reader.read_bytes(&mut tag)?;
+            let version = reader.read_u16()?;
+            let len = reader.read_u32()? as usize;
+            chunks.push((tag, version, reader.take(len)?));
+        }
+        Ok(State { version, chunks })
+    }
+    pub fn has(&self, chunk: Chunk) -> bool {
+        self.chunks.iter().any(|(tag, ..)| *tag == chunk.tag)
+    }
+    // a reader over the payload, refused when written at a newer version than `chunk`
+    pub fn chunk(&self, chunk: Chunk) -> Result<StateReader<'a>, StateError> {
+        let &(tag, version, data) = self.chunks.iter().find(|(tag, ..)| *tag == chunk.tag)
+            .ok_or(StateError::MissingChunk(chunk.tag))?;
+        if version > chunk.version { return Err(StateError::UnsupportedVersion(tag, version)) }
+        Ok(StateReader::new(data, version))
+    }
+}
+
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    // of the chunk being read, for loading older layouts
+    pub version: u16
+}
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [u8], version: u16) -> StateReader<'a> {
+        StateReader { data, position: 0, version }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.position == self.data.len()
+    }
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        if self.data.len() - self.position < len { return Err(StateError::Truncated) }
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+    pub fn read_u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+    pub fn read_u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    pub fn read_u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    pub fn read_u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+    pub fn read_bool(&mut self) -> Result<bool, StateError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(StateError::InvalidData)
+        }
+    }
+    pub fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), StateError> {
+        bytes.copy_from_slice(self.take(bytes.len())?);
+        Ok(())
+    }
+    // length prefixed bytes, the length has to match
+    pub fn read_block(&mut self, bytes: &mut [u8]) -> Result<(), StateError> {
+        if self.read_u32()? as usize != bytes.len() { return Err(StateError::InvalidData) }
+        self.read_bytes(bytes)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,119 @@
+use alloc::vec::Vec;
+
+use crate::reader::{StateError, StateReader};
+use crate::writer::StateWriter;
+
+// something that goes into a chunk, `load` reads back what `save` wrote
+pub trait Snapshot {
+    fn save(&self, w: &mut StateWriter);
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError>;
+}
+
+impl Snapshot for u8 {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u8(*self);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = r.read_u8()?;
+        Ok(())
+    }
+}
+impl Snapshot for u16 {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u16(*self);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = r.read_u16()?;
+        Ok(())
+    }
+}
+impl Snapshot for u32 {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u32(*self);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = r.read_u32()?;
+        Ok(())
+    }
+}
+impl Snapshot for u64 {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u64(*self);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = r.read_u64()?;
+        Ok(())
+    }
+}
+impl Snapshot for i16 {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u16(*self as u16);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = r.read_u16()? as i16;
+        Ok(())
+    }
+}
+impl Snapshot for i32 {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u32(*self as u32);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = r.read_u32()? as i32;
+        Ok(())
+    }
+}
+// as a u32, indexes and counters stay small
+impl Snapshot for usize {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_u32(*self as u32);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = r.read_u32()? as usize;
+        Ok(())
+    }
+}
+impl Snapshot for bool {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_bool(*self);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = r.read_bool()?;
+        Ok(())
+    }
+}
+impl<T: Snapshot, const N: usize> Snapshot for [T; N] {
+    fn save(&self, w: &mut StateWriter) {
+        for value in self { value.save(w) }
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        for value in self { value.load(r)? }
+        Ok(())
+    }
+}
+// a flag, then the value when there is one
+impl<T: Snapshot + Default> Snapshot for Option<T> {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_bool(self.is_some());
+        if let Some(value) = self { value.save(w) }
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        *self = if r.read_bool()? {
+            let mut value = T::default();
+            value.load(r)?;
+            Some(value)
+        } else {
+            None
+        };
+        Ok(())
+    }
+}
+// memory sized by the cartridge, a block of another size is refused
+impl Snapshot for Vec<u8> {
+    fn save(&self, w: &mut StateWriter) {
+        w.write_block(self);
+    }
+    fn load(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        r.read_block(self)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{Chunk, Snapshot, State, StateError, StateWriter};
+
+    const TEST: Chunk = Chunk { tag: *b"TEST", version: 2 };
+
+    #[test]
+    fn test_round_trip() {
+        let mut w = StateWriter::new();
+        w.chunk(TEST, |w| {
+            0x1234u16.save(w);
+            [true, false].save(w);
+            Some(-5i32).save(w);
+            vec![1u8, 2, 3].save(w);
+        });
+        let data = w.finish();
+        assert!(data[..10] == *b"UNES\x01\x00TEST");
+        let state = State::parse(&data).unwrap();
+        let mut r = state.chunk(TEST).unwrap();
+        let (mut a, mut b, mut c, mut d) = (0u16, [false; 2], None::<i32>, vec![0u8; 3]);
+        a.load(&mut r).unwrap();
+        b.load(&mut r).unwrap();
+        c.load(&mut r).unwrap();
+        d.load(&mut r).unwrap();
+        assert!(a == 0x1234 && b == [true, false] && c == Some(-5) && d == [1, 2, 3]);
+        assert!(r.is_empty() && r.version == 2);
+    }
+    #[test]
+    fn test_chunks() {
+        let mut w = StateWriter::new();
+        w.chunk(Chunk { tag: *b"NEW ", version: 7 }, |w| w.write_u32(1));
+        w.chunk(TEST, |w| w.write_u8(9));
+        let data = w.finish();
+        let state = State::parse(&data).unwrap();
+        // unknown chunks are skipped over
+        assert!(state.chunk(TEST).unwrap().read_u8() == Ok(9));
+        assert!(state.chunk(Chunk::CPU).err() == Some(StateError::MissingChunk(*b"CPU ")));
+        // a layout newer than the one known is refused
+        let old = Chunk { tag: *b"NEW ", version: 6 };
+        assert!(state.chunk(old).err() == Some(StateError::UnsupportedVersion(*b"NEW ", 7)));
+    }
+    #[test]
+    fn test_invalid() {
+        assert!(State::parse(b"NES\x1a\x01\x00").err() == Some(StateError::InvalidHeader));
+        assert!(State::parse(b"UNES\x02\x00").err() == Some(StateError::UnsupportedVersion(*b"UNES", 2)));
+        let mut w = StateWriter::new();
+        w.chunk(TEST, |w| w.write_u8(2));
+        w.chunk(Chunk::CARTRIDGE, |w| w.write_block(&[0; 2]));
+        let data = w.finish();
+        assert!(State::parse(&data[..data.len() - 1]).err() == Some(StateError::Truncated));
+        // bools are 0 or 1 and memory keeps its size
+        let state = State::parse(&data).unwrap();
+        assert!(state.chunk(TEST).unwrap().read_bool() == Err(StateError::InvalidData));
+        let mut ram = vec![0u8; 4];
+        assert!(ram.load(&mut state.chunk(Chunk::CARTRIDGE).unwrap()) == Err(StateError::InvalidData));
+    }
+}
\ No newline at end of file
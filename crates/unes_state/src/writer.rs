@@ -0,0 +1,58 @@
+use alloc::vec::Vec;
+
+use crate::chunk::Chunk;
+use crate::{FORMAT_VERSION, MAGIC};
+
+pub struct StateWriter {
+    data: Vec<u8>
+}
+impl Default for StateWriter {
+    fn default() -> Self {
+        let mut writer = StateWriter { data: Vec::new() };
+        writer.write_bytes(&MAGIC);
+        writer.write_u16(FORMAT_VERSION);
+        writer
+    }
+}
+impl StateWriter {
+    pub fn new() -> StateWriter {
+        StateWriter::default()
+    }
+    // a chunk holding whatever `f` writes
+    pub fn chunk(&mut self, chunk: Chunk, f: impl FnOnce(&mut StateWriter)) {
+        self.write_bytes(&chunk.tag);
+        self.write_u16(chunk.version);
+        let start = self.data.len();
+        self.write_u32(0);
+        f(self);
+        let len = (self.data.len() - start - 4) as u32;
+        self.data[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    }
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+    // raw bytes, the reader has to know how many
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+    // length prefixed bytes
+    pub fn write_block(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.write_bytes(bytes);
+    }
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+}
\ No newline at end of file